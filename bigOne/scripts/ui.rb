@@ -5,32 +5,50 @@
 def handle_button_click(params)
   puts "Bouton cliqué: #{params['id']}"
   
-  # Créer une mise à jour d'UI
+  # Créer une mise à jour d'UI (un hash Ruby suffit)
   {
+    version: 1,
     updates: [
       {
+        action: "set_text",
         id: "result_text",
-        action: "setText",
         value: "Vous avez cliqué sur le bouton #{params['id']} à #{Time.now.strftime('%H:%M:%S')}"
       }
     ]
-  }.to_json
+  }
 end
 
 def handle_input_submit(params)
   value = params['value']
   puts "Input soumis: #{value}"
   
-  # Créer une mise à jour d'UI
-  {
-    updates: [
-      {
-        id: "result_text",
-        action: "setText",
-        value: "Vous avez saisi: #{value}"
-      }
-    ]
-  }.to_json
+  # Mémoriser la dernière saisie (capacité accordée par l'application)
+  capability(:storage)['last_input'] = value
+  
+  # Modifier directement les composants affichés
+  OS.find(:result_text).content = "Vous avez saisi: #{value}"
+  OS.find(params['id']).value = ""
+  nil
+end
+
+# Composants partagés, définis dans scripts/widgets/
+import "widgets/panel"
+
+# Thème de l'application, dérivé du thème sombre
+theme :ocean, extends: :dark do |t|
+  t.color :primary, "#1478c8"
+  t.color "primary-hover", "#2088d8"
+  t.color :header, "#16283a"
+  t.font_size :title, 18
+end
+OS.theme = :ocean
+
+# Styles communs
+stylesheet do
+  rule "#toolbar button:hover", background: "#3a3a3a"
+  rule "button.primary", background: "rgb(40, 120, 60)", padding: "5 12"
+  rule "button.primary:hover", background: "rgb(50, 140, 72)"
+  rule "#nav_list button:pressed", background: "#264d80"
 end
 
 # Fenêtre principale
@@ -38,14 +56,21 @@ window(id: "main_window", title: "Démo OS Like UI", width: 800, height: 600, x:
   # En-tête avec logo et titre
   row(id: "header", spacing: 10) do
     image(id: "logo", source: "assets/logo.png", width: 50, height: 50)
-    text(id: "title", content: "Application de démonstration", size: 24, color: "rgb(240, 240, 255)")
+    svg(id: "badge", width: 24, fill: "$primary",
+        markup: '<svg viewBox="0 0 24 24"><circle cx="12" cy="12" r="10" fill="#000"/></svg>')
+    text(id: "title", content: "Application de démonstration", size: 24)
   end
   
   # Barre d'outils
   row(id: "toolbar", spacing: 5) do
     button(id: "new_btn", text: "Nouveau", on_click: "handle_button_click", icon: "assets/icons/new.png")
     button(id: "open_btn", text: "Ouvrir", on_click: "handle_button_click", icon: "assets/icons/open.png")
-    button(id: "save_btn", text: "Enregistrer", on_click: "handle_button_click", icon: "assets/icons/save.png")
+    button(id: "save_btn", text: "Enregistrer", on_click: "handle_button_click", icon: "assets/icons/save.png",
+           class: "primary")
+    button(id: "theme_btn", text: "Thème clair / sombre", on_click: ->(e) {
+      OS.theme = OS.theme == "light" ? :ocean : :light
+      nil
+    })
   end
   
   # Zone de contenu principale
@@ -63,7 +88,10 @@ window(id: "main_window", title: "Démo OS Like UI", width: 800, height: 600, x:
       
       # Visualisation 3D simple
       text(id: "preview_title", content: "Aperçu 3D", size: 18, color: "rgb(200, 200, 255)")
-      viewport3d(id: "preview_3d", width: 180, height: 150, camera: { position: [0, 3, 5], target: [0, 0, 0] })
+      viewport3d(id: "preview_3d", width: 180, height: 150, camera: { position: [0, 3, 5], target: [0, 0, 0] }) do
+        sphere(id: "preview_sphere", radius: 1, color: "rgb(204, 51, 77)", roughness: 0.5)
+        plane(id: "preview_floor", size: 6, position: [0, -1, 0], color: "rgb(60, 60, 80)")
+      end
     end
     
     # Zone de contenu principale
@@ -85,12 +113,54 @@ window(id: "main_window", title: "Démo OS Like UI", width: 800, height: 600, x:
       # Bouton de soumission
       button(id: "submit_btn", text: "Soumettre", on_click: "handle_button_click")
       
+      # Compteur réactif : seul le texte lié est recalculé à chaque clic
+      clicks = state(0)
+      row(id: "counter_row", spacing: 10) do
+        button(id: "counter_btn", text: "+1", on_click: -> {
+          clicks.value += 1
+          OS.find(:drawing_canvas).invalidate
+        })
+        text(id: "counter_text", content: -> { "Clics : #{clicks.value}" })
+      end
+      
       # Zone de résultat
       text(id: "result_text", content: "Les résultats s'afficheront ici", color: "rgb(180, 180, 255)")
       
-      # Exemple de canvas
-      text(id: "canvas_title", content: "Zone de dessin", size: 18, color: "rgb(200, 200, 255)")
-      canvas(id: "drawing_canvas", width: 400, height: 200)
+      # Exemple de canvas, dans un panneau (IDs internes préfixés : "drawing_panel/title"...)
+      panel(id: "drawing_panel", title: "Zone de dessin") do
+        # Redessiné quand le compteur l'invalide ou qu'il change de taille ;
+        # on y trace à la souris, un double-clic efface le tracé
+        strokes = []
+        canvas(id: "drawing_canvas", width: 400, height: 200,
+               on_pointer_down: ->(e) { strokes << [[e["x"], e["y"]]] if e["button"] == "left" },
+               on_pointer_move: ->(e) {
+                 next unless e["buttons"].include?("left") && strokes.last
+                 strokes.last << [e["x"], e["y"]]
+                 OS.find(:drawing_canvas).invalidate
+               },
+               on_double_click: ->(e) {
+                 strokes.clear
+                 OS.find(:drawing_canvas).invalidate
+               },
+               on_draw: ->(ctx) {
+          ctx.clear("$canvas")
+          bars = clicks.value
+          ctx.text("Clics : #{bars}", 10, 20, color: "$text-muted", size: 14)
+          bars.times do |i|
+            height = 20 + (i * 7) % 100
+            ctx.fill_rect(10 + (i % 20) * 19, 150 - height, 15, height, ctx.linear_gradient(0, 150, 0, 50, 0 => "$primary", 1 => "$primary-hover"))
+          end
+          ctx.path { |p| p.arc(ctx.width - 40, 50, 25, 0, Math::PI * 2 * [bars, 10].min / 10) }
+          ctx.stroke_path("$primary", line_width: 4, cap: :round)
+          strokes.each do |points|
+            ctx.path do |p|
+              p.move_to(*points.first)
+              points.drop(1).each { |point| p.line_to(*point) }
+            end
+            ctx.stroke_path("$text", line_width: 2, cap: :round, join: :round)
+          end
+        })
+      end
     end
   end
   
@@ -106,8 +176,21 @@ window(id: "dialog", title: "Exemple de dialogue", width: 400, height: 200, x: 3
     text(id: "dialog_text", content: "Ceci est un exemple de fenêtre de dialogue.\nVous pouvez la déplacer et la redimensionner.", align: "center")
     
     row(id: "dialog_buttons", spacing: 10, align: "center") do
-      button(id: "dialog_ok", text: "OK", on_click: "handle_button_click")
-      button(id: "dialog_cancel", text: "Annuler", on_click: "handle_button_click")
+      # Handler sous forme de lambda
+      button(id: "dialog_ok", text: "OK", on_click: ->(e) {
+        {
+          version: 1,
+          updates: [
+            { action: "set_text", id: "dialog_text", value: "Confirmé (#{e['id']})" },
+            { action: "close_window", id: "dialog" }
+          ]
+        }
+      })
+      
+      # Handler sous forme de bloc
+      button(id: "dialog_cancel", text: "Annuler") do
+        on(:click) { |e| handle_button_click(e) }
+      end
     end
   end
 end
\ No newline at end of file
@@ -0,0 +1,8 @@
+# scripts/widgets/panel.rb
+# Composant réutilisable : un panneau titré qui accueille le contenu de l'appelant
+component :panel do |title:, &children|
+  column(id: "body", spacing: 5) do
+    text(id: "title", content: title, size: 18, color: "rgb(200, 200, 255)")
+    children.call
+  end
+end
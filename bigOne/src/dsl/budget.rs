@@ -0,0 +1,166 @@
+// src/dsl/budget.rs
+use artichoke::backend::sys;
+use artichoke::prelude::*;
+use std::cell::{Cell, RefCell};
+use std::time::{Duration, Instant};
+
+/// Nombre d'instructions exécutées entre deux lectures de l'horloge
+const CHECK_INTERVAL: u32 = 1024;
+
+/// Temps d'exécution accordé à un script
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExecutionBudget {
+    /// Évaluation complète du script (construction et rechargements)
+    pub initial_build: Duration,
+    /// Appel d'un handler d'événement ou recalcul des liaisons réactives
+    pub handlers: Duration,
+}
+
+impl Default for ExecutionBudget {
+    fn default() -> Self {
+        Self {
+            initial_build: Duration::from_secs(2),
+            handlers: Duration::from_millis(250),
+        }
+    }
+}
+
+/// Marge accordée au-delà du budget avant de considérer l'interpréteur comme bloqué
+const STALL_GRACE: Duration = Duration::from_secs(1);
+
+impl ExecutionBudget {
+    /// Durée sans réponse au-delà de laquelle le thread Ruby est abandonné.
+    ///
+    /// Le chien de garde interrompt le code Ruby bien avant ; ce délai couvre un
+    /// appel bloqué hors de la VM (`sleep`, fonction native), que le hook n'atteint pas.
+    pub fn stall_limit(&self) -> Duration {
+        self.initial_build.max(self.handlers) + STALL_GRACE
+    }
+}
+
+/// Échéance du code Ruby en cours, vérifiée par le hook de l'interpréteur
+struct Deadline {
+    at: Instant,
+    budget: Duration,
+    label: String,
+    /// L'échéance a été dépassée : l'exécution en cours est condamnée
+    expired: bool,
+}
+
+impl Deadline {
+    fn message(&self) -> String {
+        format!(
+            "`{}` a dépassé son budget d'exécution ({} ms) et a été interrompu",
+            self.label,
+            self.budget.as_millis()
+        )
+    }
+}
+
+thread_local! {
+    static DEADLINE: RefCell<Option<Deadline>> = RefCell::new(None);
+    static COUNTER: Cell<u32> = Cell::new(0);
+}
+
+/// Chien de garde de l'interpréteur : le code Ruby qui dépasse son budget est
+/// interrompu par une exception `OS::ExecutionTimeout`.
+///
+/// L'échéance est contrôlée par le hook `code_fetch_hook` de mruby, appelé avant
+/// chaque instruction de la VM : une boucle infinie est donc interrompue elle aussi.
+/// Ce hook n'existe que si mruby est compilé avec `MRB_USE_DEBUG_HOOK`, ce que
+/// `.cargo/config.toml` impose au mruby compilé par Artichoke ; sans cette définition,
+/// `sys::mrb_state` n'a pas de champ `code_fetch_hook` et la compilation échoue.
+/// `WatchdogGuard::check` signale en plus le dépassement à Rust, quelle que soit
+/// la valeur retournée par Ruby.
+pub struct Watchdog;
+
+impl Watchdog {
+    /// Installe le hook sur l'interpréteur (une fois, à la création du parser)
+    pub fn install(interp: &mut Artichoke) -> Result<(), String> {
+        unsafe {
+            interp
+                .with_ffi_boundary(|mrb| (*mrb).code_fetch_hook = Some(code_fetch_hook))
+                .map_err(|e| format!("Installation du chien de garde impossible: {:?}", e))
+        }
+    }
+
+    /// Arme le chien de garde jusqu'à la libération du garde retourné
+    pub fn arm(budget: Duration, label: impl Into<String>) -> WatchdogGuard {
+        let previous = DEADLINE.with(|deadline| {
+            deadline.replace(Some(Deadline {
+                at: Instant::now() + budget,
+                budget,
+                label: label.into(),
+                expired: false,
+            }))
+        });
+        COUNTER.with(|counter| counter.set(0));
+        WatchdogGuard { previous }
+    }
+}
+
+/// Désarme le chien de garde (ou rétablit l'échéance englobante) à sa libération
+pub struct WatchdogGuard {
+    previous: Option<Deadline>,
+}
+
+impl WatchdogGuard {
+    /// Erreur de dépassement si l'échéance est passée, même si le script a
+    /// intercepté l'exception ou s'est terminé normalement entre-temps
+    pub fn check(&self) -> Result<(), String> {
+        DEADLINE.with(|deadline| match deadline.borrow().as_ref() {
+            Some(deadline) if deadline.expired || Instant::now() >= deadline.at => Err(deadline.message()),
+            _ => Ok(()),
+        })
+    }
+}
+
+impl Drop for WatchdogGuard {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        DEADLINE.with(|deadline| deadline.replace(previous));
+    }
+}
+
+/// Message de l'exception levée quand l'échéance est dépassée.
+///
+/// Une fois l'échéance passée, l'exécution est marquée comme condamnée et
+/// l'exception est relevée à chaque instruction : ni un `rescue Exception`
+/// ni un `ensure` ne peuvent relancer le script.
+fn expired() -> Option<String> {
+    DEADLINE.with(|deadline| {
+        let mut deadline = deadline.borrow_mut();
+        let deadline = deadline.as_mut()?;
+        if !deadline.expired {
+            let count = COUNTER.with(|counter| {
+                let count = counter.get().wrapping_add(1);
+                counter.set(count);
+                count
+            });
+            if count % CHECK_INTERVAL != 0 || Instant::now() < deadline.at {
+                return None;
+            }
+            deadline.expired = true;
+        }
+        Some(deadline.message())
+    })
+}
+
+unsafe extern "C" fn code_fetch_hook(
+    mrb: *mut sys::mrb_state,
+    _irep: *const sys::mrb_irep,
+    _pc: *const sys::mrb_code,
+    _regs: *mut sys::mrb_value,
+) {
+    // Aucune valeur Rust ne doit rester vivante au moment du raise (longjmp)
+    let exception = match expired() {
+        Some(message) => {
+            let os = sys::mrb_module_get(mrb, b"OS\0".as_ptr().cast());
+            let class = sys::mrb_class_get_under(mrb, os, b"ExecutionTimeout\0".as_ptr().cast());
+            let message = sys::mrb_str_new(mrb, message.as_ptr().cast(), message.len());
+            sys::mrb_exc_new_str(mrb, class, message)
+        },
+        None => return,
+    };
+    sys::mrb_exc_raise(mrb, exception);
+}
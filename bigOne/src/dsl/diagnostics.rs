@@ -0,0 +1,137 @@
+// src/dsl/diagnostics.rs
+use std::fmt;
+
+use crate::dsl::parser::ScriptError;
+
+/// Gravité d'un diagnostic DSL
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// Le script ne peut pas être appliqué
+    Error,
+    /// Le script est appliqué, mais une valeur a été ignorée
+    Warning,
+}
+
+/// Position d'un élément dans un script DSL
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceLocation {
+    pub file: String,
+    pub line: u32,
+    pub column: Option<u32>,
+}
+
+impl SourceLocation {
+    /// Lit la position `{ file:, line: }` capturée côté Ruby à la création d'un composant
+    pub fn from_json(value: &serde_json::Value) -> Option<Self> {
+        Some(Self {
+            file: value.get("file")?.as_str()?.to_string(),
+            line: value.get("line")?.as_u64()? as u32,
+            column: value.get("column").and_then(|c| c.as_u64()).map(|c| c as u32),
+        })
+    }
+}
+
+impl fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.column {
+            Some(column) => write!(f, "{}:{}:{}", self.file, self.line, column),
+            None => write!(f, "{}:{}", self.file, self.line),
+        }
+    }
+}
+
+/// Diagnostic produit pendant l'évaluation ou la conversion d'un script DSL
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    /// Position dans le script, si elle est connue
+    pub location: Option<SourceLocation>,
+    /// Chemin des IDs de composants, de la racine jusqu'au composant en cause
+    pub component_path: Vec<String>,
+    /// Composant personnalisé dont l'expansion a produit le composant en cause
+    pub custom_component: Option<String>,
+}
+
+impl Diagnostic {
+    /// Crée un diagnostic d'erreur
+    pub fn error(message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Error,
+            message: message.into(),
+            location: None,
+            component_path: Vec::new(),
+            custom_component: None,
+        }
+    }
+
+    /// Crée un avertissement
+    pub fn warning(message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Warning,
+            ..Self::error(message)
+        }
+    }
+
+    /// Associe une position dans le script
+    pub fn at(mut self, location: Option<SourceLocation>) -> Self {
+        self.location = location;
+        self
+    }
+
+    /// Associe le chemin du composant en cause
+    pub fn in_component(mut self, path: &[String]) -> Self {
+        self.component_path = path.to_vec();
+        self
+    }
+
+    /// Associe le composant personnalisé d'origine
+    pub fn from_custom(mut self, custom_component: Option<String>) -> Self {
+        self.custom_component = custom_component;
+        self
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "erreur",
+            Severity::Warning => "avertissement",
+        };
+        write!(f, "{}: {}", severity, self.message)?;
+        if !self.component_path.is_empty() {
+            write!(f, " [{}]", self.component_path.join(" > "))?;
+        }
+        if let Some(custom) = &self.custom_component {
+            write!(f, " dans le composant `{}`", custom)?;
+        }
+        if let Some(location) = &self.location {
+            write!(f, " ({})", location)?;
+        }
+        Ok(())
+    }
+}
+
+impl From<&Diagnostic> for ScriptError {
+    fn from(diagnostic: &Diagnostic) -> Self {
+        let mut message = diagnostic.message.clone();
+        if !diagnostic.component_path.is_empty() {
+            message = format!("{} [{}]", message, diagnostic.component_path.join(" > "));
+        }
+        if let Some(custom) = &diagnostic.custom_component {
+            message = format!("{} dans le composant `{}`", message, custom);
+        }
+
+        ScriptError {
+            class: "DSLError".to_string(),
+            message,
+            file: diagnostic.location.as_ref().map(|location| location.file.clone()),
+            line: diagnostic.location.as_ref().map(|location| location.line),
+            violation: None,
+        }
+    }
+}
@@ -0,0 +1,113 @@
+// src/dsl/loader.rs
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Chargeur des scripts DSL d'un répertoire racine.
+///
+/// Tous les fichiers `.rb` de la racine sont exposés à Artichoke via son système de
+/// fichiers virtuel, sous leur chemin absolu : `import "widgets/toolbar"` charge
+/// `<racine>/widgets/toolbar.rb`, et les erreurs pointent vers le vrai fichier.
+#[derive(Debug, Clone)]
+pub struct ScriptLoader {
+    root: PathBuf,
+}
+
+impl ScriptLoader {
+    /// Crée un chargeur pour le répertoire contenant le script principal
+    pub fn for_entry(script_path: &str) -> Self {
+        let root = Path::new(script_path)
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+            .unwrap_or_else(|| Path::new("."));
+        Self::new(root)
+    }
+
+    pub fn new(root: impl AsRef<Path>) -> Self {
+        let root = root.as_ref();
+        Self {
+            root: root.canonicalize().unwrap_or_else(|_| root.to_path_buf()),
+        }
+    }
+
+    /// Répertoire racine des scripts
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Lit tous les scripts `.rb` sous la racine, avec leur chemin absolu
+    pub fn sources(&self) -> io::Result<Vec<(PathBuf, Vec<u8>)>> {
+        let mut sources = Vec::new();
+        collect_sources(&self.root, &mut sources)?;
+        Ok(sources)
+    }
+}
+
+fn collect_sources(dir: &Path, sources: &mut Vec<(PathBuf, Vec<u8>)>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_sources(&path, sources)?;
+        } else if path.extension().map_or(false, |ext| ext == "rb") {
+            sources.push((path.clone(), fs::read(&path)?));
+        }
+    }
+    Ok(())
+}
+
+/// Graphe des imports relevé lors de la dernière évaluation
+#[derive(Debug, Clone, Default)]
+pub struct DependencyGraph {
+    /// Fichier importateur => fichiers importés
+    imports: HashMap<PathBuf, Vec<PathBuf>>,
+}
+
+impl DependencyGraph {
+    /// Construit le graphe à partir des arêtes `[importateur, importé]` relevées côté Ruby
+    pub fn from_edges(edges: Vec<(String, String)>) -> Self {
+        let mut imports: HashMap<PathBuf, Vec<PathBuf>> = HashMap::new();
+        for (from, to) in edges {
+            let targets = imports.entry(normalize(&from)).or_default();
+            let to = normalize(&to);
+            if !targets.contains(&to) {
+                targets.push(to);
+            }
+        }
+        Self { imports }
+    }
+
+    /// Tous les fichiers présents dans le graphe
+    pub fn files(&self) -> HashSet<PathBuf> {
+        self.imports
+            .iter()
+            .flat_map(|(from, targets)| std::iter::once(from.clone()).chain(targets.iter().cloned()))
+            .collect()
+    }
+
+    /// Fichiers qui dépendent, directement ou non, d'un fichier donné
+    pub fn dependents_of(&self, file: &Path) -> HashSet<PathBuf> {
+        let file = normalize(&file.to_string_lossy());
+        let mut dependents = HashSet::new();
+        let mut pending = vec![file];
+
+        while let Some(current) = pending.pop() {
+            for (from, targets) in &self.imports {
+                if targets.contains(&current) && dependents.insert(from.clone()) {
+                    pending.push(from.clone());
+                }
+            }
+        }
+        dependents
+    }
+}
+
+fn normalize(path: &str) -> PathBuf {
+    canonical(path)
+}
+
+/// Chemin absolu canonique quand le fichier existe, tel quel sinon
+pub fn canonical(path: impl AsRef<Path>) -> PathBuf {
+    let path = path.as_ref();
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
@@ -0,0 +1,90 @@
+// src/dsl/marshal.rs
+use artichoke::prelude::*;
+use serde_json::{Map, Number};
+
+/// Convertit une valeur JSON en valeur Ruby, sans générer de code source.
+///
+/// Les objets deviennent des hashs à clés texte, pour rester compatibles avec
+/// les handlers qui lisent `params['id']`.
+pub fn json_to_ruby(interp: &mut Artichoke, value: &serde_json::Value) -> Result<Value, Error> {
+    match value {
+        serde_json::Value::Null => Ok(Value::nil()),
+        serde_json::Value::Bool(b) => Ok(interp.convert(*b)),
+        serde_json::Value::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                Ok(interp.convert(i))
+            } else {
+                Ok(interp.convert_mut(n.as_f64().unwrap_or(f64::NAN)))
+            }
+        },
+        serde_json::Value::String(s) => interp.try_convert_mut(s.as_str()),
+        serde_json::Value::Array(items) => {
+            let mut values = Vec::with_capacity(items.len());
+            for item in items {
+                values.push(json_to_ruby(interp, item)?);
+            }
+            interp.try_convert_mut(values)
+        },
+        serde_json::Value::Object(entries) => {
+            let mut pairs = Vec::with_capacity(entries.len());
+            for (key, entry) in entries {
+                let key = interp.try_convert_mut(key.as_str())?;
+                let entry = json_to_ruby(interp, entry)?;
+                pairs.push((key, entry));
+            }
+            interp.try_convert_mut(pairs)
+        },
+    }
+}
+
+/// Convertit une valeur Ruby en valeur JSON.
+///
+/// Les symboles et les clés de hash non textuelles sont convertis en texte ;
+/// les autres objets sont représentés par leur `to_s`.
+pub fn ruby_to_json(interp: &mut Artichoke, value: Value) -> Result<serde_json::Value, Error> {
+    match value.ruby_type() {
+        Ruby::Nil => Ok(serde_json::Value::Null),
+        Ruby::Bool => {
+            let b: bool = interp.try_convert(value)?;
+            Ok(serde_json::Value::Bool(b))
+        },
+        Ruby::Fixnum => {
+            let i: i64 = interp.try_convert(value)?;
+            Ok(serde_json::Value::Number(i.into()))
+        },
+        Ruby::Float => {
+            let f: f64 = interp.try_convert(value)?;
+            Ok(Number::from_f64(f).map_or(serde_json::Value::Null, serde_json::Value::Number))
+        },
+        Ruby::String => {
+            let s: String = interp.try_convert_mut(value)?;
+            Ok(serde_json::Value::String(s))
+        },
+        Ruby::Array => {
+            let items: Vec<Value> = interp.try_convert_mut(value)?;
+            let mut values = Vec::with_capacity(items.len());
+            for item in items {
+                values.push(ruby_to_json(interp, item)?);
+            }
+            Ok(serde_json::Value::Array(values))
+        },
+        Ruby::Hash => {
+            let pairs: Vec<(Value, Value)> = interp.try_convert_mut(value)?;
+            let mut entries = Map::new();
+            for (key, entry) in pairs {
+                let key = match ruby_to_json(interp, key)? {
+                    serde_json::Value::String(s) => s,
+                    other => other.to_string(),
+                };
+                entries.insert(key, ruby_to_json(interp, entry)?);
+            }
+            Ok(serde_json::Value::Object(entries))
+        },
+        _ => {
+            // Symboles et objets quelconques : représentation textuelle
+            let text = value.funcall(interp, "to_s", &[], None)?;
+            let s: String = interp.try_convert_mut(text)?;
+            Ok(serde_json::Value::String(s))
+        },
+    }
+}
@@ -1,5 +1,19 @@
 pub mod parser;
+pub mod diagnostics;
+pub mod props;
+pub mod marshal;
+pub mod reactive;
+pub mod loader;
+pub mod sandbox;
+pub mod budget;
+pub mod worker;
+pub mod protocol;
 
 // Ré-exporter les types principaux
 pub use parser::DSLParser;
-pub use parser::DSLEvaluationResult;
\ No newline at end of file
+pub use parser::DSLEvaluationResult;
+pub use parser::ScriptError;
+pub use diagnostics::{Diagnostic, Severity, SourceLocation};
+pub use sandbox::{Capability, SandboxConfig};
+pub use budget::ExecutionBudget;
+pub use protocol::{CallbackResponse, ProtocolError, PROTOCOL_VERSION};
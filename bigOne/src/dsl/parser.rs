@@ -2,36 +2,123 @@
 use artichoke::prelude::*;
 use bevy::prelude::*;
 use std::collections::HashMap;
+use std::path::Path;
 use std::sync::{Arc, Mutex};
 use serde_json;
+use artichoke::backend::state::parser::Context;
 use crate::ui::components::*;
+use crate::dsl::diagnostics::{Diagnostic, SourceLocation};
+use crate::dsl::props::PropReader;
+use crate::dsl::marshal::{json_to_ruby, ruby_to_json};
+use crate::dsl::reactive::PropertyChange;
+use crate::dsl::loader::{DependencyGraph, ScriptLoader};
+use crate::dsl::sandbox::{Capability, SandboxConfig, SANDBOX_PRELUDE};
+use crate::dsl::budget::{ExecutionBudget, Watchdog};
+use crate::ui::color::ColorValue;
+use crate::ui::style::ResolvedStyle;
+use crate::ui::stylesheet::{Selector, StyleRule, Stylesheet};
+use crate::ui::theme::ThemeDefinition;
+use crate::ui::svg::Svg;
+use crate::ui::canvas::DrawCommand;
+use crate::dsl::loader::canonical;
 
 /// Résultat de l'évaluation du DSL
 pub struct DSLEvaluationResult {
     pub components: Vec<UIComponent>,
-    pub errors: Vec<String>,
+    /// Règles des blocs `stylesheet` du script
+    pub stylesheet: Stylesheet,
+    /// Thèmes déclarés (`theme`) ou chargés (`load_theme`), dans l'ordre
+    pub themes: Vec<ThemeDefinition>,
+    /// Thème choisi par `OS.theme =`, le cas échéant
+    pub theme: Option<String>,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl DSLEvaluationResult {
+    /// Première erreur bloquante, le cas échéant
+    pub fn first_error(&self) -> Option<&Diagnostic> {
+        self.diagnostics.iter().find(|diagnostic| diagnostic.is_error())
+    }
+}
+
+/// Nom de fichier sous lequel le prélude du DSL est évalué
+const DSL_FILE: &str = "(os-dsl)";
+
+/// Préfixe des handles de handlers Ruby (voir `OS::Handlers` dans le prélude)
+pub const HANDLER_PREFIX: &str = "#handler:";
+
+/// Erreur levée pendant l'évaluation d'un script DSL
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("{class}: {message}{}", location_suffix(.file, .line))]
+pub struct ScriptError {
+    /// Classe de l'exception Ruby (ex: "NoMethodError")
+    pub class: String,
+    /// Message de l'exception
+    pub message: String,
+    /// Fichier en cause, si l'erreur vient d'un script importé ou a pu être située
+    pub file: Option<String>,
+    /// Ligne du script en cause, si elle a pu être déterminée
+    pub line: Option<u32>,
+    /// API refusée, si l'erreur est une violation du sandbox (ex: "File.read")
+    pub violation: Option<String>,
+}
+
+impl ScriptError {
+    /// Erreur interne au DSL, sans exception Ruby associée
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self {
+            class: "DSLError".to_string(),
+            message: message.into(),
+            file: None,
+            line: None,
+            violation: None,
+        }
+    }
+
+    /// Budget d'exécution dépassé, quel que soit le résultat côté Ruby
+    pub fn timeout(message: impl Into<String>) -> Self {
+        Self {
+            class: "OS::ExecutionTimeout".to_string(),
+            ..Self::internal(message)
+        }
+    }
 }
 
 /// Service pour l'évaluation du code Ruby DSL
 pub struct DSLParser {
     interp: Artichoke,
     component_registry: Arc<Mutex<HashMap<String, UIComponent>>>,
+    dependencies: DependencyGraph,
+    sandbox: SandboxConfig,
+    /// Capacités accordées au script en cours d'exécution
+    capabilities: Vec<Capability>,
+    /// Budget d'exécution du script en cours
+    budget: ExecutionBudget,
 }
 
 impl DSLParser {
-    /// Crée une nouvelle instance du parser DSL
-    pub fn new() -> Result<Self, String> {
+    /// Crée une nouvelle instance du parser DSL.
+    ///
+    /// Le sandbox est appliqué à la fin de `initialize_dsl`, une fois le prélude chargé.
+    pub fn new(sandbox: SandboxConfig) -> Result<Self, String> {
         // Initialiser l'interpréteur Ruby via Artichoke
-        let interp = match artichoke::interpreter() {
+        let mut interp = match artichoke::interpreter() {
             Ok(interp) => interp,
             Err(e) => return Err(format!("Erreur d'initialisation Ruby: {:?}", e)),
         };
+        
+        // Aucun code Ruby ne peut bloquer l'application au-delà de son budget
+        Watchdog::install(&mut interp)?;
 
         let component_registry = Arc::new(Mutex::new(HashMap::new()));
         
         Ok(Self {
             interp,
             component_registry,
+            dependencies: DependencyGraph::default(),
+            sandbox,
+            capabilities: Vec::new(),
+            budget: ExecutionBudget::default(),
         })
     }
 
@@ -44,14 +131,909 @@ impl DSLParser {
         let dsl_setup = r#"
             # Namespace de base pour notre DSL UI
             module OS
+              # Nom de fichier attribué au prélude, exclu des positions capturées
+              DSL_FILE = '(os-dsl)'
+              
+              # Levée par le chien de garde quand un script dépasse son budget d'exécution.
+              # Hérite d'Exception : un `rescue` ordinaire ne l'intercepte pas, et elle est
+              # relevée à chaque instruction jusqu'au retour en Rust.
+              class ExecutionTimeout < Exception; end
+              
+              # Composants intégrés : nom dans le DSL => classe Ruby
+              BUILTIN_TYPES = {
+                'window' => 'Window', 'button' => 'Button', 'text' => 'Text', 'image' => 'Image',
+                'canvas' => 'Canvas', 'svg' => 'SVG', 'scrollview' => 'ScrollView', 'list' => 'List',
+                'grid' => 'Grid', 'input' => 'Input', 'viewport3d' => 'Viewport3D', 'stack' => 'Stack',
+                'row' => 'Row', 'column' => 'Column',
+                # Nœuds 3D, déclarés dans un viewport3d
+                'cube' => 'Cube', 'sphere' => 'Sphere', 'plane' => 'Plane', 'mesh' => 'Mesh',
+                'point_light' => 'PointLight', 'directional_light' => 'DirectionalLight', 'group' => 'Group',
+              }
+              
+              # Registre des handlers Ruby (lambdas et blocs) référencés par l'UI.
+              # Les handles sont dérivés de l'ID du composant et de l'événement,
+              # ils restent donc stables d'un rechargement à l'autre.
+              module Handlers
+                PREFIX = '#handler:'
+                # Handlers du script et du script précédent ; le module est gelé par le sandbox
+                class Store
+                  attr_accessor :handlers, :previous
+                  
+                  def initialize
+                    @handlers = {}
+                    @previous = nil
+                  end
+                end
+                
+                STORE = Store.new
+                
+                def self.register(owner_id, event, callable)
+                  handle = "#{PREFIX}#{owner_id}:#{event}"
+                  STORE.handlers[handle] = { owner: owner_id.to_s, callable: callable }
+                  handle
+                end
+                
+                def self.call(handle, args)
+                  entry = STORE.handlers[handle]
+                  raise ArgumentError, "Handler inconnu: #{handle}" unless entry
+                  
+                  callable = entry[:callable]
+                  callable.arity == 0 ? callable.call : callable.call(args)
+                end
+                
+                # Libère les handlers d'un composant retiré de l'UI
+                def self.release(owner_id)
+                  STORE.handlers.delete_if { |_, entry| entry[:owner] == owner_id.to_s }
+                end
+                
+                # Un rechargement enregistre un nouveau jeu de handlers ; l'ancien
+                # est conservé jusqu'à ce que le nouveau script soit appliqué
+                def self.begin_reload!
+                  STORE.previous = STORE.handlers
+                  STORE.handlers = {}
+                end
+                
+                def self.commit!
+                  STORE.previous = nil
+                end
+                
+                def self.rollback!
+                  STORE.handlers = STORE.previous if STORE.previous
+                  STORE.previous = nil
+                end
+              end
+              
+              # Valeur observable : count = state(0) ; count.value += 1
+              class State
+                attr_reader :subscribers
+                
+                def initialize(value)
+                  @value = value
+                  @subscribers = []
+                end
+                
+                def value
+                  Reactive.track(self)
+                  @value
+                end
+                
+                def value=(new_value)
+                  return if new_value == @value
+                  @value = new_value
+                  Reactive.changed(self)
+                end
+                
+                # count.update { |v| v + 1 }
+                def update
+                  self.value = yield(@value)
+                end
+                
+                def subscribe(bound)
+                  @subscribers << bound unless @subscribers.include?(bound)
+                end
+                
+                def unsubscribe(bound)
+                  @subscribers.delete(bound)
+                end
+              end
+              
+              # Imports entre scripts : import "widgets/toolbar" charge <racine>/widgets/toolbar.rb.
+              # Les fichiers sont servis par le système de fichiers virtuel d'Artichoke ;
+              # chaque import est relevé pour construire le graphe de dépendances côté Rust.
+              module Imports
+                # Racine des scripts, fixée par Rust à la première évaluation puis gelée,
+                # avant que le moindre script ne s'exécute : un script ne peut pas
+                # rediriger les imports hors de son répertoire
+                ROOT = []
+                
+                # Imports de l'évaluation en cours
+                class Store
+                  attr_accessor :loaded, :loading, :edges
+                  
+                  def initialize
+                    @loaded = {}
+                    @loading = []
+                    @edges = []
+                  end
+                end
+                
+                STORE = Store.new
+                
+                # Nouvelle évaluation : le script principal est déjà chargé
+                def self.reset!(root, entry)
+                  ROOT << root.dup.freeze unless ROOT.frozen?
+                  ROOT.freeze
+                  raise LoadError, "Racine des scripts déjà fixée à #{ROOT.first}" unless ROOT.first == root
+                  
+                  STORE.loaded = { entry => true }
+                  STORE.loading = [entry]
+                  STORE.edges = []
+                end
+                
+                def self.resolve(path)
+                  raise LoadError, "Racine des scripts non définie" if ROOT.empty?
+                  
+                  # Copie en String ordinaire : une sous-classe ne peut pas tromper les vérifications
+                  parts = []
+                  String.new(path.to_s).split('/').each do |part|
+                    next if part.empty? || part == '.'
+                    if part == '..'
+                      raise LoadError, "import \"#{path}\" sort du répertoire des scripts" if parts.empty?
+                      parts.pop
+                    else
+                      parts << part
+                    end
+                  end
+                  raise LoadError, "import vide" if parts.empty?
+                  
+                  parts[-1] = "#{parts[-1]}.rb" unless parts[-1].end_with?('.rb')
+                  "#{ROOT.first}/#{parts.join('/')}"
+                end
+                
+                def self.import(path, from)
+                  file = resolve(path)
+                  STORE.edges << [from, file]
+                  if STORE.loading.include?(file)
+                    raise LoadError, "Import circulaire: #{(STORE.loading + [file]).join(' -> ')}"
+                  end
+                  return false if STORE.loaded[file]
+                  
+                  STORE.loaded[file] = true
+                  STORE.loading.push(file)
+                  begin
+                    Sandbox.privileged { load(file) }
+                  ensure
+                    STORE.loading.pop
+                  end
+                  true
+                end
+                
+                # Arêtes [importateur, importé] relevées pendant l'évaluation
+                def self.graph
+                  STORE.edges
+                end
+              end
+              
+              # Propriétés liées à une expression : text(content: -> { "Clics: #{count.value}" }).
+              # Quand un état change, seules les propriétés qui le lisent sont recalculées,
+              # et les changements sont transmis à Rust par flush!.
+              module Reactive
+                # Liaisons et changements en attente, conservés hors du module gelé
+                class Store
+                  attr_accessor :bindings, :pending, :stack, :previous
+                  
+                  def initialize
+                    @bindings = []
+                    @pending = []
+                    @stack = []
+                    @previous = nil
+                  end
+                end
+                
+                STORE = Store.new
+                
+                class Binding
+                  attr_reader :component, :prop
+                  
+                  def initialize(component, prop, expr)
+                    @component = component
+                    @prop = prop
+                    @expr = expr
+                    @states = []
+                  end
+                  
+                  # Recalcule la valeur en enregistrant les états lus
+                  def evaluate
+                    @states.each { |state| state.unsubscribe(self) }
+                    @states = []
+                    value = Reactive.tracking(@states) { @expr.call }
+                    @states.each { |state| state.subscribe(self) }
+                    @component.props[@prop] = value
+                    value
+                  end
+                  
+                  def release
+                    @states.each { |state| state.unsubscribe(self) }
+                    @states = []
+                  end
+                end
+                
+                def self.bind(component, prop, expr)
+                  bound = Binding.new(component, prop, expr)
+                  STORE.bindings << bound
+                  bound.evaluate
+                end
+                
+                def self.tracking(states)
+                  STORE.stack.push(states)
+                  yield
+                ensure
+                  STORE.stack.pop
+                end
+                
+                def self.track(state)
+                  states = STORE.stack.last
+                  states << state if states && !states.include?(state)
+                end
+                
+                def self.changed(state)
+                  state.subscribers.dup.each do |bound|
+                    value = bound.evaluate
+                    STORE.pending << { id: bound.component.id, type: bound.component.type, prop: bound.prop.to_s, value: value, props: bound.component.props }
+                  end
+                end
+                
+                # Changements de propriétés en attente depuis le dernier appel
+                def self.flush!
+                  pending = STORE.pending
+                  STORE.pending = []
+                  pending
+                end
+                
+                # Libère les liaisons d'un composant retiré de l'UI
+                def self.release(owner_id)
+                  released, STORE.bindings = STORE.bindings.partition { |bound| bound.component.id == owner_id.to_s }
+                  released.each(&:release)
+                end
+                
+                def self.begin_reload!
+                  STORE.previous = STORE.bindings
+                  STORE.bindings = []
+                end
+                
+                def self.commit!
+                  (STORE.previous || []).each(&:release)
+                  STORE.previous = nil
+                  STORE.pending = []
+                end
+                
+                def self.rollback!
+                  STORE.bindings.each(&:release)
+                  STORE.bindings = STORE.previous if STORE.previous
+                  STORE.previous = nil
+                  STORE.pending = []
+                end
+              end
+              
+              # Règles des feuilles de style : stylesheet do rule "button.primary", background: "#36c" end
+              module Styles
+                # Règles déclarées et règles du script précédent
+                class Store
+                  attr_accessor :rules, :previous, :open
+                  
+                  def initialize
+                    @rules = []
+                    @previous = nil
+                    @open = false
+                  end
+                end
+                
+                STORE = Store.new
+                
+                def self.open
+                  raise ArgumentError, "stylesheet ne peut pas être imbriqué" if STORE.open
+                  STORE.open = true
+                  begin
+                    yield
+                  ensure
+                    STORE.open = false
+                  end
+                end
+                
+                def self.rule(selector, style)
+                  raise ArgumentError, "rule doit être appelé dans un bloc stylesheet" unless STORE.open
+                  entry = { selector: selector.to_s, style: style }
+                  location = OS.source_location
+                  entry[:location] = location if location
+                  STORE.rules << entry
+                  nil
+                end
+                
+                def self.to_json
+                  STORE.rules.to_json
+                end
+                
+                def self.begin_reload!
+                  STORE.previous = STORE.rules
+                  STORE.rules = []
+                  STORE.open = false
+                end
+                
+                def self.commit!
+                  STORE.previous = nil
+                end
+                
+                def self.rollback!
+                  STORE.rules = STORE.previous if STORE.previous
+                  STORE.previous = nil
+                end
+              end
+              
+              # Thèmes : theme :ocean, extends: :dark do |t| t.color :primary, "#1478c8" end.
+              # Le bloc reçoit le constructeur en argument : le code du script ne
+              # s'exécute jamais dans un autre contexte que le sien.
+              # Pendant l'évaluation, les définitions et le choix du thème accompagnent
+              # le résultat ; depuis un handler, ils sont mis en file comme les proxies.
+              module Themes
+                # Thèmes déclarés et thème actif
+                class Store
+                  attr_accessor :definitions, :active, :previous, :evaluating
+                  
+                  def initialize
+                    @definitions = []
+                    @active = nil
+                    @previous = nil
+                    @evaluating = false
+                  end
+                end
+                
+                STORE = Store.new
+                
+                # Jetons déclarés dans un bloc theme
+                class Builder
+                  attr_reader :tokens
+                  
+                  def initialize
+                    @tokens = { colors: {}, font_sizes: {}, spacing: {}, radii: {}, fonts: {} }
+                  end
+                  
+                  def color(name, value)
+                    @tokens[:colors][name.to_s] = value.to_s
+                  end
+                  
+                  def font_size(name, value)
+                    @tokens[:font_sizes][name.to_s] = value
+                  end
+                  
+                  def spacing(name, value)
+                    @tokens[:spacing][name.to_s] = value
+                  end
+                  
+                  def radius(name, value)
+                    @tokens[:radii][name.to_s] = value
+                  end
+                  
+                  def font(name, path)
+                    @tokens[:fonts][name.to_s] = path.to_s
+                  end
+                end
+                
+                def self.define(name, extends)
+                  builder = Builder.new
+                  yield builder if block_given?
+                  entry = { name: name.to_s, extends: extends && extends.to_s }.merge(builder.tokens)
+                  if STORE.evaluating
+                    location = OS.source_location
+                    entry[:location] = location if location
+                    STORE.definitions << entry
+                  else
+                    Live.queue({ action: 'define_theme', theme: entry })
+                  end
+                  nil
+                end
+                
+                # Fichier JSON relatif au script appelant, lu par Rust après l'évaluation
+                def self.load(path, from)
+                  raise ArgumentError, "load_theme n'est disponible qu'à l'évaluation du script" unless STORE.evaluating
+                  entry = { load: path.to_s, from: from }
+                  location = OS.source_location
+                  entry[:location] = location if location
+                  STORE.definitions << entry
+                  nil
+                end
+                
+                def self.active=(name)
+                  STORE.active = name.to_s
+                  Live.queue({ action: 'set_theme', theme: STORE.active }) unless STORE.evaluating
+                end
+                
+                def self.active
+                  STORE.active
+                end
+                
+                def self.to_json
+                  { definitions: STORE.definitions, active: STORE.active }.to_json
+                end
+                
+                def self.begin_reload!
+                  STORE.previous = [STORE.definitions, STORE.active]
+                  STORE.definitions = []
+                  STORE.evaluating = true
+                end
+                
+                def self.commit!
+                  STORE.previous = nil
+                  STORE.evaluating = false
+                end
+                
+                def self.rollback!
+                  STORE.definitions, STORE.active = STORE.previous if STORE.previous
+                  STORE.previous = nil
+                  STORE.evaluating = false
+                end
+              end
+              
+              # Accès impératif aux composants affichés : OS.find(:status).content = "OK".
+              # Les valeurs lues viennent d'un relevé de l'ECS transmis par Rust avant les
+              # handlers ; les modifications sont mises en file et appliquées après le handler.
+              module Live
+                # Propriété de texte de chaque type de composant
+                TEXT_PROPS = { 'text' => :content, 'button' => :text, 'input' => :value, 'window' => :title }
+                
+                # Dernier relevé de l'ECS et mises à jour en file
+                class Store
+                  attr_accessor :snapshot, :queue
+                  
+                  def initialize
+                    @snapshot = {}
+                    @queue = []
+                  end
+                end
+                
+                STORE = Store.new
+                
+                # Valeurs modifiées depuis le relevé précédent, et composants retirés
+                def self.update(changes, removed)
+                  (removed || []).each { |id| STORE.snapshot.delete(id) }
+                  STORE.snapshot.merge!(changes || {})
+                end
+                
+                # Valeurs relevées pour un composant ({ "text" =>, "x" =>, "visible" => ... })
+                def self.read(id)
+                  STORE.snapshot[id]
+                end
+                
+                def self.known?(id)
+                  STORE.snapshot.key?(id) || !OS.component(id).nil?
+                end
+                
+                # Met une mise à jour en file ; les valeurs connues sont modifiées aussitôt,
+                # pour que la suite du handler lise ce qu'il vient d'écrire
+                def self.queue(update, values = {})
+                  STORE.queue << update
+                  id = update[:id]
+                  (STORE.snapshot[id] ||= {}).merge!(values) unless values.empty?
+                  
+                  component = OS.component(id)
+                  return unless component
+                  values.each do |key, value|
+                    key = TEXT_PROPS.fetch(component_type(component), :text) if key == 'text'
+                    component.props[key.to_sym] = value
+                  end
+                end
+                
+                def self.component_type(component)
+                  component.type
+                end
+                
+                # Mises à jour en attente depuis le dernier appel
+                def self.flush!
+                  queued = STORE.queue
+                  STORE.queue = []
+                  queued
+                end
+                
+                def self.reset!
+                  STORE.queue = []
+                end
+              end
+              
+              # Composant affiché, retourné par OS.find
+              class Proxy
+                attr_reader :id
+                
+                def initialize(id)
+                  @id = id
+                end
+                
+                def type
+                  live('type') || (component && Live.component_type(component))
+                end
+                
+                def text
+                  live('text')
+                end
+                alias_method :content, :text
+                alias_method :value, :text
+                alias_method :title, :text
+                
+                def text=(text)
+                  text = text.to_s
+                  Live.queue({ action: 'set_text', id: @id, value: text }, 'text' => text)
+                end
+                alias_method :content=, :text=
+                alias_method :value=, :text=
+                alias_method :title=, :text=
+                
+                def x
+                  live('x')
+                end
+                
+                def y
+                  live('y')
+                end
+                
+                def position
+                  [x, y]
+                end
+                
+                def move(x, y)
+                  Live.queue({ action: 'set_position', id: @id, x: x, y: y }, 'x' => x, 'y' => y)
+                  self
+                end
+                
+                def width
+                  live('width')
+                end
+                
+                def height
+                  live('height')
+                end
+                
+                def size
+                  [width, height]
+                end
+                
+                def resize(width, height)
+                  Live.queue({ action: 'set_size', id: @id, width: width, height: height }, 'width' => width, 'height' => height)
+                  self
+                end
+                
+                def visible?
+                  live('visible') != false
+                end
+                
+                def visible=(visible)
+                  visible = visible ? true : false
+                  Live.queue({ action: 'set_visible', id: @id, visible: visible }, 'visible' => visible)
+                end
+                
+                def show
+                  self.visible = true
+                  self
+                end
+                
+                def hide
+                  self.visible = false
+                  self
+                end
+                
+                def source=(source)
+                  Live.queue({ action: 'set_image', id: @id, source: source.to_s }, 'source' => source.to_s)
+                end
+                
+                def style(**style)
+                  values = {}
+                  style.each { |key, value| values[key.to_s] = value.to_s }
+                  Live.queue({ action: 'set_style', id: @id, style: values })
+                  self
+                end
+                
+                def focused?
+                  live('focused') == true
+                end
+                
+                # Classes visées par les feuilles de style
+                def classes
+                  classes = live('class')
+                  classes.is_a?(Array) ? classes.map(&:to_s) : classes.to_s.split
+                end
+                
+                def classes=(classes)
+                  classes = (classes.is_a?(Array) ? classes : classes.to_s.split).map(&:to_s).uniq
+                  Live.queue({ action: 'set_classes', id: @id, classes: classes }, 'class' => classes)
+                end
+                
+                def class?(name)
+                  classes.include?(name.to_s)
+                end
+                
+                def add_class(name)
+                  self.classes = classes | [name.to_s]
+                  self
+                end
+                
+                def remove_class(name)
+                  self.classes = classes - [name.to_s]
+                  self
+                end
+                
+                def toggle_class(name, enabled = nil)
+                  enabled = !class?(name) if enabled.nil?
+                  enabled ? add_class(name) : remove_class(name)
+                end
+                
+                def disabled?
+                  live('disabled') == true
+                end
+                
+                def disabled=(disabled)
+                  disabled = disabled ? true : false
+                  Live.queue({ action: 'set_disabled', id: @id, disabled: disabled }, 'disabled' => disabled)
+                end
+                
+                def enable
+                  self.disabled = false
+                  self
+                end
+                
+                def disable
+                  self.disabled = true
+                  self
+                end
+                
+                def focus
+                  Live.queue({ action: 'focus', id: @id }, 'focused' => true)
+                  self
+                end
+                
+                # Déplace, tourne (degrés) ou redimensionne un nœud 3D
+                def transform(position: nil, rotation: nil, scale: nil)
+                  values = { 'position' => position, 'rotation' => rotation, 'scale' => scale }.reject { |_, value| value.nil? }
+                  Live.queue({ action: 'set_transform', id: @id }.merge(values), values) unless values.empty?
+                  self
+                end
+                
+                # Modifie le matériau d'un nœud 3D : color, metallic, roughness, texture
+                def material(**material)
+                  values = {}
+                  material.each { |key, value| values[key.to_s] = value }
+                  Live.queue({ action: 'set_material', id: @id }.merge(values), values) unless values.empty?
+                  self
+                end
+                
+                # Rappelle le on_draw d'un canevas
+                def invalidate
+                  Live.queue({ action: 'invalidate', id: @id })
+                  self
+                end
+                
+                def remove
+                  Live.queue({ action: 'remove', id: @id })
+                  nil
+                end
+                
+                def close
+                  Live.queue({ action: 'close_window', id: @id })
+                  nil
+                end
+                
+                # Propriété quelconque : valeur relevée, sinon valeur de construction
+                def [](prop)
+                  live(prop.to_s)
+                end
+                
+                private
+                
+                def component
+                  OS.component(@id)
+                end
+                
+                def live(key)
+                  values = Live.read(@id)
+                  return values[key] if values && values.key?(key)
+                  
+                  component = self.component
+                  return nil unless component
+                  key = Live::TEXT_PROPS.fetch(Live.component_type(component), :text).to_s if key == 'text'
+                  component.props[key.to_sym]
+                end
+              end
+              
+              # Contexte reçu par le on_draw d'un canevas : chaque appel ajoute une opération
+              # à la liste d'affichage, rastérisée par Rust. Une peinture est une couleur
+              # (CSS ou jeton "$nom") ou un dégradé (linear_gradient, radial_gradient).
+              class DrawContext
+                attr_reader :width, :height, :commands
+                
+                def initialize(width, height)
+                  @width = width
+                  @height = height
+                  @commands = []
+                  @path = []
+                end
+                
+                def clear(color = nil)
+                  draw(op: 'clear', paint: color && color.to_s)
+                end
+                
+                def fill_rect(x, y, width, height, paint)
+                  draw(op: 'fill_rect', x: x, y: y, width: width, height: height, paint: paint(paint))
+                end
+                
+                def stroke_rect(x, y, width, height, paint, line_width: 1, cap: nil, join: nil, dash: nil)
+                  draw({ op: 'stroke_rect', x: x, y: y, width: width, height: height, paint: paint(paint) }.merge(stroke(line_width, cap, join, dash)))
+                end
+                
+                # Chemin courant, repris par fill_path, stroke_path et clip
+                def begin_path
+                  @path = []
+                  self
+                end
+                
+                # ctx.path { |p| p.move_to(0, 0); p.line_to(10, 10) }
+                def path
+                  begin_path
+                  yield self
+                  self
+                end
+                
+                def move_to(x, y)
+                  segment(op: 'move_to', x: x, y: y)
+                end
+                
+                def line_to(x, y)
+                  segment(op: 'line_to', x: x, y: y)
+                end
+                
+                def quad_to(cx, cy, x, y)
+                  segment(op: 'quad_to', cx: cx, cy: cy, x: x, y: y)
+                end
+                
+                def cubic_to(c1x, c1y, c2x, c2y, x, y)
+                  segment(op: 'cubic_to', c1x: c1x, c1y: c1y, c2x: c2x, c2y: c2y, x: x, y: y)
+                end
+                
+                # Angles en radians, dans le sens horaire sauf counterclockwise
+                def arc(cx, cy, radius, start_angle, end_angle, counterclockwise = false)
+                  segment(op: 'arc', cx: cx, cy: cy, radius: radius, start: start_angle, end: end_angle, counterclockwise: counterclockwise ? true : false)
+                end
+                
+                def rect(x, y, width, height)
+                  segment(op: 'rect', x: x, y: y, width: width, height: height)
+                end
+                
+                def close_path
+                  segment(op: 'close')
+                end
+                
+                def fill_path(paint, even_odd: false)
+                  draw(op: 'fill_path', path: @path.dup, paint: paint(paint), even_odd: even_odd ? true : false)
+                end
+                
+                def stroke_path(paint, line_width: 1, cap: nil, join: nil, dash: nil)
+                  draw({ op: 'stroke_path', path: @path.dup, paint: paint(paint) }.merge(stroke(line_width, cap, join, dash)))
+                end
+                
+                # Restreint les dessins suivants au chemin courant, jusqu'au restore
+                def clip(even_odd: false)
+                  draw(op: 'clip', path: @path.dup, even_odd: even_odd ? true : false)
+                end
+                
+                # Texte posé sur sa ligne de base ; align: :left, :center ou :right
+                def text(text, x, y, color: '$text', size: 16, font: nil, align: :left)
+                  draw(op: 'text', text: text.to_s, x: x, y: y, color: color.to_s, size: size, font: font && font.to_s, align: align.to_s)
+                end
+                
+                # Image des assets, à sa taille naturelle sauf dimensions données
+                def image(source, x, y, width = nil, height = nil)
+                  draw(op: 'image', source: source.to_s, x: x, y: y, width: width, height: height)
+                end
+                
+                # Sauvegarde le repère et la découpe ; avec un bloc, les restaure à sa sortie
+                def save
+                  draw(op: 'save')
+                  return self unless block_given?
+                  begin
+                    yield self
+                  ensure
+                    restore
+                  end
+                  self
+                end
+                
+                def restore
+                  draw(op: 'restore')
+                end
+                
+                # Matrice a b c d e f, comme transform() en CSS
+                def transform(a, b, c, d, e, f)
+                  draw(op: 'transform', matrix: [a, b, c, d, e, f].map(&:to_f))
+                end
+                
+                def translate(x, y)
+                  transform(1, 0, 0, 1, x, y)
+                end
+                
+                def scale(x, y = x)
+                  transform(x, 0, 0, y, 0, 0)
+                end
+                
+                def rotate(angle)
+                  cos = Math.cos(angle)
+                  sin = Math.sin(angle)
+                  transform(cos, sin, -sin, cos, 0, 0)
+                end
+                
+                # Arrêts : { 0 => "red", 1 => "blue" } ou [[0, "red"], [1, "blue"]]
+                def linear_gradient(x0, y0, x1, y1, stops)
+                  { type: 'linear', x0: x0, y0: y0, x1: x1, y1: y1, stops: stops(stops) }
+                end
+                
+                def radial_gradient(cx, cy, radius, stops)
+                  { type: 'radial', cx: cx, cy: cy, radius: radius, stops: stops(stops) }
+                end
+                
+                private
+                
+                def draw(command)
+                  @commands << command
+                  self
+                end
+                
+                def segment(segment)
+                  @path << segment
+                  self
+                end
+                
+                def paint(paint)
+                  paint.is_a?(Hash) ? paint : paint.to_s
+                end
+                
+                def stroke(line_width, cap, join, dash)
+                  stroke = { line_width: line_width }
+                  stroke[:cap] = cap.to_s if cap
+                  stroke[:join] = join.to_s if join
+                  stroke[:dash] = dash if dash
+                  stroke
+                end
+                
+                def stops(stops)
+                  stops.to_a.map { |offset, color| [offset.to_f, color.to_s] }
+                end
+              end
+              
               # Classe de base pour tous les composants
               class Component
-                attr_reader :id, :props, :children
+                attr_reader :id, :props, :children, :location
+                # Nom du composant personnalisé qui a produit ce composant, le cas échéant
+                attr_accessor :origin
                 
                 def initialize(id, props = {})
                   @id = id.to_s
                   @props = props || {}
                   @children = []
+                  @location = OS.source_location
+                  
+                  # Les lambdas passées en propriété deviennent des handles pour les
+                  # événements (on_click: -> (e) { ... }) et des liaisons sinon
+                  @props.keys.each do |key|
+                    value = @props[key]
+                    next unless value.is_a?(Proc)
+                    
+                    @props[key] = if key.to_s.start_with?('on_')
+                      Handlers.register(@id, key, value)
+                    else
+                      Reactive.bind(self, key, value)
+                    end
+                  end
+                end
+                
+                # Associe un bloc à un événement : on(:click) { |e| ... }
+                def on(event, &block)
+                  key = :"on_#{event}"
+                  @props[key] = Handlers.register(@id, key, block)
+                  self
                 end
                 
                 def add_child(child)
@@ -59,14 +1041,21 @@ impl DSLParser {
                   self
                 end
                 
+                # Type du composant dans le DSL
+                def type
+                  self.class.name.split('::').last.downcase
+                end
+                
                 def to_hash
                   result = {
                     id: @id,
-                    type: self.class.name.split('::').last.downcase,
+                    type: type,
                     props: @props,
                   }
                   
                   result[:children] = @children.map(&:to_hash) unless @children.empty?
+                  result[:location] = @location if @location
+                  result[:origin] = @origin if @origin
                   result
                 end
                 
@@ -77,195 +1066,428 @@ impl DSLParser {
               
               # Window component
               class Window < Component
-                def initialize(id: nil, title: 'Window', width: 400, height: 300, x: nil, y: nil, resizable: true, draggable: true, props: {})
+                def initialize(id: nil, title: 'Window', width: 400, height: 300, x: nil, y: nil, resizable: true, draggable: true, props: {}, **extra)
                   id ||= "window_#{rand(1000000)}"
-                  super(id, props.merge(title: title, width: width, height: height, x: x, y: y, resizable: resizable, draggable: draggable))
+                  super(id, props.merge(extra).merge(title: title, width: width, height: height, x: x, y: y, resizable: resizable, draggable: draggable))
                 end
               end
               
               # Button component
               class Button < Component
-                def initialize(id: nil, text: 'Button', on_click: nil, width: nil, height: nil, icon: nil, props: {})
+                def initialize(id: nil, text: 'Button', on_click: nil, width: nil, height: nil, icon: nil, props: {}, **extra)
                   id ||= "button_#{rand(1000000)}"
-                  super(id, props.merge(text: text, on_click: on_click, width: width, height: height, icon: icon))
+                  super(id, props.merge(extra).merge(text: text, on_click: on_click, width: width, height: height, icon: icon))
                 end
               end
               
               # Text component
               class Text < Component
-                def initialize(id: nil, content: '', size: 16, color: nil, align: 'left', font: nil, props: {})
+                def initialize(id: nil, content: '', size: 16, color: nil, align: 'left', font: nil, props: {}, **extra)
                   id ||= "text_#{rand(1000000)}"
-                  super(id, props.merge(content: content, size: size, color: color, align: align, font: font))
+                  super(id, props.merge(extra).merge(content: content, size: size, color: color, align: align, font: font))
                 end
               end
               
               # Image component
               class Image < Component
-                def initialize(id: nil, source: '', width: nil, height: nil, scale: nil, props: {})
+                def initialize(id: nil, source: '', width: nil, height: nil, scale: nil, props: {}, **extra)
                   id ||= "image_#{rand(1000000)}"
-                  super(id, props.merge(source: source, width: width, height: height, scale: scale))
+                  super(id, props.merge(extra).merge(source: source, width: width, height: height, scale: scale))
                 end
               end
               
               # Canvas component
               class Canvas < Component
-                def initialize(id: nil, width: 200, height: 200, on_draw: nil, on_click: nil, props: {})
+                def initialize(id: nil, width: 200, height: 200, on_draw: nil, on_click: nil, props: {}, **extra)
                   id ||= "canvas_#{rand(1000000)}"
-                  super(id, props.merge(width: width, height: height, on_draw: on_draw, on_click: on_click))
+                  super(id, props.merge(extra).merge(width: width, height: height, on_draw: on_draw, on_click: on_click))
                 end
               end
               
               # SVG component
               class SVG < Component
-                def initialize(id: nil, source: '', width: nil, height: nil, props: {})
+                def initialize(id: nil, source: nil, markup: nil, width: nil, height: nil, props: {}, **extra)
                   id ||= "svg_#{rand(1000000)}"
-                  super(id, props.merge(source: source, width: width, height: height))
+                  super(id, props.merge(extra).merge(source: source, markup: markup, width: width, height: height))
                 end
               end
               
               # ScrollView component
               class ScrollView < Component
-                def initialize(id: nil, width: 200, height: 200, props: {})
+                def initialize(id: nil, width: 200, height: 200, props: {}, **extra)
                   id ||= "scrollview_#{rand(1000000)}"
-                  super(id, props.merge(width: width, height: height))
+                  super(id, props.merge(extra).merge(width: width, height: height))
                 end
               end
               
               # List component
               class List < Component
-                def initialize(id: nil, direction: 'vertical', spacing: 5, props: {})
+                def initialize(id: nil, direction: 'vertical', spacing: 5, props: {}, **extra)
                   id ||= "list_#{rand(1000000)}"
-                  super(id, props.merge(direction: direction, spacing: spacing))
+                  super(id, props.merge(extra).merge(direction: direction, spacing: spacing))
+                end
+              end
+              
+              # Grid component
+              class Grid < Component
+                def initialize(id: nil, columns: 2, rows: nil, spacing: 5, props: {}, **extra)
+                  id ||= "grid_#{rand(1000000)}"
+                  super(id, props.merge(extra).merge(columns: columns, rows: rows, spacing: spacing))
+                end
+              end
+              
+              # Input component
+              class Input < Component
+                def initialize(id: nil, placeholder: '', value: '', width: nil, on_change: nil, on_submit: nil, props: {}, **extra)
+                  id ||= "input_#{rand(1000000)}"
+                  super(id, props.merge(extra).merge(placeholder: placeholder, value: value, width: width, on_change: on_change, on_submit: on_submit))
+                end
+              end
+              
+              # Viewport3D component
+              class Viewport3D < Component
+                def initialize(id: nil, width: 300, height: 300, scene: nil, props: {}, **extra)
+                  id ||= "viewport3d_#{rand(1000000)}"
+                  
+                  # Configuration par défaut de la caméra
+                  camera = extra.delete(:camera) || props[:camera] || { position: [0, 5, 10], target: [0, 0, 0] }
+                  
+                  super(id, props.merge(extra).merge(width: width, height: height, scene: scene, camera: camera))
+                end
+              end
+              
+              # Layout components
+              class Stack < Component
+                def initialize(id: nil, props: {}, **extra)
+                  id ||= "stack_#{rand(1000000)}"
+                  super(id, props.merge(extra))
+                end
+              end
+              
+              class Row < Component
+                def initialize(id: nil, spacing: 5, align: 'center', props: {}, **extra)
+                  id ||= "row_#{rand(1000000)}"
+                  super(id, props.merge(extra).merge(spacing: spacing, align: align))
+                end
+              end
+              
+              class Column < Component
+                def initialize(id: nil, spacing: 5, align: 'center', props: {}, **extra)
+                  id ||= "column_#{rand(1000000)}"
+                  super(id, props.merge(extra).merge(spacing: spacing, align: align))
+                end
+              end
+              
+              # Nœuds 3D : position, rotation (degrés) et scale en [x, y, z] ; matériau
+              # color, metallic, roughness et texture
+              class Cube < Component
+                def initialize(id: nil, size: 1, props: {}, **extra)
+                  id ||= "cube_#{rand(1000000)}"
+                  super(id, props.merge(extra).merge(size: size))
+                end
+              end
+              
+              class Sphere < Component
+                def initialize(id: nil, radius: 0.5, props: {}, **extra)
+                  id ||= "sphere_#{rand(1000000)}"
+                  super(id, props.merge(extra).merge(radius: radius))
                 end
               end
               
-              # Grid component
-              class Grid < Component
-                def initialize(id: nil, columns: 2, rows: nil, spacing: 5, props: {})
-                  id ||= "grid_#{rand(1000000)}"
-                  super(id, props.merge(columns: columns, rows: rows, spacing: spacing))
+              class Plane < Component
+                def initialize(id: nil, size: 1, props: {}, **extra)
+                  id ||= "plane_#{rand(1000000)}"
+                  super(id, props.merge(extra).merge(size: size))
                 end
               end
               
-              # Input component
-              class Input < Component
-                def initialize(id: nil, placeholder: '', value: '', width: nil, on_change: nil, on_submit: nil, props: {})
-                  id ||= "input_#{rand(1000000)}"
-                  super(id, props.merge(placeholder: placeholder, value: value, width: width, on_change: on_change, on_submit: on_submit))
+              # Maillage glTF : mesh(source: "models/rock.glb#Mesh0/Primitive0")
+              class Mesh < Component
+                def initialize(id: nil, source: '', props: {}, **extra)
+                  id ||= "mesh_#{rand(1000000)}"
+                  super(id, props.merge(extra).merge(source: source))
                 end
               end
               
-              # Viewport3D component
-              class Viewport3D < Component
-                def initialize(id: nil, width: 300, height: 300, scene: nil, props: {})
-                  id ||= "viewport3d_#{rand(1000000)}"
-                  
-                  # Configuration par défaut de la caméra
-                  camera = props[:camera] || { position: [0, 5, 10], target: [0, 0, 0] }
-                  
-                  super(id, props.merge(width: width, height: height, scene: scene, camera: camera))
+              class PointLight < Component
+                def initialize(id: nil, intensity: 800, range: 20, props: {}, **extra)
+                  id ||= "point_light_#{rand(1000000)}"
+                  super(id, props.merge(extra).merge(intensity: intensity, range: range))
+                end
+                
+                def type
+                  'point_light'
                 end
               end
               
-              # Layout components
-              class Stack < Component
-                def initialize(id: nil, props: {})
-                  id ||= "stack_#{rand(1000000)}"
-                  super(id, props)
+              class DirectionalLight < Component
+                def initialize(id: nil, illuminance: 10000, props: {}, **extra)
+                  id ||= "directional_light_#{rand(1000000)}"
+                  super(id, props.merge(extra).merge(illuminance: illuminance))
+                end
+                
+                def type
+                  'directional_light'
                 end
               end
               
-              class Row < Component
-                def initialize(id: nil, spacing: 5, align: 'center', props: {})
-                  id ||= "row_#{rand(1000000)}"
-                  super(id, props.merge(spacing: spacing, align: align))
+              class Group < Component
+                def initialize(id: nil, props: {}, **extra)
+                  id ||= "group_#{rand(1000000)}"
+                  super(id, props.merge(extra))
                 end
               end
               
-              class Column < Component
-                def initialize(id: nil, spacing: 5, align: 'center', props: {})
-                  id ||= "column_#{rand(1000000)}"
-                  super(id, props.merge(spacing: spacing, align: align))
+              # État de la construction en cours, hors du module : OS est gelé
+              # par le sandbox une fois le prélude chargé
+              class Build
+                attr_accessor :components, :custom, :scopes, :counters, :parent_stack, :previous
+                
+                def initialize
+                  # Store for all created components
+                  @components = {}
+                  # Composants personnalisés (component :card do ... end)
+                  @custom = {}
+                  # Expansions de composants personnalisés en cours : { id:, name:, counters: }
+                  @scopes = []
+                  # Compteurs d'IDs automatiques hors de toute expansion
+                  @counters = {}
+                  # Composants dont le bloc est en cours d'exécution
+                  @parent_stack = []
+                  # Composants du script précédent, restaurés par rollback!
+                  @previous = nil
                 end
               end
               
+              BUILD = Build.new
+              
               # Helper methods for DSL construction
               class << self
-                # Store for all created components
-                @@components = {}
-                
                 # Define all component creation methods
-                %w(window button text image canvas svg scrollview list grid input viewport3d stack row column).each do |type|
+                BUILTIN_TYPES.each do |type, class_name|
                   define_method(type) do |**kwargs, &block|
-                    # Convert type to class name (e.g., 'viewport3d' -> 'Viewport3D')
-                    class_name = type.split('_').map(&:capitalize).join
+                    # Dans un composant personnalisé, les IDs sont préfixés par l'instance
+                    unless BUILD.scopes.empty?
+                      kwargs[:id] = scoped_id(kwargs[:id] ? kwargs[:id].to_s : next_local_id(type))
+                    end
                     
                     # Create component
                     component = OS.const_get(class_name).new(**kwargs)
+                    component.origin = BUILD.scopes.last[:name] unless BUILD.scopes.empty?
                     
                     # Register component
-                    @@components[component.id] = component
+                    BUILD.components[component.id] = component
                     
                     # Process block if given (for nested components)
-                    if block_given?
+                    if block
                       # Current component becomes parent for the block
-                      @parent_stack ||= []
-                      @parent_stack.push(component)
+                      BUILD.parent_stack.push(component)
                       
-                      # Execute block
-                      block.call
-                      
-                      # Restore parent
-                      @parent_stack.pop
+                      # Execute block, puis restaurer le parent même si le bloc lève une erreur
+                      begin
+                        block.call
+                      ensure
+                        BUILD.parent_stack.pop
+                      end
                     end
                     
                     # Add to parent if within a block
-                    unless @parent_stack.nil? || @parent_stack.empty?
-                      @parent_stack.last.add_child(component)
+                    unless BUILD.parent_stack.empty?
+                      BUILD.parent_stack.last.add_child(component)
                     end
                     
                     component
                   end
                 end
                 
+                # Déclare un composant réutilisable :
+                #   component :card do |title:, &children|
+                #     column { text(content: title); children.call }
+                #   end
+                def define_component(name, &definition)
+                  name = name.to_s
+                  raise ArgumentError, "`#{name}` est un composant intégré" if BUILTIN_TYPES.key?(name)
+                  raise ArgumentError, "component :#{name} attend un bloc" unless definition
+                  
+                  BUILD.custom[name] = definition
+                  Sandbox.privileged do
+                    Object.send(:define_method, name) do |**kwargs, &block|
+                      OS.expand(name, **kwargs, &block)
+                    end
+                  end
+                end
+                
+                # Développe un composant personnalisé en composants intégrés
+                def expand(name, id: nil, **kwargs, &children)
+                  definition = BUILD.custom[name]
+                  raise NameError, "Composant inconnu: #{name}" unless definition
+                  
+                  instance_id = scoped_id(id ? id.to_s : next_local_id(name))
+                  
+                  # Le contenu passé par l'appelant reste dans la portée de l'appelant
+                  outer = BUILD.scopes.dup
+                  content = proc { OS.with_scopes(outer) { children.call if children } }
+                  
+                  BUILD.scopes.push({ id: instance_id, name: name, counters: {} })
+                  begin
+                    definition.call(**kwargs, &content)
+                  ensure
+                    BUILD.scopes.pop
+                  end
+                end
+                
+                def with_scopes(scopes)
+                  saved = BUILD.scopes
+                  begin
+                    BUILD.scopes = scopes
+                    yield
+                  ensure
+                    BUILD.scopes = saved
+                  end
+                end
+                
+                # Préfixe un ID par l'instance de composant personnalisé courante
+                def scoped_id(local_id)
+                  scope = BUILD.scopes.last
+                  scope ? "#{scope[:id]}/#{local_id}" : local_id
+                end
+                
+                # ID automatique déterministe (type_1, type_2...) dans la portée courante
+                def next_local_id(type)
+                  counters = BUILD.scopes.empty? ? BUILD.counters : BUILD.scopes.last[:counters]
+                  counters[type] = (counters[type] || 0) + 1
+                  "#{type}_#{counters[type]}"
+                end
+                
+                # Position de l'appel dans le script utilisateur, hors prélude DSL
+                def source_location
+                  frame = caller_locations.find { |location| location.path != DSL_FILE }
+                  frame && { file: frame.path, line: frame.lineno }
+                end
+                
                 # Get all components
                 def components
-                  @@components
+                  BUILD.components
                 end
                 
                 # Get component by ID
                 def component(id)
-                  @@components[id.to_s]
+                  BUILD.components[id.to_s]
+                end
+                
+                # Thème actif : OS.theme = :light
+                def theme=(name)
+                  Themes.active = name
+                end
+                
+                def theme
+                  Themes.active || 'dark'
+                end
+                
+                # Composant affiché, modifiable depuis un handler (nil s'il n'existe pas)
+                def find(id)
+                  id = id.to_s
+                  Live.known?(id) ? Proxy.new(id) : nil
+                end
+                
+                # Composant dont le bloc est en cours d'exécution
+                def current_component
+                  BUILD.parent_stack.last
                 end
                 
                 # Reset all components
                 def reset!
-                  @@components = {}
+                  BUILD.previous = BUILD.components
+                  BUILD.components = {}
+                  BUILD.scopes = []
+                  BUILD.counters = {}
+                  BUILD.parent_stack = []
+                  Handlers.begin_reload!
+                  Reactive.begin_reload!
+                  Styles.begin_reload!
+                  Themes.begin_reload!
+                  Live.reset!
+                end
+                
+                # Le script a été appliqué : l'état précédent peut être libéré
+                def commit!
+                  BUILD.previous = nil
+                  Handlers.commit!
+                  Reactive.commit!
+                  Styles.commit!
+                  Themes.commit!
+                end
+                
+                # Le script a échoué : l'UI précédente et ses handlers restent en place
+                def rollback!
+                  BUILD.components = BUILD.previous if BUILD.previous
+                  BUILD.previous = nil
+                  Handlers.rollback!
+                  Reactive.rollback!
+                  Styles.rollback!
+                  Themes.rollback!
                 end
                 
                 # Export all components to JSON
                 def to_json
                   # Find root components (those that aren't children of others)
                   child_ids = []
-                  @@components.each_value do |component|
+                  BUILD.components.each_value do |component|
                     component.children.each do |child|
                       child_ids << child.id
                     end
                   end
                   
-                  root_components = @@components.values.reject { |c| child_ids.include?(c.id) }
+                  root_components = BUILD.components.values.reject { |c| child_ids.include?(c.id) }
                   root_components.map(&:to_hash).to_json
                 end
               end
             end
             
             # Define global DSL methods
-            %w(window button text image canvas svg scrollview list grid input viewport3d stack row column).each do |type|
-              define_method(type) do |**kwargs, &block|
+            OS::BUILTIN_TYPES.each_key do |type|
+              Object.send(:define_method, type) do |**kwargs, &block|
                 OS.send(type, **kwargs, &block)
               end
             end
             
+            # Composants réutilisables
+            def component(name, &definition)
+              OS.define_component(name, &definition)
+            end
+            
+            # État réactif : count = state(0)
+            def state(initial = nil)
+              OS::State.new(initial)
+            end
+            
+            # Feuille de style : stylesheet do rule "#sidebar text", size: 14 end
+            def stylesheet(&block)
+              raise ArgumentError, "stylesheet attend un bloc" unless block
+              OS::Styles.open(&block)
+            end
+            
+            def rule(selector, **style)
+              OS::Styles.rule(selector, style)
+            end
+            
+            # Thème : theme :ocean, extends: :dark do |t| t.color :primary, "#1478c8" end
+            def theme(name, extends: nil, &block)
+              OS::Themes.define(name, extends, &block)
+            end
+            
+            # Thème au format JSON, relatif au script appelant
+            def load_theme(path)
+              frame = caller_locations(1, 1).first
+              OS::Themes.load(path, frame ? frame.path : OS::DSL_FILE)
+            end
+            
+            # Import d'un autre script, relatif au répertoire des scripts
+            def import(path)
+              frame = caller_locations(1, 1).first
+              OS::Imports.import(path, frame ? frame.path : OS::DSL_FILE)
+            end
+            
             # Other DSL utilities
             def rgb(r, g, b)
               "rgb(#{r}, #{g}, #{b})"
@@ -275,358 +1497,805 @@ impl DSLParser {
               "rgba(#{r}, #{g}, #{b}, #{a})"
             end
             
-            # Helper for event handlers
+            # Helper for event handlers, à utiliser dans le bloc d'un composant :
+            #   button(id: "ok") { on(:click) { |e| ... } }
             def on(event, &block)
-              block.to_s
+              component = OS.current_component
+              raise ArgumentError, "on(:#{event}) doit être appelé dans le bloc d'un composant" unless component
+              
+              component.on(event, &block)
             end
         "#;
 
-        // Évaluer le code de configuration du DSL
-        self.interp.eval(dsl_setup.as_bytes())
+        // Évaluer le code de configuration du DSL sous son propre nom de fichier,
+        // pour que les positions capturées pointent vers le script utilisateur
+        self.eval_in_file(dsl_setup.as_bytes(), DSL_FILE)
             .map_err(|e| format!("Erreur lors de l'initialisation du DSL: {:?}", e))?;
 
+        // Capacités et gardes du sandbox
+        self.eval_in_file(SANDBOX_PRELUDE.as_bytes(), DSL_FILE)
+            .map_err(|e| format!("Erreur lors de l'initialisation du sandbox: {:?}", e))?;
+        if self.sandbox.enabled {
+            self.interp.eval(b"OS::Sandbox.lock!")
+                .map_err(|e| format!("Erreur lors du verrouillage du sandbox: {:?}", e))?;
+        }
+
         Ok(())
     }
 
-    /// Évalue un script DSL et retourne les composants générés
-    pub fn evaluate_dsl(&mut self, script: &str) -> Result<DSLEvaluationResult, String> {
-        // Réinitialiser les composants
-        self.interp.eval(b"OS.reset!").map_err(|e| format!("Erreur lors de la réinitialisation des composants: {:?}", e))?;
+    /// Évalue du code Ruby en l'attribuant à un fichier (backtraces, `caller_locations`)
+    fn eval_in_file(&mut self, code: &[u8], file: &str) -> Result<Value, artichoke::Error> {
+        let pushed = match Context::new(file.as_bytes().to_vec()) {
+            Some(context) => self.interp.push_context(context).is_ok(),
+            None => false,
+        };
+
+        let result = self.interp.eval(code);
+
+        if pushed {
+            let _ = self.interp.pop_context();
+        }
+        result
+    }
+
+    /// Évalue un script DSL et retourne les composants générés.
+    ///
+    /// L'évaluation et la conversion vont jusqu'au bout avant de retourner :
+    /// en cas d'échec, aucun composant n'est produit et l'erreur Ruby est détaillée.
+    /// Les problèmes de conversion sont retournés sous forme de diagnostics situés.
+    pub fn evaluate_dsl(&mut self, script: &str, script_path: &str) -> Result<DSLEvaluationResult, ScriptError> {
+        // Exposer les scripts du répertoire courant aux imports
+        let entry = Path::new(script_path)
+            .canonicalize()
+            .map(|path| path.to_string_lossy().into_owned())
+            .unwrap_or_else(|_| script_path.to_string());
+        self.mount_scripts(&ScriptLoader::for_entry(script_path), &entry)?;
+        self.grant_capabilities(&entry)?;
+        self.budget = self.sandbox.budget_for(&entry);
         
-        // Évaluer le script DSL
-        match self.interp.eval(script.as_bytes()) {
-            Ok(_) => {
-                // Récupérer les composants générés au format JSON
-                let components_json = self.interp.eval(b"OS.to_json")
-                    .map_err(|e| format!("Erreur lors de l'exportation des composants: {:?}", e))?;
-                
-                let components_str: String = self.interp.try_convert_mut(components_json)
-                    .map_err(|e| format!("Erreur lors de la conversion JSON: {:?}", e))?;
-                
-                // Convertir le JSON en structure de composants UI
-                let components = self.parse_components_json(&components_str)?;
-                
-                Ok(DSLEvaluationResult {
-                    components,
-                    errors: Vec::new(),
-                })
-            },
+        // Réinitialiser les composants (l'état précédent est conservé jusqu'au commit)
+        self.interp.eval(b"OS.reset!").map_err(|e| self.script_error(e))?;
+        
+        let result = {
+            let label = Path::new(&entry).file_name().map_or(entry.clone(), |name| name.to_string_lossy().into_owned());
+            let watchdog = Watchdog::arm(self.budget.initial_build, label);
+            let result = self.evaluate_components(script, &entry);
+            watchdog.check().map_err(ScriptError::timeout).and(result)
+        };
+        
+        // Le graphe est relevé même en cas d'échec, pour surveiller le fichier fautif
+        self.dependencies = self.read_dependencies().unwrap_or_else(|e| {
+            warn!("Graphe des imports indisponible: {}", e);
+            DependencyGraph::default()
+        });
+        
+        // Valider le nouvel état, ou restaurer les handlers de l'UI encore affichée
+        let commit = matches!(&result, Ok(eval_result) if eval_result.first_error().is_none());
+        let outcome: &[u8] = if commit { b"OS.commit!" } else { b"OS.rollback!" };
+        self.interp.eval(outcome).map_err(|e| self.script_error(e))?;
+        
+        if commit {
+            self.persist_capabilities();
+        }
+        result
+    }
+
+    /// Graphe des imports relevé lors de la dernière évaluation
+    pub fn dependencies(&self) -> &DependencyGraph {
+        &self.dependencies
+    }
+
+    /// Monte les scripts de la racine dans le système de fichiers virtuel
+    /// et prépare `OS::Imports` pour une nouvelle évaluation.
+    ///
+    /// La racine des imports est fixée à la première évaluation : un parser ne
+    /// sert ensuite que les scripts de ce répertoire.
+    fn mount_scripts(&mut self, loader: &ScriptLoader, entry: &str) -> Result<(), ScriptError> {
+        let sources = loader.sources().map_err(|e| {
+            ScriptError::internal(format!("Lecture des scripts de {} impossible: {}", loader.root().display(), e))
+        })?;
+        for (path, contents) in sources {
+            self.interp.def_rb_source_file(&path, contents)
+                .map_err(|e| ScriptError::internal(format!("Montage de {} impossible: {:?}", path.display(), e)))?;
+        }
+
+        let root = loader.root().to_string_lossy().into_owned();
+        let imports = self.interp.eval(b"OS::Imports").map_err(|e| self.script_error(e))?;
+        let root = self.interp.try_convert_mut(root).map_err(|e| self.script_error(e))?;
+        let entry = self.interp.try_convert_mut(entry).map_err(|e| self.script_error(e))?;
+        imports.funcall(&mut self.interp, "reset!", &[root, entry], None)
+            .map_err(|e| self.script_error(e))?;
+        Ok(())
+    }
+
+    /// Transmet au script les capacités qui lui sont accordées
+    fn grant_capabilities(&mut self, entry: &str) -> Result<(), ScriptError> {
+        self.interp.eval(b"OS::Sandbox.revoke_all!").map_err(|e| self.script_error(e))?;
+        self.capabilities = self.sandbox.capabilities_for(entry).to_vec();
+
+        let sandbox = self.interp.eval(b"OS::Sandbox").map_err(|e| self.script_error(e))?;
+        for capability in self.capabilities.clone() {
+            let data = capability.initial_data().map_err(ScriptError::internal)?;
+            let name = self.interp.try_convert_mut(capability.name()).map_err(|e| self.script_error(e))?;
+            let data = json_to_ruby(&mut self.interp, &data).map_err(|e| self.script_error(e))?;
+            sandbox.funcall(&mut self.interp, "grant!", &[name, data], None)
+                .map_err(|e| self.script_error(e))?;
+        }
+        Ok(())
+    }
+
+    /// Enregistre les écritures faites via les capacités du script
+    fn persist_capabilities(&mut self) {
+        if self.capabilities.is_empty() {
+            return;
+        }
+        let changes = match self.interp.eval(b"OS::Sandbox.flush!") {
+            Ok(changes) => ruby_to_json(&mut self.interp, changes),
+            Err(e) => Err(e),
+        };
+        let changes = match changes {
+            Ok(changes) => changes,
             Err(e) => {
-                // Erreur lors de l'évaluation du script
-                Ok(DSLEvaluationResult {
-                    components: Vec::new(),
-                    errors: vec![format!("Erreur d'évaluation: {:?}", e)],
-                })
+                warn!("Changements des capacités indisponibles: {:?}", e);
+                return;
+            }
+        };
+
+        for capability in &self.capabilities {
+            let Some(capability_changes) = changes.get(capability.name()) else {
+                continue;
+            };
+            if let Err(e) = capability.persist(capability_changes) {
+                warn!("Capacité `{}`: {}", capability.name(), e);
             }
         }
     }
 
+    /// Lit les arêtes `[importateur, importé]` relevées par `OS::Imports`
+    fn read_dependencies(&mut self) -> Result<DependencyGraph, String> {
+        let graph = self.interp.eval(b"OS::Imports.graph")
+            .map_err(|e| format!("{:?}", e))?;
+        let graph = ruby_to_json(&mut self.interp, graph)
+            .map_err(|e| format!("{:?}", e))?;
+        let edges: Vec<(String, String)> = serde_json::from_value(graph)
+            .map_err(|e| e.to_string())?;
+        Ok(DependencyGraph::from_edges(edges))
+    }
+
+    /// Évalue le script puis convertit les composants déclarés
+    fn evaluate_components(&mut self, script: &str, script_path: &str) -> Result<DSLEvaluationResult, ScriptError> {
+        // Évaluer le script DSL
+        self.eval_in_file(script.as_bytes(), script_path).map_err(|e| self.script_error(e))?;
+
+        // Récupérer les composants générés au format JSON
+        let components_json = self.interp.eval(b"OS.to_json")
+            .map_err(|e| self.script_error(e))?;
+        
+        let components_str: String = self.interp.try_convert_mut(components_json)
+            .map_err(|e| ScriptError::internal(format!("Erreur lors de la conversion JSON: {:?}", e)))?;
+        
+        // Convertir le JSON en structure de composants UI
+        let mut diagnostics = Vec::new();
+        let components = self.parse_components_json(&components_str, &mut diagnostics)
+            .map_err(ScriptError::internal)?;
+        
+        // Règles des feuilles de style
+        let rules = self.interp.eval(b"OS::Styles.to_json")
+            .map_err(|e| self.script_error(e))?;
+        let rules: String = self.interp.try_convert_mut(rules)
+            .map_err(|e| ScriptError::internal(format!("Erreur lors de la conversion JSON: {:?}", e)))?;
+        let stylesheet = parse_stylesheet_json(&rules, &mut diagnostics)
+            .map_err(ScriptError::internal)?;
+        
+        // Thèmes déclarés ou chargés par le script
+        let themes = self.interp.eval(b"OS::Themes.to_json")
+            .map_err(|e| self.script_error(e))?;
+        let themes: String = self.interp.try_convert_mut(themes)
+            .map_err(|e| ScriptError::internal(format!("Erreur lors de la conversion JSON: {:?}", e)))?;
+        let (themes, theme) = parse_themes_json(&themes, &ScriptLoader::for_entry(script_path), &mut diagnostics)
+            .map_err(ScriptError::internal)?;
+        
+        Ok(DSLEvaluationResult {
+            components,
+            stylesheet,
+            themes,
+            theme,
+            diagnostics,
+        })
+    }
+
+    /// Extrait la classe, le message et la ligne d'une exception Artichoke
+    fn script_error(&mut self, error: artichoke::Error) -> ScriptError {
+        let class = error.name().to_string();
+        let message = String::from_utf8_lossy(&error.message()).into_owned();
+
+        // La position vient de la backtrace ("ui.rb:12:in ..."), ou du message
+        // pour les erreurs de syntaxe ("ui.rb:12:5: syntax error, ...").
+        // Les frames du prélude sont ignorées : l'erreur est attribuée au script fautif.
+        let location = error
+            .vm_backtrace(&mut self.interp)
+            .unwrap_or_default()
+            .iter()
+            .find_map(|frame| frame_location(&String::from_utf8_lossy(frame)))
+            .or_else(|| frame_location(&message));
+
+        let (file, line) = match location {
+            Some((file, line)) => (Some(file), Some(line)),
+            None => (None, None),
+        };
+
+        // Violation du sandbox : l'API refusée est conservée par OS::Sandbox
+        let violation = if class.ends_with("Violation") {
+            self.interp
+                .eval(b"OS::Sandbox.take_violation")
+                .ok()
+                .and_then(|api| self.interp.try_convert_mut(api).ok())
+        } else {
+            None
+        };
+        ScriptError { class, message, file, line, violation }
+    }
+
     /// Convertit le JSON généré par le DSL en structure de composants Rust
-    fn parse_components_json(&self, json: &str) -> Result<Vec<UIComponent>, String> {
+    fn parse_components_json(&self, json: &str, diagnostics: &mut Vec<Diagnostic>) -> Result<Vec<UIComponent>, String> {
         let json_components: Vec<serde_json::Value> = serde_json::from_str(json)
             .map_err(|e| format!("Erreur lors de la désérialisation JSON: {:?}", e))?;
         
         let mut components = Vec::new();
+        let mut path = Vec::new();
         
         for json_component in json_components {
-            if let Some(component) = self.parse_component_value(&json_component)? {
-                components.push(component);
+            match self.parse_component_value(&json_component, &mut path, diagnostics) {
+                Some(UIComponent::Node3D(node)) => diagnostics.push(
+                    Diagnostic::warning(format!("Le nœud 3D `{}` doit être déclaré dans un `viewport3d` : ignoré", node.id))
+                        .at(SourceLocation::from_json(&json_component["location"])),
+                ),
+                Some(component) => components.push(component),
+                None => {},
             }
         }
         
         Ok(components)
     }
 
-    /// Convertit une valeur JSON en composant UI
-    fn parse_component_value(&self, value: &serde_json::Value) -> Result<Option<UIComponent>, String> {
-        let component_type = value["type"].as_str()
-            .ok_or_else(|| "Type de composant manquant".to_string())?;
-        
-        let component_id = value["id"].as_str()
-            .ok_or_else(|| "ID de composant manquant".to_string())?
-            .to_string();
-        
-        let props = &value["props"];
+    /// Convertit une valeur JSON en composant UI.
+    ///
+    /// Les problèmes rencontrés sont ajoutés à `diagnostics`, avec la position du
+    /// composant dans le script et le chemin des IDs depuis la racine.
+    pub(crate) fn parse_component_value(&self, value: &serde_json::Value, path: &mut Vec<String>, diagnostics: &mut Vec<Diagnostic>) -> Option<UIComponent> {
+        let location = SourceLocation::from_json(&value["location"]);
+        let origin = value["origin"].as_str().map(|origin| origin.to_string());
+
+        let Some(component_id) = value["id"].as_str().map(|id| id.to_string()) else {
+            diagnostics.push(Diagnostic::error("ID de composant manquant").at(location).in_component(path).from_custom(origin));
+            return None;
+        };
+
+        path.push(component_id.clone());
+        let component = self.parse_component_props(value, component_id, location, origin, path, diagnostics);
+        path.pop();
+
+        component
+    }
+
+    /// Convertit les propriétés et les enfants d'un composant identifié
+    fn parse_component_props(&self, value: &serde_json::Value, component_id: String, location: Option<SourceLocation>, origin: Option<String>, path: &mut Vec<String>, diagnostics: &mut Vec<Diagnostic>) -> Option<UIComponent> {
+        let Some(component_type) = value["type"].as_str() else {
+            diagnostics.push(Diagnostic::error("Type de composant manquant").at(location).in_component(path).from_custom(origin));
+            return None;
+        };
         
         // Parse children recursively if they exist
         let mut children = Vec::new();
         if let Some(json_children) = value.get("children") {
             if let Some(children_array) = json_children.as_array() {
                 for child_value in children_array {
-                    if let Some(child) = self.parse_component_value(child_value)? {
+                    if let Some(child) = self.parse_component_value(child_value, path, diagnostics) {
                         children.push(child);
                     }
                 }
             }
         }
         
+        // Les nœuds 3D ne vivent que dans un viewport3d ou un autre nœud 3D, qui n'accueillent qu'eux
+        let scene_parent = component_type == "viewport3d" || NODE3D_TYPES.contains(&component_type);
+        children.retain(|child| {
+            let node3d = matches!(child, UIComponent::Node3D(_));
+            if node3d == scene_parent {
+                return true;
+            }
+            let message = if scene_parent {
+                format!("`{}` ne peut contenir que des nœuds 3D : `{}` (`{}`) ignoré", component_type, child.id(), child.type_name())
+            } else {
+                format!("Le nœud 3D `{}` doit être déclaré dans un `viewport3d` : ignoré", child.id())
+            };
+            diagnostics.push(Diagnostic::warning(message).at(location.clone()).in_component(path).from_custom(origin.clone()));
+            false
+        });
+        
+        let mut props = PropReader::new(component_type, &value["props"], location.clone(), origin.clone(), path, diagnostics);
+        
         // Parse component based on its type
-        match component_type {
-            "window" => {
-                let title = props["title"].as_str().unwrap_or("Window").to_string();
-                let width = props["width"].as_f64().unwrap_or(400.0) as f32;
-                let height = props["height"].as_f64().unwrap_or(300.0) as f32;
-                let x = props["x"].as_f64().map(|v| v as f32);
-                let y = props["y"].as_f64().map(|v| v as f32);
-                let resizable = props["resizable"].as_bool();
-                let draggable = props["draggable"].as_bool();
-                
-                Ok(Some(UIComponent::Window(WindowProps {
-                    id: component_id,
-                    title,
-                    width,
-                    height,
-                    x,
-                    y,
-                    resizable,
-                    draggable,
-                    children,
-                })))
-            },
-            "button" => {
-                let text = props["text"].as_str().unwrap_or("Button").to_string();
-                let width = props["width"].as_f64().map(|v| v as f32);
-                let height = props["height"].as_f64().map(|v| v as f32);
-                let on_click = props["on_click"].as_str().map(|s| s.to_string());
-                let icon = props["icon"].as_str().map(|s| s.to_string());
-                
-                let mut style = HashMap::new();
-                if let Some(style_obj) = props.get("style") {
-                    if let Some(style_map) = style_obj.as_object() {
-                        for (key, value) in style_map {
-                            if let Some(value_str) = value.as_str() {
-                                style.insert(key.clone(), value_str.to_string());
-                            }
-                        }
-                    }
-                }
-                
-                Ok(Some(UIComponent::Button(ButtonProps {
-                    id: component_id,
-                    text,
-                    width,
-                    height,
-                    on_click,
-                    icon,
-                    style: Some(style),
-                })))
-            },
-            "text" => {
-                let content = props["content"].as_str().unwrap_or("").to_string();
-                let size = props["size"].as_f64().map(|v| v as f32);
-                let color = props["color"].as_str().map(|s| s.to_string());
-                let align = props["align"].as_str().map(|s| s.to_string());
-                let font = props["font"].as_str().map(|s| s.to_string());
-                
-                Ok(Some(UIComponent::Text(TextProps {
-                    id: component_id,
-                    content,
-                    size,
-                    color,
-                    align,
-                    font,
-                })))
-            },
-            // Les autres types de composants suivent le même modèle...
-            "image" => {
-                let source = props["source"].as_str().unwrap_or("").to_string();
-                let width = props["width"].as_f64().map(|v| v as f32);
-                let height = props["height"].as_f64().map(|v| v as f32);
-                let scale = props["scale"].as_f64().map(|v| v as f32);
-                
-                Ok(Some(UIComponent::Image(ImageProps {
-                    id: component_id,
-                    source,
-                    width,
-                    height,
-                    scale,
-                })))
-            },
-            "canvas" => {
-                let width = props["width"].as_f64().unwrap_or(200.0) as f32;
-                let height = props["height"].as_f64().unwrap_or(200.0) as f32;
-                let on_draw = props["on_draw"].as_str().map(|s| s.to_string());
-                let on_click = props["on_click"].as_str().map(|s| s.to_string());
-                
-                Ok(Some(UIComponent::Canvas(CanvasProps {
-                    id: component_id,
-                    width,
-                    height,
-                    on_draw,
-                    on_click,
-                })))
-            },
+        let component = match component_type {
+            "window" => UIComponent::Window(WindowProps {
+                id: component_id,
+                title: props.string("title").unwrap_or_else(|| "Window".to_string()),
+                width: props.number("width").unwrap_or(400.0),
+                height: props.number("height").unwrap_or(300.0),
+                x: props.number("x"),
+                y: props.number("y"),
+                resizable: props.bool("resizable"),
+                draggable: props.bool("draggable"),
+                children,
+                style: props.style(),
+                classes: props.classes(),
+            }),
+            "button" => UIComponent::Button(ButtonProps {
+                id: component_id,
+                text: props.string("text").unwrap_or_else(|| "Button".to_string()),
+                width: props.number("width"),
+                height: props.number("height"),
+                on_click: props.string("on_click"),
+                icon: props.string("icon"),
+                disabled: props.bool("disabled"),
+                style: props.style(),
+                classes: props.classes(),
+            }),
+            "text" => UIComponent::Text(TextProps {
+                id: component_id,
+                content: props.string("content").unwrap_or_default(),
+                size: props.number("size"),
+                color: props.color("color"),
+                align: props.string("align"),
+                font: props.string("font"),
+                style: props.style(),
+                classes: props.classes(),
+            }),
+            "image" => UIComponent::Image(ImageProps {
+                id: component_id,
+                source: props.string("source").unwrap_or_default(),
+                width: props.number("width"),
+                height: props.number("height"),
+                scale: props.number("scale"),
+                style: props.style(),
+                classes: props.classes(),
+            }),
+            "canvas" => UIComponent::Canvas(CanvasProps {
+                id: component_id,
+                width: props.number("width").unwrap_or(200.0),
+                height: props.number("height").unwrap_or(200.0),
+                on_draw: props.string("on_draw"),
+                on_click: props.string("on_click"),
+                on_pointer_down: props.string("on_pointer_down"),
+                on_pointer_move: props.string("on_pointer_move"),
+                on_pointer_up: props.string("on_pointer_up"),
+                on_wheel: props.string("on_wheel"),
+                on_double_click: props.string("on_double_click"),
+                style: props.style(),
+                classes: props.classes(),
+            }),
             "svg" => {
-                let source = props["source"].as_str().unwrap_or("").to_string();
-                let width = props["width"].as_f64().map(|v| v as f32);
-                let height = props["height"].as_f64().map(|v| v as f32);
-                
-                Ok(Some(UIComponent::SVG(SVGProps {
+                let source = props.string("source");
+                let markup = props.string("markup");
+                match (&source, &markup) {
+                    (None, None) => props.warn("`svg` attend `source:` ou `markup:`".to_string()),
+                    (Some(_), Some(_)) => props.warn("`svg` reçoit `source:` et `markup:` : `source` est ignorée".to_string()),
+                    _ => {},
+                }
+                let markup = markup.filter(|markup| match Svg::from_data(markup.clone().into_bytes()) {
+                    Ok(_) => true,
+                    Err(error) => {
+                        props.warn(format!("`markup:` de `svg`: {}", error));
+                        false
+                    }
+                });
+                UIComponent::SVG(SVGProps {
                     id: component_id,
                     source,
-                    width,
-                    height,
-                })))
-            },
-            "scrollview" => {
-                let width = props["width"].as_f64().unwrap_or(200.0) as f32;
-                let height = props["height"].as_f64().unwrap_or(200.0) as f32;
-                
-                Ok(Some(UIComponent::ScrollView(ScrollViewProps {
-                    id: component_id,
-                    width,
-                    height,
-                    children,
-                })))
-            },
-            "list" => {
-                let direction = props["direction"].as_str().map(|s| s.to_string());
-                let spacing = props["spacing"].as_f64().map(|v| v as f32);
-                
-                Ok(Some(UIComponent::List(ListProps {
-                    id: component_id,
-                    items: children,
-                    direction,
-                    spacing,
-                })))
-            },
-            "grid" => {
-                let columns = props["columns"].as_u64().unwrap_or(2) as usize;
-                let rows = props["rows"].as_u64().map(|v| v as usize);
-                let spacing = props["spacing"].as_f64().map(|v| v as f32);
-                
-                Ok(Some(UIComponent::Grid(GridProps {
-                    id: component_id,
-                    columns,
-                    rows,
-                    items: children,
-                    spacing,
-                })))
-            },
-            "input" => {
-                let placeholder = props["placeholder"].as_str().map(|s| s.to_string());
-                let value = props["value"].as_str().map(|s| s.to_string());
-                let width = props["width"].as_f64().map(|v| v as f32);
-                let on_change = props["on_change"].as_str().map(|s| s.to_string());
-                let on_submit = props["on_submit"].as_str().map(|s| s.to_string());
-                
-                Ok(Some(UIComponent::Input(InputProps {
-                    id: component_id,
-                    placeholder,
-                    value,
-                    width,
-                    on_change,
-                    on_submit,
-                })))
+                    markup,
+                    fill: props.color("fill"),
+                    stroke: props.color("stroke"),
+                    width: props.number("width"),
+                    height: props.number("height"),
+                    style: props.style(),
+                    classes: props.classes(),
+                })
             },
+            "scrollview" => UIComponent::ScrollView(ScrollViewProps {
+                id: component_id,
+                width: props.number("width").unwrap_or(200.0),
+                height: props.number("height").unwrap_or(200.0),
+                children,
+                style: props.style(),
+                classes: props.classes(),
+            }),
+            "list" => UIComponent::List(ListProps {
+                id: component_id,
+                items: children,
+                direction: props.string("direction"),
+                spacing: props.number("spacing"),
+                style: props.style(),
+                classes: props.classes(),
+            }),
+            "grid" => UIComponent::Grid(GridProps {
+                id: component_id,
+                columns: props.count("columns").unwrap_or(2),
+                rows: props.count("rows"),
+                items: children,
+                spacing: props.number("spacing"),
+                style: props.style(),
+                classes: props.classes(),
+            }),
+            "input" => UIComponent::Input(InputProps {
+                id: component_id,
+                placeholder: props.string("placeholder"),
+                value: props.string("value"),
+                width: props.number("width"),
+                on_change: props.string("on_change"),
+                on_submit: props.string("on_submit"),
+                disabled: props.bool("disabled"),
+                style: props.style(),
+                classes: props.classes(),
+            }),
             "viewport3d" => {
-                let width = props["width"].as_f64().unwrap_or(300.0) as f32;
-                let height = props["height"].as_f64().unwrap_or(300.0) as f32;
-                let scene = props["scene"].as_str().map(|s| s.to_string());
-                
-                let camera = if let Some(cam) = props.get("camera") {
-                    let position = if let Some(pos) = cam.get("position").and_then(|p| p.as_array()) {
-                        if pos.len() >= 3 {
-                            [
-                                pos[0].as_f64().unwrap_or(0.0) as f32,
-                                pos[1].as_f64().unwrap_or(5.0) as f32,
-                                pos[2].as_f64().unwrap_or(10.0) as f32,
-                            ]
-                        } else {
-                            [0.0, 5.0, 10.0]
-                        }
-                    } else {
-                        [0.0, 5.0, 10.0]
-                    };
-                    
-                    let target = if let Some(tgt) = cam.get("target").and_then(|t| t.as_array()) {
-                        if tgt.len() >= 3 {
-                            [
-                                tgt[0].as_f64().unwrap_or(0.0) as f32,
-                                tgt[1].as_f64().unwrap_or(0.0) as f32,
-                                tgt[2].as_f64().unwrap_or(0.0) as f32,
-                            ]
-                        } else {
-                            [0.0, 0.0, 0.0]
-                        }
-                    } else {
-                        [0.0, 0.0, 0.0]
-                    };
-                    
-                    Some(CameraProps { position, target })
-                } else {
-                    None
-                };
+                let camera = props.value("camera").map(|cam| CameraProps {
+                    position: props.vec3(cam.get("position"), "camera.position", [0.0, 5.0, 10.0]),
+                    target: props.vec3(cam.get("target"), "camera.target", [0.0, 0.0, 0.0]),
+                });
                 
-                Ok(Some(UIComponent::Viewport3D(Viewport3DProps {
+                UIComponent::Viewport3D(Viewport3DProps {
                     id: component_id,
-                    width,
-                    height,
-                    scene,
+                    width: props.number("width").unwrap_or(300.0),
+                    height: props.number("height").unwrap_or(300.0),
+                    scene: props.string("scene"),
+                    auto_frame: props.bool("auto_frame"),
+                    on_load: props.string("on_load"),
+                    on_error: props.string("on_error"),
                     camera,
-                })))
-            },
-            "stack" => {
-                Ok(Some(UIComponent::Stack(StackProps {
-                    id: component_id,
-                    children,
-                })))
-            },
-            "row" => {
-                let spacing = props["spacing"].as_f64().map(|v| v as f32);
-                let align = props["align"].as_str().map(|s| s.to_string());
-                
-                Ok(Some(UIComponent::Row(RowProps {
-                    id: component_id,
                     children,
-                    spacing,
-                    align,
-                })))
+                    style: props.style(),
+                    classes: props.classes(),
+                })
             },
-            "column" => {
-                let spacing = props["spacing"].as_f64().map(|v| v as f32);
-                let align = props["align"].as_str().map(|s| s.to_string());
-                
-                Ok(Some(UIComponent::Column(ColumnProps {
-                    id: component_id,
-                    children,
-                    spacing,
-                    align,
-                })))
+            "cube" | "sphere" | "plane" | "mesh" | "point_light" | "directional_light" | "group" => {
+                parse_node3d(component_type, component_id, children, &mut props)
             },
+            "stack" => UIComponent::Stack(StackProps {
+                id: component_id,
+                children,
+                style: props.style(),
+                classes: props.classes(),
+            }),
+            "row" => UIComponent::Row(RowProps {
+                id: component_id,
+                children,
+                spacing: props.number("spacing"),
+                align: props.string("align"),
+                style: props.style(),
+                classes: props.classes(),
+            }),
+            "column" => UIComponent::Column(ColumnProps {
+                id: component_id,
+                children,
+                spacing: props.number("spacing"),
+                align: props.string("align"),
+                style: props.style(),
+                classes: props.classes(),
+            }),
             _ => {
                 // Type de composant inconnu
-                Err(format!("Type de composant inconnu: {}", component_type))
+                diagnostics.push(
+                    Diagnostic::error(format!("Type de composant inconnu: {}", component_type))
+                        .at(location)
+                        .in_component(path)
+                        .from_custom(origin),
+                );
+                return None;
             }
+        };
+        
+        props.finish();
+        Some(component)
+    }
+
+    /// Exécute un callback Ruby et retourne le résultat.
+    ///
+    /// Les arguments sont convertis en valeurs Ruby (hash, nombres, booléens,
+    /// tableaux...) et la méthode est appelée directement, sans générer de code
+    /// source. La valeur retournée par le handler est reconvertie en JSON.
+    pub fn execute_callback(&mut self, callback_name: &str, args: &serde_json::Value) -> Result<serde_json::Value, String> {
+        let args_ruby = json_to_ruby(&mut self.interp, args)
+            .map_err(|e| format!("Erreur lors de la conversion des arguments: {:?}", e))?;
+        
+        let watchdog = Watchdog::arm(self.budget.handlers, handler_label(callback_name));
+        let result = self.dispatch(callback_name, args_ruby);
+        watchdog.check()?;
+        let result = result
+            .map_err(|e| format!("Erreur lors de l'exécution du callback {}: {}", callback_name, self.script_error(e)))?;
+        self.persist_capabilities();
+        
+        // Convertir le résultat en JSON
+        ruby_to_json(&mut self.interp, result)
+            .map_err(|e| format!("Erreur lors de la conversion du résultat: {:?}", e))
+    }
+
+    /// Exécute le `on_draw` d'un canevas avec un `OS::DrawContext` et retourne la liste
+    /// d'affichage enregistrée
+    pub fn draw_canvas(&mut self, callback_name: &str, width: f32, height: f32) -> Result<Vec<DrawCommand>, String> {
+        let size = [
+            json_to_ruby(&mut self.interp, &serde_json::json!(width)),
+            json_to_ruby(&mut self.interp, &serde_json::json!(height)),
+        ];
+        let [Ok(width), Ok(height)] = size else {
+            return Err("Erreur lors de la conversion de la taille du canevas".to_string());
+        };
+        let context = self.interp.eval(b"OS::DrawContext")
+            .and_then(|class| class.funcall(&mut self.interp, "new", &[width, height], None))
+            .map_err(|e| format!("Contexte de dessin indisponible: {}", self.script_error(e)))?;
+
+        let watchdog = Watchdog::arm(self.budget.handlers, handler_label(callback_name));
+        let drawn = self.dispatch(callback_name, context);
+        watchdog.check()?;
+        drawn.map_err(|e| format!("Erreur lors de l'exécution du dessin {}: {}", callback_name, self.script_error(e)))?;
+        self.persist_capabilities();
+
+        let commands = context.funcall(&mut self.interp, "commands", &[], None)
+            .map_err(|e| format!("Erreur lors de la lecture du dessin: {}", self.script_error(e)))?;
+        let commands = ruby_to_json(&mut self.interp, commands)
+            .map_err(|e| format!("Erreur lors de la conversion du dessin: {:?}", e))?;
+        serde_json::from_value(commands).map_err(|e| format!("Dessin invalide: {}", e))
+    }
+
+    /// Appelle un handler : les lambdas et blocs via leur handle, les noms de méthode
+    /// directement sur l'objet principal du script
+    fn dispatch(&mut self, callback_name: &str, args: Value) -> Result<Value, artichoke::Error> {
+        if callback_name.starts_with(HANDLER_PREFIX) {
+            self.call_handler(callback_name, args)
+        } else {
+            let top_self = self.interp.top_self();
+            top_self.funcall(&mut self.interp, callback_name, &[args], None)
         }
     }
 
-    /// Exécute un callback Ruby et retourne le résultat
-    pub fn execute_callback(&mut self, callback_name: &str, args: HashMap<String, String>) -> Result<String, String> {
-        // Convertir les arguments en hash Ruby
-        let args_ruby = format!(
-            "{{ {} }}", 
-            args.iter()
-                .map(|(k, v)| format!("'{}' => '{}'", k, v.replace("'", "\\'")))
-                .collect::<Vec<String>>()
-                .join(", ")
-        );
+    /// Appelle un handler enregistré côté Ruby (lambda ou bloc)
+    fn call_handler(&mut self, handle: &str, args: Value) -> Result<Value, artichoke::Error> {
+        let handlers = self.interp.eval(b"OS::Handlers")?;
+        let handle = self.interp.try_convert_mut(handle)?;
+        handlers.funcall(&mut self.interp, "call", &[handle, args], None)
+    }
+
+    /// Libère les handlers et les liaisons Ruby d'un composant retiré de l'UI
+    pub fn release_component(&mut self, component_id: &str) -> Result<(), String> {
+        let owner = self.interp.try_convert_mut(component_id)
+            .map_err(|e| format!("Erreur lors de la conversion de l'ID: {:?}", e))?;
         
-        // Construire et exécuter l'appel de fonction
-        let ruby_call = format!("{}({})", callback_name, args_ruby);
-        match self.interp.eval(ruby_call.as_bytes()) {
-            Ok(result) => {
-                // Convertir le résultat en String
-                let result_str: String = self.interp.try_convert_mut(result)
-                    .map_err(|e| format!("Erreur lors de la conversion du résultat: {:?}", e))?;
-                
-                Ok(result_str)
-            },
-            Err(e) => {
-                Err(format!("Erreur lors de l'exécution du callback: {:?}", e))
+        for registry in [&b"OS::Handlers"[..], &b"OS::Reactive"[..]] {
+            let registry = self.interp.eval(registry)
+                .map_err(|e| format!("Registre Ruby indisponible: {:?}", e))?;
+            registry
+                .funcall(&mut self.interp, "release", &[owner], None)
+                .map_err(|e| format!("Erreur lors de la libération de {}: {:?}", component_id, e))?;
+        }
+        Ok(())
+    }
+
+    /// Transmet à Ruby les valeurs relevées dans l'ECS, lues par `OS.find(id)` :
+    /// seuls les composants modifiés et retirés depuis le relevé précédent
+    pub fn push_snapshot(&mut self, snapshot: &serde_json::Value, removed: &[String]) -> Result<(), String> {
+        let snapshot = json_to_ruby(&mut self.interp, snapshot)
+            .map_err(|e| format!("Erreur lors de la conversion du relevé: {:?}", e))?;
+        let removed = json_to_ruby(&mut self.interp, &serde_json::json!(removed))
+            .map_err(|e| format!("Erreur lors de la conversion du relevé: {:?}", e))?;
+        let live = self.interp.eval(b"OS::Live")
+            .map_err(|e| format!("Registre Ruby indisponible: {:?}", e))?;
+        live.funcall(&mut self.interp, "update", &[snapshot, removed], None)
+            .map_err(|e| format!("Erreur lors de la transmission du relevé: {:?}", e))?;
+        Ok(())
+    }
+
+    /// Récupère les mises à jour mises en file par les proxies `OS.find(id)`
+    /// depuis le dernier appel, au format du protocole des handlers
+    pub fn take_queued_updates(&mut self) -> Result<serde_json::Value, String> {
+        let queued = self.interp.eval(b"OS::Live.flush!")
+            .map_err(|e| format!("Erreur lors de la lecture des mises à jour: {}", self.script_error(e)))?;
+        ruby_to_json(&mut self.interp, queued)
+            .map_err(|e| format!("Erreur lors de la conversion des mises à jour: {:?}", e))
+    }
+
+    /// Récupère les propriétés recalculées par les liaisons réactives
+    /// depuis le dernier appel (typiquement après un callback)
+    pub fn take_property_changes(&mut self) -> Result<Vec<PropertyChange>, String> {
+        let watchdog = Watchdog::arm(self.budget.handlers, "liaisons réactives");
+        let pending = self.interp.eval(b"OS::Reactive.flush!");
+        watchdog.check()?;
+        let pending = pending
+            .map_err(|e| format!("Erreur lors de la lecture des liaisons: {}", self.script_error(e)))?;
+        let pending = ruby_to_json(&mut self.interp, pending)
+            .map_err(|e| format!("Erreur lors de la conversion des liaisons: {:?}", e))?;
+        
+        serde_json::from_value(pending)
+            .map_err(|e| format!("Changement de propriété invalide: {}", e))
+    }
+}
+
+/// Convertit les propriétés d'un nœud 3D
+fn parse_node3d(component_type: &str, id: String, children: Vec<UIComponent>, props: &mut PropReader) -> UIComponent {
+    let kind = match component_type {
+        "cube" => Node3DKind::Cube { size: props.number("size").unwrap_or(1.0) },
+        "sphere" => Node3DKind::Sphere { radius: props.number("radius").unwrap_or(0.5) },
+        "plane" => Node3DKind::Plane { size: props.number("size").unwrap_or(1.0) },
+        "mesh" => {
+            let source = props.string("source").unwrap_or_default();
+            if source.is_empty() {
+                props.warn("`mesh` attend `source:` (ex: \"models/rock.glb#Mesh0/Primitive0\")".to_string());
+            }
+            Node3DKind::Mesh { source }
+        },
+        "point_light" => Node3DKind::PointLight {
+            intensity: props.number("intensity").unwrap_or(800.0),
+            range: props.number("range").unwrap_or(20.0),
+        },
+        "directional_light" => Node3DKind::DirectionalLight {
+            illuminance: props.number("illuminance").unwrap_or(10000.0),
+        },
+        _ => Node3DKind::Group,
+    };
+
+    let position = props.value("position");
+    let position = props.vec3(position, "position", [0.0; 3]);
+    let rotation = props.value("rotation");
+    let rotation = props.vec3(rotation, "rotation", [0.0; 3]);
+    let scale = props.value("scale");
+    let scale = props.vec3(scale, "scale", [1.0; 3]);
+
+    let material = match kind {
+        Node3DKind::Group => MaterialProps::default(),
+        // Une lumière n'a qu'une couleur
+        Node3DKind::PointLight { .. } | Node3DKind::DirectionalLight { .. } => MaterialProps { color: scene_color(component_type, props), ..default() },
+        _ => MaterialProps {
+            color: scene_color(component_type, props),
+            metallic: props.number("metallic"),
+            roughness: props.number("roughness"),
+            texture: props.string("texture"),
+        },
+    };
+
+    UIComponent::Node3D(Node3DProps {
+        id,
+        kind,
+        transform: Transform3DProps { position, rotation, scale },
+        material,
+        children,
+    })
+}
+
+/// Couleur d'un nœud 3D ; les jetons de thème ne s'appliquent qu'à l'UI
+fn scene_color(component_type: &str, props: &mut PropReader) -> Option<String> {
+    props.color("color").filter(|color| match ColorValue::parse(color) {
+        Ok(ColorValue::Token(_)) => {
+            props.warn(format!("Couleur `{}` de `{}`: jeton de thème non supporté en 3D", color, component_type));
+            false
+        },
+        _ => true,
+    })
+}
+
+/// Convertit les règles relevées par `OS::Styles` ; les règles invalides sont
+/// signalées et ignorées, les autres restent dans l'ordre de déclaration
+fn parse_stylesheet_json(json: &str, diagnostics: &mut Vec<Diagnostic>) -> Result<Stylesheet, String> {
+    let entries: Vec<serde_json::Value> = serde_json::from_str(json)
+        .map_err(|e| format!("Erreur lors de la désérialisation des styles: {:?}", e))?;
+
+    let mut stylesheet = Stylesheet::default();
+    for entry in entries {
+        let location = SourceLocation::from_json(&entry["location"]);
+        let selector_text = entry["selector"].as_str().unwrap_or_default();
+        let selector = match Selector::parse(selector_text) {
+            Ok(selector) => selector,
+            Err(error) => {
+                diagnostics.push(Diagnostic::warning(format!("Sélecteur `{}` ignoré: {}", selector_text, error)).at(location));
+                continue;
             }
+        };
+
+        let mut style = HashMap::new();
+        for (key, value) in entry["style"].as_object().into_iter().flatten() {
+            let value = match value {
+                serde_json::Value::String(value) => value.clone(),
+                serde_json::Value::Number(value) => value.to_string(),
+                serde_json::Value::Bool(value) => value.to_string(),
+                other => {
+                    diagnostics.push(Diagnostic::warning(format!(
+                        "Règle `{}`: la valeur de `{}` doit être un texte ou un nombre, reçu {}",
+                        selector_text, key, other
+                    )).at(location.clone()));
+                    continue;
+                }
+            };
+            style.insert(key.clone(), value);
+        }
+
+        let (style, errors) = ResolvedStyle::resolve(&style);
+        for error in errors {
+            diagnostics.push(Diagnostic::warning(format!("Règle `{}`: {}", selector_text, error)).at(location.clone()));
+        }
+        stylesheet.rules.push(StyleRule { selector, style });
+    }
+    Ok(stylesheet)
+}
+
+/// Convertit les thèmes relevés par `OS::Themes` et lit les fichiers de `load_theme`,
+/// qui doivent se trouver sous la racine des scripts ; les thèmes invalides sont
+/// signalés et ignorés
+fn parse_themes_json(json: &str, loader: &ScriptLoader, diagnostics: &mut Vec<Diagnostic>) -> Result<(Vec<ThemeDefinition>, Option<String>), String> {
+    let recorded: serde_json::Value = serde_json::from_str(json)
+        .map_err(|e| format!("Erreur lors de la désérialisation des thèmes: {:?}", e))?;
+
+    let mut themes = Vec::new();
+    for entry in recorded["definitions"].as_array().into_iter().flatten() {
+        let location = SourceLocation::from_json(&entry["location"]);
+        let definition = match entry["load"].as_str() {
+            Some(path) => {
+                let from = Path::new(entry["from"].as_str().unwrap_or_default());
+                let file = canonical(from.parent().unwrap_or(Path::new(".")).join(path));
+                if !file.starts_with(loader.root()) {
+                    diagnostics.push(Diagnostic::warning(format!("Thème `{}` ignoré: hors du répertoire des scripts", path)).at(location));
+                    continue;
+                }
+                let loaded = std::fs::read_to_string(&file)
+                    .map_err(|e| e.to_string())
+                    .and_then(|contents| serde_json::from_str(&contents).map_err(|e| e.to_string()));
+                match loaded {
+                    Ok(definition) => definition,
+                    Err(error) => {
+                        diagnostics.push(Diagnostic::warning(format!("Thème `{}` illisible: {}", path, error)).at(location));
+                        continue;
+                    }
+                }
+            },
+            None => entry.clone(),
+        };
+
+        match ThemeDefinition::from_json(&definition) {
+            Ok((definition, errors)) => {
+                for error in errors {
+                    diagnostics.push(Diagnostic::warning(format!("Thème `{}`: {}", definition.name, error)).at(location.clone()));
+                }
+                themes.push(definition);
+            },
+            Err(error) => diagnostics.push(Diagnostic::warning(format!("Thème ignoré: {}", error)).at(location)),
         }
     }
+    Ok((themes, recorded["active"].as_str().map(str::to_string)))
+}
+
+/// Nom lisible d'un handler : "#handler:ok:click" devient "ok:click"
+pub(crate) fn handler_label(callback_name: &str) -> String {
+    callback_name
+        .strip_prefix(HANDLER_PREFIX)
+        .unwrap_or(callback_name)
+        .to_string()
+}
+
+/// Extrait le fichier et la ligne d'une position Ruby de la forme "fichier:ligne[:...]",
+/// hors prélude DSL
+fn frame_location(frame: &str) -> Option<(String, u32)> {
+    let mut parts = frame.split(':');
+    let file = parts.next()?.trim();
+    if file == DSL_FILE {
+        return None;
+    }
+    let line = parts.next()?.trim().parse().ok()?;
+    Some((file.to_string(), line))
+}
+
+/// Suffixe " (fichier:ligne)" affiché avec une `ScriptError`
+fn location_suffix(file: &Option<String>, line: &Option<u32>) -> String {
+    match (file, line) {
+        (Some(file), Some(line)) => format!(" ({}:{})", file, line),
+        (None, Some(line)) => format!(" (ligne {})", line),
+        _ => String::new(),
+    }
 }
 
 // Implémentation du trait Resource pour DSLParser
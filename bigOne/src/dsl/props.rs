@@ -0,0 +1,221 @@
+// src/dsl/props.rs
+use std::collections::{HashMap, HashSet};
+use serde_json::{Map, Value};
+
+use crate::dsl::diagnostics::{Diagnostic, SourceLocation};
+use crate::ui::color::ColorValue;
+use crate::ui::style::ResolvedStyle;
+
+/// Lecture typée des propriétés d'un composant DSL.
+///
+/// Les valeurs de mauvais type et les propriétés inconnues produisent des
+/// avertissements au lieu d'être remplacées silencieusement par une valeur par défaut.
+pub struct PropReader<'a> {
+    component_type: &'a str,
+    props: Option<&'a Map<String, Value>>,
+    location: Option<SourceLocation>,
+    origin: Option<String>,
+    path: &'a [String],
+    used: HashSet<&'static str>,
+    diagnostics: &'a mut Vec<Diagnostic>,
+}
+
+impl<'a> PropReader<'a> {
+    pub fn new(
+        component_type: &'a str,
+        props: &'a Value,
+        location: Option<SourceLocation>,
+        origin: Option<String>,
+        path: &'a [String],
+        diagnostics: &'a mut Vec<Diagnostic>,
+    ) -> Self {
+        Self {
+            component_type,
+            props: props.as_object(),
+            location,
+            origin,
+            path,
+            used: HashSet::new(),
+            diagnostics,
+        }
+    }
+
+    /// Ajoute un avertissement situé sur le composant courant
+    pub fn warn(&mut self, message: String) {
+        self.diagnostics.push(
+            Diagnostic::warning(message)
+                .at(self.location.clone())
+                .in_component(self.path)
+                .from_custom(self.origin.clone()),
+        );
+    }
+
+    /// Valeur brute d'une propriété (`nil` est traité comme absent)
+    pub fn value(&mut self, key: &'static str) -> Option<&'a Value> {
+        self.used.insert(key);
+        self.props?.get(key).filter(|value| !value.is_null())
+    }
+
+    /// Propriété texte
+    pub fn string(&mut self, key: &'static str) -> Option<String> {
+        match self.value(key)? {
+            Value::String(value) => Some(value.clone()),
+            other => {
+                self.type_warning(key, "du texte", other);
+                None
+            }
+        }
+    }
+
+    /// Propriété numérique
+    pub fn number(&mut self, key: &'static str) -> Option<f32> {
+        match self.value(key)? {
+            Value::Number(value) => value.as_f64().map(|v| v as f32),
+            other => {
+                self.type_warning(key, "un nombre", other);
+                None
+            }
+        }
+    }
+
+    /// Propriété entière positive
+    pub fn count(&mut self, key: &'static str) -> Option<usize> {
+        match self.value(key)? {
+            Value::Number(value) if value.as_u64().is_some() => value.as_u64().map(|v| v as usize),
+            other => {
+                self.type_warning(key, "un entier positif", other);
+                None
+            }
+        }
+    }
+
+    /// Propriété booléenne
+    pub fn bool(&mut self, key: &'static str) -> Option<bool> {
+        match self.value(key)? {
+            Value::Bool(value) => Some(*value),
+            other => {
+                self.type_warning(key, "un booléen", other);
+                None
+            }
+        }
+    }
+
+    /// Propriété de type hash dont les valeurs sont converties en texte
+    pub fn string_map(&mut self, key: &'static str) -> Option<HashMap<String, String>> {
+        let object = match self.value(key)? {
+            Value::Object(object) => object,
+            other => {
+                self.type_warning(key, "un hash", other);
+                return None;
+            }
+        };
+
+        let mut map = HashMap::new();
+        for (entry_key, entry_value) in object {
+            match entry_value {
+                Value::String(value) => { map.insert(entry_key.clone(), value.clone()); },
+                Value::Number(value) => { map.insert(entry_key.clone(), value.to_string()); },
+                Value::Bool(value) => { map.insert(entry_key.clone(), value.to_string()); },
+                Value::Null => {},
+                other => self.warn(format!(
+                    "La valeur `{}.{}` de `{}` doit être un texte ou un nombre, reçu {}",
+                    key, entry_key, self.component_type, other
+                )),
+            }
+        }
+        Some(map)
+    }
+
+    /// Propriété couleur (syntaxe CSS ou jeton de thème) ; une valeur illisible est signalée
+    /// et ignorée
+    pub fn color(&mut self, key: &'static str) -> Option<String> {
+        let color = self.string(key)?;
+        match ColorValue::parse(&color) {
+            Ok(_) => Some(color),
+            Err(reason) => {
+                self.warn(format!("Couleur `{}` de `{}` invalide: {}", key, self.component_type, reason));
+                None
+            }
+        }
+    }
+
+    /// Propriété `style:` ; les entrées que le résolveur de style rejette sont signalées
+    pub fn style(&mut self) -> Option<HashMap<String, String>> {
+        let style = self.string_map("style")?;
+        let (_, errors) = ResolvedStyle::resolve(&style);
+        for error in errors {
+            self.warn(format!("Style de `{}`: {}", self.component_type, error));
+        }
+        Some(style)
+    }
+
+    /// Propriété `class:` : un texte (`"primary large"`) ou un tableau de noms
+    pub fn classes(&mut self) -> Vec<String> {
+        let classes = match self.value("class") {
+            None => return Vec::new(),
+            Some(Value::String(classes)) => classes.split_whitespace().map(str::to_string).collect(),
+            Some(Value::Array(classes)) if classes.iter().all(Value::is_string) => {
+                classes.iter().filter_map(Value::as_str).map(str::to_string).collect()
+            },
+            Some(other) => {
+                self.type_warning("class", "un texte ou un tableau de textes", other);
+                return Vec::new();
+            }
+        };
+        let mut unique: Vec<String> = Vec::new();
+        for class in classes {
+            if !unique.contains(&class) {
+                unique.push(class);
+            }
+        }
+        unique
+    }
+
+    /// Vecteur de trois nombres (`[x, y, z]`) lu dans une valeur imbriquée
+    pub fn vec3(&mut self, value: Option<&Value>, key: &str, default: [f32; 3]) -> [f32; 3] {
+        let Some(value) = value.filter(|value| !value.is_null()) else {
+            return default;
+        };
+
+        let components: Option<Vec<f32>> = value
+            .as_array()
+            .filter(|array| array.len() == 3)
+            .and_then(|array| array.iter().map(|v| v.as_f64().map(|v| v as f32)).collect());
+
+        match components {
+            Some(c) => [c[0], c[1], c[2]],
+            None => {
+                self.warn(format!(
+                    "La propriété `{}` de `{}` attend un tableau de trois nombres, reçu {}",
+                    key, self.component_type, value
+                ));
+                default
+            }
+        }
+    }
+
+    /// Signale les propriétés qui n'ont été lues par aucun champ du composant
+    pub fn finish(mut self) {
+        let Some(props) = self.props else {
+            return;
+        };
+
+        let mut unknown: Vec<&String> = props
+            .iter()
+            .filter(|(key, value)| !value.is_null() && !self.used.contains(key.as_str()))
+            .map(|(key, _)| key)
+            .collect();
+        unknown.sort();
+
+        for key in unknown {
+            self.warn(format!("Propriété inconnue `{}` pour `{}`", key, self.component_type));
+        }
+    }
+
+    fn type_warning(&mut self, key: &str, expected: &str, received: &Value) {
+        self.warn(format!(
+            "La propriété `{}` de `{}` attend {}, reçu {} : valeur ignorée",
+            key, self.component_type, expected, received
+        ));
+    }
+}
@@ -0,0 +1,493 @@
+// src/dsl/protocol.rs
+//! Protocole de réponse des handlers Ruby.
+//!
+//! Un handler retourne un hash (ou sa forme JSON) :
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "updates": [
+//!     { "action": "set_text", "id": "result_text", "value": "OK" },
+//!     { "action": "set_visible", "id": "sidebar", "visible": false },
+//!     { "action": "insert_child", "parent": "list", "index": 0,
+//!       "component": { "type": "text", "id": "item_1", "props": { "content": "Nouveau" } } }
+//!   ]
+//! }
+//! ```
+//!
+//! | action          | champs                                             |
+//! |-----------------|----------------------------------------------------|
+//! | `set_text`      | `id`, `value`                                      |
+//! | `set_image`     | `id`, `source`                                     |
+//! | `set_position`  | `id`, `x`, `y`                                     |
+//! | `set_size`      | `id`, `width`, `height`                            |
+//! | `set_visible`   | `id`, `visible`                                    |
+//! | `set_style`     | `id`, `style` (hash de textes ou de nombres)       |
+//! | `set_classes`   | `id`, `classes` (tableau de noms)                  |
+//! | `set_disabled`  | `id`, `disabled`                                   |
+//! | `replace`       | `id`, `component`                                  |
+//! | `insert_child`  | `parent`, `component`, `index` (optionnel)         |
+//! | `remove`        | `id`                                               |
+//! | `open_window`   | `component` (de type `window`)                     |
+//! | `close_window`  | `id`                                               |
+//! | `focus`         | `id`                                               |
+//! | `invalidate`    | `id` (canevas à redessiner)                        |
+//! | `set_theme`     | `theme` (nom d'un thème connu)                     |
+//! | `define_theme`  | `theme` (définition au format de `load_theme`)     |
+//! | `set_transform` | `id`, `position`, `rotation`, `scale` (optionnels) |
+//! | `set_material`  | `id`, `color`, `metallic`, `roughness`, `texture`  |
+//!
+//! Les champs absents de `set_transform` et `set_material` (nœuds 3D) restent inchangés.
+//!
+//! Les composants suivent le format produit par le DSL (`type`, `id`, `props`,
+//! `children`). Sans `version`, la réponse est lue comme une réponse historique :
+//! les actions en camelCase (`setText`...) y restent acceptées.
+
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use crate::dsl::diagnostics::Diagnostic;
+use crate::ui::color::parse_color;
+use crate::ui::components::{MaterialProps, UIComponent, UIComponentUpdate, UIUpdateEvent};
+use crate::ui::theme::ThemeDefinition;
+
+/// Version courante du protocole
+pub const PROTOCOL_VERSION: u64 = 1;
+
+/// Réponse décodée d'un handler
+#[derive(Debug, Default)]
+pub struct CallbackResponse {
+    /// Mises à jour valides, dans l'ordre de la réponse
+    pub updates: Vec<UIUpdateEvent>,
+    /// Entrées rejetées ; les autres mises à jour sont tout de même appliquées
+    pub errors: Vec<ProtocolError>,
+}
+
+/// Entrée de réponse invalide
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("{}{message}", .index.map(|index| format!("mise à jour #{}: ", index)).unwrap_or_default())]
+pub struct ProtocolError {
+    /// Position de l'entrée dans `updates`, si l'erreur la concerne
+    pub index: Option<usize>,
+    pub message: String,
+}
+
+impl CallbackResponse {
+    /// Ajoute les mises à jour et les erreurs d'une autre réponse, à la suite
+    pub fn append(&mut self, other: CallbackResponse) {
+        self.updates.extend(other.updates);
+        self.errors.extend(other.errors);
+    }
+}
+
+impl ProtocolError {
+    fn new(index: Option<usize>, message: impl Into<String>) -> Self {
+        Self {
+            index,
+            message: message.into(),
+        }
+    }
+}
+
+/// Valeur de style : texte ou nombre
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+enum StyleValue {
+    Text(String),
+    Number(f64),
+}
+
+/// Entrée typée de `updates`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+enum UpdateAction {
+    #[serde(alias = "setText")]
+    SetText { id: String, value: String },
+    #[serde(alias = "setImage")]
+    SetImage { id: String, source: String },
+    #[serde(alias = "setPosition")]
+    SetPosition { id: String, x: f32, y: f32 },
+    #[serde(alias = "setSize")]
+    SetSize { id: String, width: f32, height: f32 },
+    #[serde(alias = "setVisible")]
+    SetVisible { id: String, visible: bool },
+    #[serde(alias = "setStyle")]
+    SetStyle { id: String, style: HashMap<String, StyleValue> },
+    #[serde(alias = "setClasses")]
+    SetClasses { id: String, classes: Vec<String> },
+    #[serde(alias = "setDisabled")]
+    SetDisabled { id: String, disabled: bool },
+    Replace { id: String, component: serde_json::Value },
+    #[serde(alias = "insertChild")]
+    InsertChild {
+        parent: String,
+        component: serde_json::Value,
+        #[serde(default)]
+        index: Option<usize>,
+    },
+    Remove { id: String },
+    #[serde(alias = "openWindow")]
+    OpenWindow { component: serde_json::Value },
+    #[serde(alias = "closeWindow")]
+    CloseWindow { id: String },
+    Focus { id: String },
+    Invalidate { id: String },
+    #[serde(alias = "setTheme")]
+    SetTheme { theme: String },
+    #[serde(alias = "defineTheme")]
+    DefineTheme { theme: serde_json::Value },
+    #[serde(alias = "setTransform")]
+    SetTransform {
+        id: String,
+        #[serde(default)]
+        position: Option<[f32; 3]>,
+        #[serde(default)]
+        rotation: Option<[f32; 3]>,
+        #[serde(default)]
+        scale: Option<[f32; 3]>,
+    },
+    #[serde(alias = "setMaterial")]
+    SetMaterial {
+        id: String,
+        #[serde(default)]
+        color: Option<String>,
+        #[serde(default)]
+        metallic: Option<f32>,
+        #[serde(default)]
+        roughness: Option<f32>,
+        #[serde(default)]
+        texture: Option<String>,
+    },
+}
+
+/// Décode la réponse d'un handler.
+///
+/// `parse_component` convertit un composant au format du DSL ; ses diagnostics
+/// d'erreur invalident l'entrée concernée.
+pub fn parse_response<F>(response: serde_json::Value, mut parse_component: F) -> CallbackResponse
+where
+    F: FnMut(&serde_json::Value, &mut Vec<Diagnostic>) -> Option<UIComponent>,
+{
+    let mut decoded = CallbackResponse::default();
+
+    // Un handler sans valeur de retour utile ne produit pas de mise à jour
+    let response = match response {
+        serde_json::Value::String(json) => match serde_json::from_str(&json) {
+            Ok(value) => value,
+            Err(_) => return decoded,
+        },
+        serde_json::Value::Object(_) => response,
+        _ => return decoded,
+    };
+
+    match response.get("version") {
+        None => {},
+        Some(version) if version.as_u64() == Some(PROTOCOL_VERSION) => {},
+        Some(version) => {
+            decoded.errors.push(ProtocolError::new(None, format!(
+                "version de protocole non supportée: {} (version courante: {})",
+                version, PROTOCOL_VERSION
+            )));
+            return decoded;
+        },
+    }
+
+    let updates = match response.get("updates") {
+        None | Some(serde_json::Value::Null) => return decoded,
+        Some(serde_json::Value::Array(updates)) => updates,
+        Some(other) => {
+            decoded.errors.push(ProtocolError::new(None, format!("`updates` doit être un tableau, reçu {}", other)));
+            return decoded;
+        },
+    };
+
+    for (index, entry) in updates.iter().enumerate() {
+        let action = match UpdateAction::deserialize(entry) {
+            Ok(action) => action,
+            Err(e) => {
+                decoded.errors.push(ProtocolError::new(Some(index), e.to_string()));
+                continue;
+            },
+        };
+        match to_update(action, &mut parse_component) {
+            Ok(update) => decoded.updates.push(update),
+            Err(message) => decoded.errors.push(ProtocolError::new(Some(index), message)),
+        }
+    }
+    decoded
+}
+
+/// Convertit une entrée typée en événement de mise à jour, en validant ses valeurs
+fn to_update<F>(action: UpdateAction, parse_component: &mut F) -> Result<UIUpdateEvent, String>
+where
+    F: FnMut(&serde_json::Value, &mut Vec<Diagnostic>) -> Option<UIComponent>,
+{
+    let (id, update) = match action {
+        UpdateAction::SetText { id, value } => (id, UIComponentUpdate::SetText(value)),
+        UpdateAction::SetImage { id, source } => (id, UIComponentUpdate::SetImage(source)),
+        UpdateAction::SetPosition { id, x, y } => (id, UIComponentUpdate::SetPosition(x, y)),
+        UpdateAction::SetSize { id, width, height } => {
+            if width < 0.0 || height < 0.0 {
+                return Err(format!("taille négative pour `{}`: {}x{}", id, width, height));
+            }
+            (id, UIComponentUpdate::SetSize(width, height))
+        },
+        UpdateAction::SetVisible { id, visible } => (id, UIComponentUpdate::SetVisible(visible)),
+        UpdateAction::SetStyle { id, style } => {
+            let style = style
+                .into_iter()
+                .map(|(key, value)| {
+                    let value = match value {
+                        StyleValue::Text(text) => text,
+                        StyleValue::Number(number) => number.to_string(),
+                    };
+                    (key, value)
+                })
+                .collect();
+            (id, UIComponentUpdate::SetStyle(style))
+        },
+        UpdateAction::SetClasses { id, classes } => {
+            if let Some(class) = classes.iter().find(|class| class.is_empty() || class.contains(char::is_whitespace)) {
+                return Err(format!("nom de classe invalide pour `{}`: {:?}", id, class));
+            }
+            (id, UIComponentUpdate::SetClasses(classes))
+        },
+        UpdateAction::SetDisabled { id, disabled } => (id, UIComponentUpdate::SetDisabled(disabled)),
+        UpdateAction::Replace { id, component } => {
+            let component = component_from(&component, parse_component)?;
+            (id, UIComponentUpdate::Replace(component))
+        },
+        UpdateAction::InsertChild { parent, component, index } => {
+            let component = component_from(&component, parse_component)?;
+            (parent, UIComponentUpdate::InsertChild { component, index })
+        },
+        UpdateAction::Remove { id } => (id, UIComponentUpdate::Remove),
+        UpdateAction::OpenWindow { component } => {
+            let component = component_from(&component, parse_component)?;
+            if !matches!(component, UIComponent::Window(_)) {
+                return Err(format!("open_window attend un composant `window`, reçu `{}`", component.type_name()));
+            }
+            (component.id().to_string(), UIComponentUpdate::OpenWindow(component))
+        },
+        UpdateAction::CloseWindow { id } => (id, UIComponentUpdate::CloseWindow),
+        UpdateAction::Focus { id } => (id, UIComponentUpdate::Focus),
+        UpdateAction::Invalidate { id } => (id, UIComponentUpdate::Invalidate),
+        // Les thèmes visent toute l'UI : l'événement n'a pas d'ID
+        UpdateAction::SetTheme { theme } => {
+            if theme.is_empty() {
+                return Err("nom de thème vide".to_string());
+            }
+            return Ok(UIUpdateEvent { id: String::new(), update: UIComponentUpdate::SetTheme(theme) });
+        },
+        UpdateAction::DefineTheme { theme } => {
+            let (definition, errors) = ThemeDefinition::from_json(&theme)?;
+            for error in errors {
+                bevy::log::warn!("Thème `{}`: {}", definition.name, error);
+            }
+            return Ok(UIUpdateEvent { id: String::new(), update: UIComponentUpdate::DefineTheme(definition) });
+        },
+        UpdateAction::SetTransform { id, position, rotation, scale } => {
+            if position.is_none() && rotation.is_none() && scale.is_none() {
+                return Err(format!("set_transform sans `position`, `rotation` ni `scale` pour `{}`", id));
+            }
+            (id, UIComponentUpdate::SetTransform { position, rotation, scale })
+        },
+        UpdateAction::SetMaterial { id, color, metallic, roughness, texture } => {
+            if let Some(Err(reason)) = color.as_deref().map(parse_color) {
+                return Err(format!("couleur de matériau invalide pour `{}`: {}", id, reason));
+            }
+            (id, UIComponentUpdate::SetMaterial(MaterialProps { color, metallic, roughness, texture }))
+        },
+    };
+
+    if id.is_empty() {
+        return Err("ID de composant vide".to_string());
+    }
+    Ok(UIUpdateEvent { id, update })
+}
+
+fn component_from<F>(value: &serde_json::Value, parse_component: &mut F) -> Result<UIComponent, String>
+where
+    F: FnMut(&serde_json::Value, &mut Vec<Diagnostic>) -> Option<UIComponent>,
+{
+    let mut diagnostics = Vec::new();
+    let component = parse_component(value, &mut diagnostics);
+
+    if let Some(error) = diagnostics.iter().find(|diagnostic| diagnostic.is_error()) {
+        return Err(format!("composant invalide: {}", error));
+    }
+    for warning in &diagnostics {
+        bevy::log::warn!("{}", warning);
+    }
+    component.ok_or_else(|| "composant invalide".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::components::TextProps;
+    use serde_json::json;
+
+    /// Convertit les composants `text` des réponses, et signale les autres types
+    fn parse_text(value: &serde_json::Value, diagnostics: &mut Vec<Diagnostic>) -> Option<UIComponent> {
+        if value["type"] != "text" {
+            diagnostics.push(Diagnostic::error(format!("type non supporté: {}", value["type"])));
+            return None;
+        }
+        Some(UIComponent::Text(TextProps {
+            id: value["id"].as_str()?.to_string(),
+            content: value["props"]["content"].as_str().unwrap_or_default().to_string(),
+            size: None,
+            color: None,
+            align: None,
+            font: None,
+            style: None,
+            classes: Vec::new(),
+        }))
+    }
+
+    fn parse(response: serde_json::Value) -> CallbackResponse {
+        parse_response(response, parse_text)
+    }
+
+    #[test]
+    fn decodes_updates_in_order() {
+        let response = parse(json!({
+            "version": 1,
+            "updates": [
+                { "action": "set_text", "id": "status", "value": "OK" },
+                { "action": "set_visible", "id": "sidebar", "visible": false },
+                { "action": "set_style", "id": "status", "style": { "padding": 8, "color": "red" } },
+                { "action": "remove", "id": "old" },
+            ]
+        }));
+
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        let ids: Vec<&str> = response.updates.iter().map(|update| update.id.as_str()).collect();
+        assert_eq!(ids, ["status", "sidebar", "status", "old"]);
+        assert!(matches!(&response.updates[0].update, UIComponentUpdate::SetText(text) if text == "OK"));
+        assert!(matches!(response.updates[1].update, UIComponentUpdate::SetVisible(false)));
+        let UIComponentUpdate::SetStyle(style) = &response.updates[2].update else {
+            panic!("set_style attendu");
+        };
+        assert_eq!(style["padding"], "8");
+        assert_eq!(style["color"], "red");
+        assert!(matches!(response.updates[3].update, UIComponentUpdate::Remove));
+    }
+
+    #[test]
+    fn rejects_invalid_entries_and_keeps_the_others() {
+        let response = parse(json!({
+            "version": 1,
+            "updates": [
+                { "action": "set_size", "id": "box", "width": -1, "height": 10 },
+                { "action": "set_text", "id": "label", "value": "ok" },
+                { "action": "explode", "id": "label" },
+                { "action": "set_classes", "id": "label", "classes": ["a b"] },
+                { "action": "set_text", "id": "", "value": "sans id" },
+            ]
+        }));
+
+        assert_eq!(response.updates.len(), 1);
+        assert_eq!(response.updates[0].id, "label");
+        let indices: Vec<Option<usize>> = response.errors.iter().map(|error| error.index).collect();
+        assert_eq!(indices, [Some(0), Some(2), Some(3), Some(4)]);
+    }
+
+    #[test]
+    fn rejects_unsupported_versions() {
+        let response = parse(json!({
+            "version": 2,
+            "updates": [{ "action": "set_text", "id": "status", "value": "OK" }]
+        }));
+
+        assert!(response.updates.is_empty());
+        assert_eq!(response.errors.len(), 1);
+        assert_eq!(response.errors[0].index, None);
+    }
+
+    #[test]
+    fn accepts_legacy_camel_case_and_json_strings() {
+        let response = parse(json!(r#"{ "updates": [{ "action": "setText", "id": "status", "value": "OK" }] }"#));
+
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        assert!(matches!(&response.updates[0].update, UIComponentUpdate::SetText(text) if text == "OK"));
+    }
+
+    #[test]
+    fn ignores_responses_without_updates() {
+        for response in [json!(null), json!(42), json!("pas du json"), json!({ "version": 1 })] {
+            let decoded = parse(response);
+            assert!(decoded.updates.is_empty());
+            assert!(decoded.errors.is_empty());
+        }
+
+        let decoded = parse(json!({ "updates": "set_text" }));
+        assert_eq!(decoded.errors.len(), 1);
+    }
+
+    #[test]
+    fn converts_components_with_the_given_parser() {
+        let response = parse(json!({
+            "version": 1,
+            "updates": [
+                { "action": "insert_child", "parent": "list", "index": 0,
+                  "component": { "type": "text", "id": "item_1", "props": { "content": "Nouveau" } } },
+                { "action": "replace", "id": "title", "component": { "type": "image", "id": "title" } },
+                { "action": "open_window", "component": { "type": "text", "id": "popup" } },
+            ]
+        }));
+
+        assert_eq!(response.updates.len(), 1);
+        assert_eq!(response.updates[0].id, "list");
+        let UIComponentUpdate::InsertChild { component, index } = &response.updates[0].update else {
+            panic!("insert_child attendu");
+        };
+        assert_eq!(component.id(), "item_1");
+        assert_eq!(*index, Some(0));
+
+        // Composant refusé par le parser, puis fenêtre qui n'en est pas une
+        let indices: Vec<Option<usize>> = response.errors.iter().map(|error| error.index).collect();
+        assert_eq!(indices, [Some(1), Some(2)]);
+    }
+
+    #[test]
+    fn theme_updates_target_the_whole_ui() {
+        let response = parse(json!({
+            "version": 1,
+            "updates": [
+                { "action": "set_theme", "theme": "light" },
+                { "action": "define_theme", "theme": { "name": "ocean", "colors": { "primary": "#1478c8" } } },
+                { "action": "set_theme", "theme": "" },
+            ]
+        }));
+
+        assert_eq!(response.updates.len(), 2);
+        assert!(response.updates.iter().all(|update| update.id.is_empty()));
+        assert!(matches!(&response.updates[0].update, UIComponentUpdate::SetTheme(name) if name == "light"));
+        assert!(matches!(&response.updates[1].update, UIComponentUpdate::DefineTheme(theme) if theme.name == "ocean"));
+        assert_eq!(response.errors.len(), 1);
+    }
+
+    #[test]
+    fn validates_3d_updates() {
+        let response = parse(json!({
+            "version": 1,
+            "updates": [
+                { "action": "set_transform", "id": "cube", "position": [1.0, 2.0, 3.0] },
+                { "action": "set_transform", "id": "cube" },
+                { "action": "set_material", "id": "cube", "color": "rgb(10, 20, 30)", "metallic": 0.5 },
+                { "action": "set_material", "id": "cube", "color": "pas une couleur" },
+            ]
+        }));
+
+        assert_eq!(response.updates.len(), 2);
+        assert!(matches!(
+            response.updates[0].update,
+            UIComponentUpdate::SetTransform { position: Some([1.0, 2.0, 3.0]), rotation: None, scale: None }
+        ));
+        assert!(matches!(&response.updates[1].update, UIComponentUpdate::SetMaterial(material) if material.metallic == Some(0.5)));
+        let indices: Vec<Option<usize>> = response.errors.iter().map(|error| error.index).collect();
+        assert_eq!(indices, [Some(1), Some(3)]);
+    }
+}
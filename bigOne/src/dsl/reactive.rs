@@ -0,0 +1,95 @@
+// src/dsl/reactive.rs
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use crate::ui::components::{MaterialProps, UIComponentUpdate, UIUpdateEvent, NODE3D_TYPES};
+
+/// Changement de propriété produit par une liaison réactive côté Ruby
+/// (voir `OS::Reactive.flush!` dans le prélude)
+#[derive(Debug, Clone, Deserialize)]
+pub struct PropertyChange {
+    /// ID du composant concerné
+    pub id: String,
+    /// Nom de la propriété recalculée
+    pub prop: String,
+    /// Nouvelle valeur
+    pub value: serde_json::Value,
+    /// Propriétés courantes du composant, pour les mises à jour qui en combinent plusieurs
+    #[serde(default)]
+    pub props: serde_json::Value,
+    /// Type du composant, qui distingue les propriétés des nœuds 3D
+    #[serde(default, rename = "type")]
+    pub component_type: String,
+}
+
+impl PropertyChange {
+    /// Traduit le changement en mise à jour UI
+    pub fn to_update(&self) -> Option<UIComponentUpdate> {
+        if NODE3D_TYPES.contains(&self.component_type.as_str()) {
+            return self.scene_update();
+        }
+        match self.prop.as_str() {
+            "content" | "text" | "title" | "value" => Some(UIComponentUpdate::SetText(text_value(&self.value))),
+            "source" => self.value.as_str().map(|source| UIComponentUpdate::SetImage(source.to_string())),
+            "visible" => self.value.as_bool().map(UIComponentUpdate::SetVisible),
+            "x" | "y" => Some(UIComponentUpdate::SetPosition(self.number("x")?, self.number("y")?)),
+            "width" | "height" => Some(UIComponentUpdate::SetSize(self.number("width")?, self.number("height")?)),
+            "class" => Some(UIComponentUpdate::SetClasses(class_names(&self.value))),
+            "disabled" => self.value.as_bool().map(UIComponentUpdate::SetDisabled),
+            _ => Some(UIComponentUpdate::SetStyle(HashMap::from([
+                (self.prop.clone(), text_value(&self.value)),
+            ]))),
+        }
+    }
+
+    /// Transformation, matériau ou visibilité d'un nœud 3D ; les autres propriétés
+    /// (taille, source...) ne sont pas liables
+    fn scene_update(&self) -> Option<UIComponentUpdate> {
+        let vec3 = || serde_json::from_value::<[f32; 3]>(self.value.clone()).ok();
+        let number = || self.value.as_f64().map(|v| v as f32);
+        match self.prop.as_str() {
+            "position" => Some(UIComponentUpdate::SetTransform { position: Some(vec3()?), rotation: None, scale: None }),
+            "rotation" => Some(UIComponentUpdate::SetTransform { position: None, rotation: Some(vec3()?), scale: None }),
+            "scale" => Some(UIComponentUpdate::SetTransform { position: None, rotation: None, scale: Some(vec3()?) }),
+            "color" => Some(UIComponentUpdate::SetMaterial(MaterialProps { color: Some(text_value(&self.value)), ..Default::default() })),
+            "texture" => Some(UIComponentUpdate::SetMaterial(MaterialProps { texture: Some(text_value(&self.value)), ..Default::default() })),
+            "metallic" => Some(UIComponentUpdate::SetMaterial(MaterialProps { metallic: Some(number()?), ..Default::default() })),
+            "roughness" => Some(UIComponentUpdate::SetMaterial(MaterialProps { roughness: Some(number()?), ..Default::default() })),
+            "visible" => self.value.as_bool().map(UIComponentUpdate::SetVisible),
+            _ => None,
+        }
+    }
+
+    /// Lit une propriété numérique courante du composant
+    fn number(&self, key: &str) -> Option<f32> {
+        self.props.get(key)?.as_f64().map(|v| v as f32)
+    }
+}
+
+/// Convertit une liste de changements en événements de mise à jour UI
+pub fn property_updates(changes: Vec<PropertyChange>) -> Vec<UIUpdateEvent> {
+    changes
+        .into_iter()
+        .filter_map(|change| {
+            let update = change.to_update()?;
+            Some(UIUpdateEvent { id: change.id, update })
+        })
+        .collect()
+}
+
+/// Classes liées : un texte (`"primary large"`) ou un tableau de noms
+fn class_names(value: &serde_json::Value) -> Vec<String> {
+    match value {
+        serde_json::Value::Array(names) => names.iter().map(text_value).collect(),
+        other => text_value(other).split_whitespace().map(str::to_string).collect(),
+    }
+}
+
+/// Représentation texte d'une valeur liée
+fn text_value(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(s) => s.clone(),
+        serde_json::Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
@@ -0,0 +1,490 @@
+// src/dsl/sandbox.rs
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use crate::dsl::budget::ExecutionBudget;
+use crate::dsl::loader::canonical;
+
+/// Taille maximale d'un fichier exposé par la capacité `files`
+const MAX_FILE_SIZE: u64 = 1024 * 1024;
+
+/// Fichier de la capacité `storage`, dans le répertoire accordé
+const STORAGE_FILE: &str = "storage.json";
+
+/// API système accordée explicitement à un script
+#[derive(Debug, Clone, PartialEq)]
+pub enum Capability {
+    /// Stockage clé/valeur persistant : `capability(:storage)["theme"] = "sombre"`
+    Storage(PathBuf),
+    /// Fichiers texte d'un répertoire : `capability(:files).read("notes.txt")`
+    Files { root: PathBuf, writable: bool },
+}
+
+impl Capability {
+    /// Nom de la capacité côté Ruby
+    pub fn name(&self) -> &'static str {
+        match self {
+            Capability::Storage(_) => "storage",
+            Capability::Files { .. } => "files",
+        }
+    }
+
+    /// Données initiales transmises à l'objet Ruby de la capacité
+    pub(crate) fn initial_data(&self) -> Result<serde_json::Value, String> {
+        match self {
+            Capability::Storage(dir) => {
+                let path = dir.join(STORAGE_FILE);
+                if !path.exists() {
+                    return Ok(serde_json::Value::Object(Default::default()));
+                }
+                let contents = fs::read_to_string(&path)
+                    .map_err(|e| format!("Lecture de {} impossible: {}", path.display(), e))?;
+                serde_json::from_str(&contents)
+                    .map_err(|e| format!("Stockage {} invalide: {}", path.display(), e))
+            },
+            Capability::Files { root, writable } => {
+                let mut files = serde_json::Map::new();
+                if root.is_dir() {
+                    collect_text_files(root, root, &mut files)
+                        .map_err(|e| format!("Lecture de {} impossible: {}", root.display(), e))?;
+                }
+                Ok(serde_json::json!({ "files": files, "writable": writable }))
+            },
+        }
+    }
+
+    /// Enregistre les changements remontés par `OS::Sandbox.flush!`
+    pub(crate) fn persist(&self, changes: &serde_json::Value) -> Result<(), String> {
+        match self {
+            Capability::Storage(dir) => {
+                if changes.is_null() {
+                    return Ok(());
+                }
+                fs::create_dir_all(dir)
+                    .map_err(|e| format!("Création de {} impossible: {}", dir.display(), e))?;
+                let path = dir.join(STORAGE_FILE);
+                let contents = serde_json::to_string_pretty(changes).map_err(|e| e.to_string())?;
+                fs::write(&path, contents)
+                    .map_err(|e| format!("Écriture de {} impossible: {}", path.display(), e))
+            },
+            Capability::Files { root, writable } => {
+                let writes: Vec<(String, String)> = serde_json::from_value(changes.clone())
+                    .map_err(|e| format!("Écritures de fichiers invalides: {}", e))?;
+                if writes.is_empty() {
+                    return Ok(());
+                }
+                if !writable {
+                    return Err(format!("Le répertoire {} est en lecture seule", root.display()));
+                }
+                for (relative, contents) in writes {
+                    // Le chemin est revérifié ici : l'objet Ruby n'est pas une garantie
+                    let path = confined_path(root, &relative)
+                        .ok_or_else(|| format!("Chemin hors du répertoire accordé: {}", relative))?;
+                    if let Some(parent) = path.parent() {
+                        fs::create_dir_all(parent)
+                            .map_err(|e| format!("Création de {} impossible: {}", parent.display(), e))?;
+                    }
+                    fs::write(&path, contents)
+                        .map_err(|e| format!("Écriture de {} impossible: {}", path.display(), e))?;
+                }
+                Ok(())
+            },
+        }
+    }
+}
+
+/// Configuration du sandbox d'un `DSLParser`.
+///
+/// En mode sandbox, les scripts n'ont accès ni à `eval` ni au système
+/// (`system`, backticks, `File`, `IO`, `ENV`...) ; les capacités et les budgets
+/// d'exécution sont définis script par script.
+#[derive(Debug, Clone)]
+pub struct SandboxConfig {
+    /// Restreindre l'interpréteur
+    pub enabled: bool,
+    /// Script principal => capacités accordées
+    grants: HashMap<PathBuf, Vec<Capability>>,
+    /// Script principal => budget d'exécution, s'il diffère du budget par défaut
+    budgets: HashMap<PathBuf, ExecutionBudget>,
+}
+
+impl SandboxConfig {
+    /// Sandbox actif, sans aucune capacité
+    pub fn sandboxed() -> Self {
+        Self {
+            enabled: true,
+            grants: HashMap::new(),
+            budgets: HashMap::new(),
+        }
+    }
+
+    /// Interpréteur sans restriction (outils de développement uniquement)
+    pub fn unrestricted() -> Self {
+        Self {
+            enabled: false,
+            ..Self::sandboxed()
+        }
+    }
+
+    /// Accorde une capacité à un script principal et à ses imports
+    pub fn grant(mut self, script_path: impl AsRef<Path>, capability: Capability) -> Self {
+        self.grants.entry(canonical(script_path)).or_default().push(capability);
+        self
+    }
+
+    /// Définit le budget d'exécution d'un script principal
+    pub fn budget(mut self, script_path: impl AsRef<Path>, budget: ExecutionBudget) -> Self {
+        self.budgets.insert(canonical(script_path), budget);
+        self
+    }
+
+    /// Budget d'exécution d'un script principal
+    pub fn budget_for(&self, script_path: impl AsRef<Path>) -> ExecutionBudget {
+        self.budgets
+            .get(&canonical(script_path))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Capacités accordées à un script principal
+    pub fn capabilities_for(&self, script_path: impl AsRef<Path>) -> &[Capability] {
+        self.grants
+            .get(&canonical(script_path))
+            .map(Vec::as_slice)
+            .unwrap_or(&[])
+    }
+}
+
+impl Default for SandboxConfig {
+    fn default() -> Self {
+        Self::sandboxed()
+    }
+}
+
+/// Chemin relatif résolu sous `root`, s'il ne peut pas en sortir
+fn confined_path(root: &Path, relative: &str) -> Option<PathBuf> {
+    let relative = Path::new(relative);
+    if relative.components().all(|component| matches!(component, Component::Normal(_)))
+        && relative.components().next().is_some()
+    {
+        Some(root.join(relative))
+    } else {
+        None
+    }
+}
+
+fn collect_text_files(root: &Path, dir: &Path, files: &mut serde_json::Map<String, serde_json::Value>) -> std::io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        if path.is_dir() {
+            collect_text_files(root, &path, files)?;
+            continue;
+        }
+        if entry.metadata()?.len() > MAX_FILE_SIZE {
+            continue;
+        }
+        // Seuls les fichiers texte sont exposés
+        if let (Ok(contents), Ok(relative)) = (fs::read_to_string(&path), path.strip_prefix(root)) {
+            let key = relative.to_string_lossy().replace('\\', "/");
+            files.insert(key, serde_json::Value::String(contents));
+        }
+    }
+    Ok(())
+}
+
+/// Verrouillage de l'interpréteur, évalué après le prélude du DSL.
+///
+/// Les méthodes dangereuses sont remplacées par des gardes ; la version d'origine
+/// n'est plus accessible qu'aux appels privilégiés du prélude (`OS::Sandbox.privileged`),
+/// reconnus à leur fichier source.
+pub(crate) const SANDBOX_PRELUDE: &str = r#"
+    module OS
+      module Sandbox
+        # Accès refusé par le sandbox
+        class Violation < SecurityError
+          attr_reader :api
+
+          def initialize(api)
+            @api = api
+            super("`#{api}` n'est pas autorisé dans le sandbox")
+          end
+        end
+
+        # Remplace une constante système (File, IO, ENV...) : tout accès est une violation
+        class Blocked < BasicObject
+          def initialize(api, deny)
+            @api = api
+            @deny = deny
+          end
+
+          def method_missing(name, *args, &block)
+            @deny.call("#{@api}.#{name}")
+          end
+
+          def respond_to_missing?(name, include_private = false)
+            true
+          end
+        end
+
+        # Stockage clé/valeur persistant (capacité :storage)
+        class Storage
+          def initialize(data)
+            @data = data.is_a?(Hash) ? data : {}
+            @dirty = false
+          end
+
+          def [](key)
+            @data[key.to_s]
+          end
+
+          def []=(key, value)
+            @dirty = true
+            @data[key.to_s] = value
+          end
+
+          def delete(key)
+            @dirty = true
+            @data.delete(key.to_s)
+          end
+
+          def keys
+            @data.keys
+          end
+
+          def take_changes
+            return nil unless @dirty
+            @dirty = false
+            @data
+          end
+        end
+
+        # Fichiers texte d'un répertoire accordé (capacité :files)
+        class Files
+          def initialize(data)
+            @files = data['files'] || {}
+            @writable = data['writable'] == true
+            @writes = []
+          end
+
+          def list
+            @files.keys.sort
+          end
+
+          def exist?(path)
+            @files.key?(relative(path))
+          end
+
+          def read(path)
+            key = relative(path)
+            raise ArgumentError, "Fichier introuvable: #{path}" unless @files.key?(key)
+            @files[key]
+          end
+
+          def write(path, content)
+            Sandbox.violation!("files.write") unless @writable
+            key = relative(path)
+            @files[key] = content.to_s
+            @writes << [key, content.to_s]
+            content
+          end
+
+          def take_changes
+            writes = @writes
+            @writes = []
+            writes
+          end
+
+          private
+
+          # Chemins relatifs au répertoire accordé uniquement
+          def relative(path)
+            parts = path.to_s.split('/').reject { |part| part.empty? || part == '.' }
+            if path.to_s.start_with?('/') || parts.include?('..') || parts.empty?
+              Sandbox.violation!("files:#{path}")
+            end
+            parts.join('/')
+          end
+        end
+
+        CAPABILITIES = { 'storage' => Storage, 'files' => Files }
+
+        # Méthodes gardées : propriétaire => [méthode, API signalée]
+        GUARDED = [
+          [Kernel, :eval, 'eval'], [Kernel, :binding, 'binding'],
+          [BasicObject, :instance_eval, 'instance_eval'], [BasicObject, :instance_exec, 'instance_exec'],
+          [Module, :class_eval, 'class_eval'], [Module, :module_eval, 'module_eval'],
+          [Module, :class_exec, 'class_exec'], [Module, :module_exec, 'module_exec'],
+          [Proc, :binding, 'Proc#binding'],
+          [Kernel, :system, 'system'], [Kernel, :`, '`commande`'], [Kernel, :exec, 'exec'],
+          [Kernel, :spawn, 'spawn'], [Kernel, :fork, 'fork'], [Kernel, :open, 'open'],
+          [Kernel, :require, 'require'], [Kernel, :require_relative, 'require_relative'],
+          [Kernel, :load, 'load'],
+        ]
+
+        # Constantes système remplacées
+        BLOCKED_CONSTANTS = %i[File IO Dir ENV Process ObjectSpace]
+
+        # Méthodes qui modifient un module, refusées sur les modules verrouillés
+        MUTATORS = [
+          [Module, :define_method], [Module, :remove_method], [Module, :undef_method],
+          [Module, :alias_method], [Module, :const_set], [Module, :remove_const],
+          [Module, :class_variable_set], [Kernel, :define_singleton_method],
+          [Kernel, :instance_variable_set],
+        ]
+
+        # Capacités accordées au script en cours : nom => objet Ruby
+        GRANTED = {}
+
+        @@last_violation = nil
+
+        def self.violation!(api)
+          @@last_violation = api
+          raise Violation.new(api)
+        end
+
+        # API de la dernière violation, lue par Rust pour structurer l'erreur
+        def self.take_violation
+          api = @@last_violation
+          @@last_violation = nil
+          api
+        end
+
+        def self.grant!(name, data)
+          capability = CAPABILITIES[name.to_s]
+          raise ArgumentError, "Capacité inconnue: #{name}" unless capability
+          GRANTED[name.to_s] = capability.new(data)
+        end
+
+        def self.revoke_all!
+          GRANTED.clear
+        end
+
+        def self.capability(name)
+          GRANTED[name.to_s] || violation!("capability(:#{name})")
+        end
+
+        # Changements à persister, par capacité
+        def self.flush!
+          GRANTED.map { |name, capability| [name, capability.take_changes] }.to_h
+        end
+
+        # Sans sandbox, les appels privilégiés sont des appels ordinaires
+        def self.privileged
+          yield
+        end
+
+        # Les gardes n'utilisent que des valeurs capturées ici : la classe des violations,
+        # le fichier du prélude et les méthodes d'origine. Les modules dont elles dépendent
+        # sont ensuite gelés, un script ne peut donc plus les redéfinir.
+        def self.lock!
+          return true if frozen?
+
+          depth = 0
+          last_violation = nil
+          violation = Violation
+          prelude = ::OS::DSL_FILE.dup.freeze
+          define = Module.instance_method(:define_method)
+          make_private = Module.instance_method(:private)
+          sandbox = singleton_class
+
+          deny = lambda do |api|
+            last_violation = api
+            raise violation.new(api)
+          end
+
+          define.bind(sandbox).call(:violation!) { |api| deny.call(api) }
+          define.bind(sandbox).call(:take_violation) do
+            api = last_violation
+            last_violation = nil
+            api
+          end
+
+          # Réservé au prélude : un script ne peut pas se déclarer privilégié
+          define.bind(sandbox).call(:privileged) do |&block|
+            frame = caller_locations(1, 1).first
+            deny.call('privileged') unless frame && frame.path == prelude
+
+            depth += 1
+            begin
+              block.call
+            ensure
+              depth -= 1
+            end
+          end
+
+          BLOCKED_CONSTANTS.each do |name|
+            next unless Object.const_defined?(name)
+            Object.send(:remove_const, name)
+            Object.const_set(name, Blocked.new(name.to_s, deny))
+          end
+
+          guard = lambda do |owner, name, api, allowed|
+            next unless owner.method_defined?(name) || owner.private_method_defined?(name)
+            was_private = owner.private_method_defined?(name)
+            original = owner.instance_method(name)
+
+            define.bind(owner).call(name) do |*args, **kwargs, &block|
+              unless depth > 0 || (allowed && allowed.call(self, *args))
+                deny.call(api.respond_to?(:call) ? api.call(*args) : api)
+              end
+
+              # Le code appelé (un fichier importé, un bloc) ne reste pas privilégié
+              saved = depth
+              depth = 0
+              begin
+                original.bind(self).call(*args, **kwargs, &block)
+              ensure
+                depth = saved
+              end
+            end
+            make_private.bind(owner).call(name) if was_private
+          end
+
+          GUARDED.each { |owner, name, api| guard.call(owner, name, api, nil) }
+
+          # send n'atteint que les méthodes publiques
+          public_only = lambda { |receiver, name, *| receiver.respond_to?(name) }
+          send_api = lambda { |name, *| "send(:#{name})" }
+          guard.call(Kernel, :send, send_api, public_only)
+          guard.call(BasicObject, :__send__, send_api, public_only)
+
+          # Modules verrouillés : OS et tous les modules et classes du prélude (le sandbox
+          # compris), les modules patchés par les gardes, et les classes qu'appellent les
+          # gardes et le code privilégié
+          locked = [Kernel, BasicObject, Module, Class, Proc, Method, UnboundMethod, String, Array, Hash]
+          pending = [::OS]
+          until pending.empty?
+            namespace = pending.shift
+            next if locked.include?(namespace)
+            locked << namespace
+            namespace.constants(false).each do |name|
+              value = namespace.const_get(name)
+              pending << value if Module === value
+            end
+          end
+          location = caller_locations(0, 1).first
+          locked << location.class if location
+          locked += locked.map(&:singleton_class)
+
+          unlocked = lambda { |receiver, *| locked.none? { |locked_module| locked_module.equal?(receiver) } }
+          MUTATORS.each { |owner, name| guard.call(owner, name, name.to_s, unlocked) }
+
+          locked.each(&:freeze)
+          true
+        end
+      end
+
+      class << self
+        def capability(name)
+          Sandbox.capability(name)
+        end
+      end
+    end
+
+    # Capacité accordée au script : storage = capability(:storage)
+    def capability(name)
+      OS::Sandbox.capability(name)
+    end
+"#;
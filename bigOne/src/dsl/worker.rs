@@ -0,0 +1,428 @@
+// src/dsl/worker.rs
+use std::collections::VecDeque;
+use std::path::Path;
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender};
+use std::sync::Mutex;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::dsl::loader::DependencyGraph;
+use crate::dsl::parser::{handler_label, DSLEvaluationResult, DSLParser, ScriptError};
+use crate::dsl::protocol::{self, CallbackResponse};
+use crate::dsl::reactive::PropertyChange;
+use crate::dsl::sandbox::SandboxConfig;
+use crate::ui::canvas::DrawCommand;
+
+/// Travail demandé à l'interpréteur Ruby
+#[derive(Debug, Clone)]
+pub enum RubyRequest {
+    /// Évaluer un script principal (construction ou rechargement de l'UI)
+    Evaluate { script_path: String, script: String },
+    /// Exécuter un handler d'événement
+    Callback { callback: String, arguments: serde_json::Value },
+    /// Libérer les handlers et liaisons d'un composant retiré
+    Release { id: String },
+    /// Mettre à jour les valeurs des composants affichés lues par `OS.find(id)` :
+    /// composants modifiés depuis le relevé précédent, et IDs des composants retirés
+    Snapshot { components: serde_json::Value, removed: Vec<String> },
+    /// Exécuter le `on_draw` d'un canevas de `width` x `height` pixels logiques
+    Draw { id: String, callback: String, width: f32, height: f32 },
+}
+
+impl RubyRequest {
+    /// Requête en attente de réponse, ou `None` si aucune réponse n'est retournée
+    fn in_flight(&self) -> Option<InFlight> {
+        match self {
+            RubyRequest::Evaluate { script_path, .. } => Some(InFlight {
+                label: Path::new(script_path)
+                    .file_name()
+                    .map_or(script_path.clone(), |name| name.to_string_lossy().into_owned()),
+                evaluation: true,
+            }),
+            RubyRequest::Callback { callback, .. } | RubyRequest::Draw { callback, .. } => Some(InFlight {
+                label: handler_label(callback),
+                evaluation: false,
+            }),
+            RubyRequest::Release { .. } | RubyRequest::Snapshot { .. } => None,
+        }
+    }
+}
+
+/// Requête envoyée dont la réponse n'a pas encore été relevée
+#[derive(Debug, Clone, PartialEq)]
+pub struct InFlight {
+    /// Script évalué ou handler appelé, tel qu'affiché dans les erreurs
+    pub label: String,
+    /// Évaluation complète d'un script, plutôt qu'un handler
+    pub evaluation: bool,
+}
+
+/// Résultat d'une requête, dans l'ordre des requêtes
+pub enum RubyResponse {
+    Evaluated {
+        script_path: String,
+        result: Result<DSLEvaluationResult, ScriptError>,
+        /// Graphe des imports relevé pendant l'évaluation, même en cas d'échec
+        dependencies: DependencyGraph,
+    },
+    Callback {
+        callback: String,
+        /// Réponse décodée selon le protocole des handlers
+        result: Result<CallbackResponse, String>,
+        /// Propriétés recalculées par les états modifiés dans le handler
+        property_changes: Result<Vec<PropertyChange>, String>,
+    },
+    Drawn {
+        id: String,
+        result: Result<Vec<DrawCommand>, String>,
+    },
+}
+
+/// Interpréteur Ruby hébergé sur un thread dédié.
+///
+/// Artichoke n'est pas `Send` : le parser est créé sur le thread de travail et
+/// n'en sort jamais. Les requêtes sont traitées une à une, dans l'ordre d'envoi,
+/// et les réponses sont relevées par Bevy à la frame suivante : un handler lent
+/// ne bloque plus le rendu ni les entrées.
+pub struct RubyWorker {
+    requests: Option<Sender<RubyRequest>>,
+    responses: Mutex<Receiver<RubyResponse>>,
+    /// Réponses reçues pendant une attente synchrone, à traiter avant les suivantes
+    backlog: VecDeque<RubyResponse>,
+    /// Requêtes envoyées dont la réponse n'a pas encore été relevée, dans l'ordre :
+    /// la première est celle que l'interpréteur traite
+    in_flight: VecDeque<InFlight>,
+    /// Dernière réponse reçue, ou premier envoi après une période d'inactivité
+    last_progress: Instant,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl RubyWorker {
+    /// Démarre le thread et initialise le DSL, en attendant que le prélude soit chargé
+    pub fn spawn(sandbox: SandboxConfig) -> Result<Self, String> {
+        let (request_sender, requests) = channel::<RubyRequest>();
+        let (response_sender, responses) = channel::<RubyResponse>();
+        let (ready_sender, ready) = channel::<Result<(), String>>();
+
+        let thread = thread::Builder::new()
+            .name("ruby-dsl".to_string())
+            .spawn(move || {
+                let parser = DSLParser::new(sandbox).and_then(|mut parser| {
+                    parser.initialize_dsl()?;
+                    Ok(parser)
+                });
+                let mut parser = match parser {
+                    Ok(parser) => {
+                        let _ = ready_sender.send(Ok(()));
+                        parser
+                    },
+                    Err(error) => {
+                        let _ = ready_sender.send(Err(error));
+                        return;
+                    }
+                };
+
+                // Le thread s'arrête quand le RubyWorker est libéré
+                for request in requests {
+                    if let Some(response) = handle_request(&mut parser, request) {
+                        if response_sender.send(response).is_err() {
+                            break;
+                        }
+                    }
+                }
+            })
+            .map_err(|e| format!("Démarrage du thread Ruby impossible: {}", e))?;
+
+        ready
+            .recv()
+            .map_err(|_| "Le thread Ruby s'est arrêté pendant son initialisation".to_string())??;
+
+        Ok(Self {
+            requests: Some(request_sender),
+            responses: Mutex::new(responses),
+            backlog: VecDeque::new(),
+            in_flight: VecDeque::new(),
+            last_progress: Instant::now(),
+            thread: Some(thread),
+        })
+    }
+
+    /// Envoie une requête sans attendre sa réponse
+    pub fn send(&mut self, request: RubyRequest) {
+        let in_flight = request.in_flight();
+        let sent = self
+            .requests
+            .as_ref()
+            .map_or(false, |requests| requests.send(request).is_ok());
+        if let (true, Some(in_flight)) = (sent, in_flight) {
+            if self.in_flight.is_empty() {
+                self.last_progress = Instant::now();
+            }
+            self.in_flight.push_back(in_flight);
+        }
+    }
+
+    /// Évalue un script et attend le résultat (construction initiale de l'UI), au plus `limit`.
+    ///
+    /// Les autres réponses reçues entre-temps sont conservées pour `try_recv`.
+    /// Passé ce délai, une erreur est retournée et l'interpréteur reste `stalled`.
+    pub fn evaluate_now(&mut self, script_path: &str, script: &str, limit: Duration) -> RubyResponse {
+        self.send(RubyRequest::Evaluate {
+            script_path: script_path.to_string(),
+            script: script.to_string(),
+        });
+
+        let deadline = Instant::now() + limit;
+        loop {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            let response = match self.responses.lock() {
+                Ok(responses) => responses.recv_timeout(timeout),
+                Err(_) => Err(RecvTimeoutError::Disconnected),
+            };
+            let response = match response {
+                Ok(response) => response,
+                Err(error) => {
+                    let error = match error {
+                        RecvTimeoutError::Timeout => ScriptError::timeout(format!(
+                            "L'interpréteur n'a pas répondu en {} ms",
+                            limit.as_millis()
+                        )),
+                        RecvTimeoutError::Disconnected => {
+                            self.in_flight.clear();
+                            ScriptError::internal("Le thread Ruby s'est arrêté")
+                        },
+                    };
+                    return RubyResponse::Evaluated {
+                        script_path: script_path.to_string(),
+                        result: Err(error),
+                        dependencies: DependencyGraph::default(),
+                    };
+                }
+            };
+            self.last_progress = Instant::now();
+            // Les réponses mises de côté sont décomptées par try_recv
+            if matches!(response, RubyResponse::Evaluated { .. }) {
+                self.in_flight.pop_back();
+                return response;
+            }
+            self.backlog.push_back(response);
+        }
+    }
+
+    /// Réponses disponibles, dans l'ordre des requêtes
+    pub fn try_recv(&mut self) -> Vec<RubyResponse> {
+        let mut responses: Vec<RubyResponse> = self.backlog.drain(..).collect();
+        if let Ok(receiver) = self.responses.lock() {
+            responses.extend(receiver.try_iter());
+        }
+        if !responses.is_empty() {
+            self.last_progress = Instant::now();
+        }
+        let answered = responses.len().min(self.in_flight.len());
+        self.in_flight.drain(..answered);
+        responses
+    }
+
+    /// Des requêtes sont en cours de traitement
+    pub fn is_busy(&self) -> bool {
+        !self.in_flight.is_empty()
+    }
+
+    /// Requête en cours de traitement par l'interpréteur
+    pub fn current(&self) -> Option<&InFlight> {
+        self.in_flight.front()
+    }
+
+    /// Aucune réponse depuis `limit` alors que des requêtes sont en attente :
+    /// le script est bloqué hors d'atteinte du chien de garde
+    pub fn stalled(&self, limit: Duration) -> bool {
+        self.is_busy() && self.last_progress.elapsed() >= limit
+    }
+
+    /// Abandonne un interpréteur bloqué sans attendre son thread, qui ne
+    /// s'arrêtera qu'avec le processus
+    pub fn abandon(mut self) {
+        self.thread = None;
+    }
+}
+
+impl Drop for RubyWorker {
+    fn drop(&mut self) {
+        // Fermer le canal termine la boucle du thread
+        self.requests = None;
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn handle_request(parser: &mut DSLParser, request: RubyRequest) -> Option<RubyResponse> {
+    match request {
+        RubyRequest::Evaluate { script_path, script } => {
+            let result = parser.evaluate_dsl(&script, &script_path);
+            Some(RubyResponse::Evaluated {
+                script_path,
+                result,
+                dependencies: parser.dependencies().clone(),
+            })
+        },
+        RubyRequest::Callback { callback, arguments } => {
+            let result = parser.execute_callback(&callback, &arguments);
+            // La file des proxies est vidée même si le handler a échoué
+            let queued = parser.take_queued_updates();
+            let result = result.and_then(|returned| {
+                // Les modifications faites pendant le handler précèdent celles qu'il retourne.
+                // Les composants sont convertis ici : le parser ne quitte pas le thread
+                let queued = serde_json::json!({ "version": protocol::PROTOCOL_VERSION, "updates": queued? });
+                let mut response = protocol::parse_response(queued, |component, diagnostics| {
+                    parser.parse_component_value(component, &mut Vec::new(), diagnostics)
+                });
+                response.append(protocol::parse_response(returned, |component, diagnostics| {
+                    parser.parse_component_value(component, &mut Vec::new(), diagnostics)
+                }));
+                Ok(response)
+            });
+            let property_changes = parser.take_property_changes();
+            Some(RubyResponse::Callback { callback, result, property_changes })
+        },
+        RubyRequest::Release { id } => {
+            if let Err(error) = parser.release_component(&id) {
+                bevy::log::warn!("{}", error);
+            }
+            None
+        },
+        RubyRequest::Snapshot { components, removed } => {
+            if let Err(error) = parser.push_snapshot(&components, &removed) {
+                bevy::log::warn!("{}", error);
+            }
+            None
+        },
+        RubyRequest::Draw { id, callback, width, height } => {
+            let result = parser.draw_canvas(&callback, width, height);
+            Some(RubyResponse::Drawn { id, result })
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Worker sans interpréteur : les requêtes restent dans le canal retourné et les
+    /// réponses sont fournies par le test
+    fn detached_worker() -> (RubyWorker, Receiver<RubyRequest>, Sender<RubyResponse>) {
+        let (request_sender, requests) = channel();
+        let (response_sender, responses) = channel();
+        let worker = RubyWorker {
+            requests: Some(request_sender),
+            responses: Mutex::new(responses),
+            backlog: VecDeque::new(),
+            in_flight: VecDeque::new(),
+            last_progress: Instant::now(),
+            thread: None,
+        };
+        (worker, requests, response_sender)
+    }
+
+    fn callback(name: &str) -> RubyRequest {
+        RubyRequest::Callback {
+            callback: name.to_string(),
+            arguments: serde_json::Value::Null,
+        }
+    }
+
+    fn answered(name: &str) -> RubyResponse {
+        RubyResponse::Callback {
+            callback: name.to_string(),
+            result: Ok(CallbackResponse::default()),
+            property_changes: Ok(Vec::new()),
+        }
+    }
+
+    #[test]
+    fn tracks_requests_until_answered() {
+        let (mut worker, _requests, responses) = detached_worker();
+
+        worker.send(RubyRequest::Evaluate {
+            script_path: "scripts/ui.rb".to_string(),
+            script: String::new(),
+        });
+        worker.send(RubyRequest::Release { id: "old".to_string() });
+        worker.send(callback("on_save"));
+        assert_eq!(
+            worker.current(),
+            Some(&InFlight {
+                label: "ui.rb".to_string(),
+                evaluation: true
+            })
+        );
+
+        responses
+            .send(RubyResponse::Evaluated {
+                script_path: "scripts/ui.rb".to_string(),
+                result: Err(ScriptError::internal("échec")),
+                dependencies: DependencyGraph::default(),
+            })
+            .unwrap();
+        assert_eq!(worker.try_recv().len(), 1);
+        assert_eq!(worker.current().map(|current| current.evaluation), Some(false));
+        assert!(worker.is_busy());
+
+        responses.send(answered("on_save")).unwrap();
+        assert_eq!(worker.try_recv().len(), 1);
+        assert!(!worker.is_busy());
+        assert!(!worker.stalled(Duration::ZERO));
+    }
+
+    #[test]
+    fn stalls_only_while_requests_are_pending() {
+        let (mut worker, _requests, responses) = detached_worker();
+        worker.last_progress = Instant::now() - Duration::from_secs(10);
+
+        worker.send(callback("on_tick"));
+        assert!(!worker.stalled(Duration::from_secs(1)));
+
+        worker.last_progress = Instant::now() - Duration::from_secs(10);
+        assert!(worker.stalled(Duration::from_secs(1)));
+
+        responses.send(answered("on_tick")).unwrap();
+        worker.try_recv();
+        assert!(!worker.stalled(Duration::ZERO));
+    }
+
+    #[test]
+    fn keeps_responses_received_while_evaluating() {
+        let (mut worker, _requests, responses) = detached_worker();
+
+        worker.send(callback("on_click"));
+        responses.send(answered("on_click")).unwrap();
+        responses
+            .send(RubyResponse::Evaluated {
+                script_path: "ui.rb".to_string(),
+                result: Err(ScriptError::internal("échec")),
+                dependencies: DependencyGraph::default(),
+            })
+            .unwrap();
+
+        let evaluated = worker.evaluate_now("ui.rb", "", Duration::from_secs(1));
+        assert!(matches!(evaluated, RubyResponse::Evaluated { .. }));
+        assert!(worker.is_busy());
+
+        let pending = worker.try_recv();
+        assert!(matches!(pending.as_slice(), [RubyResponse::Callback { .. }]));
+        assert!(!worker.is_busy());
+    }
+
+    #[test]
+    fn times_out_without_an_answer() {
+        let (mut worker, _requests, _responses) = detached_worker();
+
+        let evaluated = worker.evaluate_now("ui.rb", "", Duration::from_millis(10));
+
+        let RubyResponse::Evaluated { result: Err(error), .. } = evaluated else {
+            panic!("délai dépassé attendu");
+        };
+        assert!(error.to_string().contains("10 ms"), "{}", error);
+        assert_eq!(worker.current().map(|current| current.label.as_str()), Some("ui.rb"));
+    }
+}
@@ -4,40 +4,31 @@ use bevy::{
     ui::{FocusPolicy, Style, UiRect, Val, JustifyContent, AlignItems, PositionType, FlexDirection},
     window::PrimaryWindow,
 };
-use std::collections::HashMap;
 
 use crate::ui::components::*;
+use crate::ui::registry::UIRegistry;
+use crate::ui::color::ColorValue;
+use crate::ui::style::{InlineStyle, ResolvedStyle, StyleProperty};
+use crate::ui::theme::Themed;
 use crate::dsl::parser::*;
 
-/// Gestionnaire de construction d'UI à partir des composants DSL
-pub struct UIBuilder {
-    // Registre des entités créées, mappées par ID de composant
-    entity_registry: HashMap<String, Entity>,
-    // Fonction de callback pour exécuter du code Ruby depuis l'UI
-    ruby_callback: Option<Box<dyn Fn(String, HashMap<String, String>) -> Result<String, String> + Send + Sync>>,
+/// Gestionnaire de construction d'UI à partir des composants DSL.
+///
+/// Les entités créées sont enregistrées dans le `UIRegistry` emprunté.
+pub struct UIBuilder<'a> {
+    pub(super) registry: &'a mut UIRegistry,
 }
 
-impl UIBuilder {
-    /// Crée un nouveau constructeur d'UI
-    pub fn new() -> Self {
-        Self {
-            entity_registry: HashMap::new(),
-            ruby_callback: None,
-        }
-    }
-
-    /// Définit la fonction de callback pour exécuter du code Ruby
-    pub fn set_ruby_callback<F>(&mut self, callback: F)
-    where
-        F: Fn(String, HashMap<String, String>) -> Result<String, String> + Send + Sync + 'static,
-    {
-        self.ruby_callback = Some(Box::new(callback));
+impl<'a> UIBuilder<'a> {
+    /// Crée un constructeur d'UI qui enregistre ses entités dans `registry`
+    pub fn new(registry: &'a mut UIRegistry) -> Self {
+        Self { registry }
     }
 
     /// Construit l'UI à partir des composants
     pub fn build_ui(&mut self, commands: &mut Commands, components: &[UIComponent], asset_server: &Res<AssetServer>) {
         // Nettoyer le registre pour la reconstruction
-        self.entity_registry.clear();
+        self.registry.clear();
         
         // Construire chaque composant racine
         for component in components {
@@ -47,7 +38,7 @@ impl UIBuilder {
 
     /// Construit un composant UI et ses enfants
     pub fn build_component(&mut self, commands: &mut Commands, component: &UIComponent, parent: Option<Entity>, asset_server: &Res<AssetServer>) -> Option<Entity> {
-        match component {
+        let entity = match component {
             UIComponent::Window(props) => self.build_window(commands, props, asset_server),
             UIComponent::Button(props) => self.build_button(commands, props, parent, asset_server),
             UIComponent::Text(props) => self.build_text(commands, props, parent, asset_server),
@@ -62,7 +53,43 @@ impl UIBuilder {
             UIComponent::Stack(props) => self.build_stack(commands, props, parent, asset_server),
             UIComponent::Row(props) => self.build_row(commands, props, parent, asset_server),
             UIComponent::Column(props) => self.build_column(commands, props, parent, asset_server),
+            UIComponent::Node3D(props) => self.build_node3d(commands, props, parent, asset_server),
+        }?;
+
+        // Les nœuds 3D n'ont ni style ni état d'interaction
+        if let UIComponent::Node3D(_) = component {
+            return Some(entity);
         }
+
+        // Le style est calculé par `restyle_system` à partir du thème, des feuilles de style, des
+        // classes, de l'état d'interaction et du style propre du composant
+        let mut inline = component.style().map(|style| ResolvedStyle::resolve(style).0).unwrap_or_default();
+        if let UIComponent::Text(props) = component {
+            // Les propriétés explicites du texte l'emportent sur l'apparence du thème
+            let mut explicit = Vec::new();
+            if let Some(Ok(color)) = props.color.as_deref().map(ColorValue::parse) {
+                explicit.push(StyleProperty::TextColor(color));
+            }
+            if let Some(size) = props.size {
+                explicit.push(StyleProperty::FontSize(size));
+            }
+            if let Some(font) = &props.font {
+                explicit.push(StyleProperty::Font(font.clone()));
+            }
+            inline.properties.splice(0..0, explicit);
+        }
+        // `Interaction` suit le survol et l'appui de tout nœud, pas seulement des boutons
+        let mut element = commands.entity(entity);
+        element.insert((InlineStyle(inline), StyleClasses(component.classes().to_vec()), Interaction::None));
+        if component.disabled() {
+            element.insert(Disabled);
+        }
+        Some(entity)
+    }
+
+    /// Retourne l'entité enregistrée pour un ID de composant
+    pub fn entity(&self, id: &str) -> Option<Entity> {
+        self.registry.get(id)
     }
 
     /// Construit une fenêtre
@@ -81,7 +108,6 @@ impl UIBuilder {
                         border: UiRect::all(Val::Px(1.0)),
                         ..default()
                     },
-                    background_color: Color::rgb(0.15, 0.15, 0.15).into(),
                     ..default()
                 },
                 UIElement {
@@ -107,31 +133,30 @@ impl UIBuilder {
             });
         }
 
-        // Créer l'en-tête de la fenêtre avec le titre
+        // Créer l'en-tête de la fenêtre avec le titre ; les couleurs, tailles et polices
+        // des pièces de la fenêtre viennent du thème (voir `Themed`)
         let header_entity = commands
-            .spawn(NodeBundle {
-                style: Style {
-                    width: Val::Percent(100.0),
-                    height: Val::Px(30.0),
-                    justify_content: JustifyContent::SpaceBetween,
-                    align_items: AlignItems::Center,
-                    padding: UiRect::all(Val::Px(5.0)),
+            .spawn((
+                NodeBundle {
+                    style: Style {
+                        width: Val::Percent(100.0),
+                        height: Val::Px(30.0),
+                        justify_content: JustifyContent::SpaceBetween,
+                        align_items: AlignItems::Center,
+                        padding: UiRect::all(Val::Px(5.0)),
+                        ..default()
+                    },
                     ..default()
                 },
-                background_color: Color::rgb(0.2, 0.2, 0.2).into(),
-                ..default()
-            })
+                Themed::background("header"),
+            ))
             .id();
 
         // Titre de la fenêtre
         let title_entity = commands
-            .spawn(TextBundle::from_section(
-                props.title.clone(),
-                TextStyle {
-                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
-                    font_size: 16.0,
-                    color: Color::WHITE,
-                },
+            .spawn((
+                TextBundle::from_section(props.title.clone(), TextStyle::default()),
+                Themed::text("text", "title", "bold"),
             ))
             .id();
 
@@ -146,25 +171,21 @@ impl UIBuilder {
                         align_items: AlignItems::Center,
                         ..default()
                     },
-                    background_color: Color::rgb(0.8, 0.2, 0.2).into(),
                     ..default()
                 },
                 EventCallback {
                     event_type: "click".to_string(),
                     callback: "close_window".to_string(),
                 },
+                Themed::background("danger"),
             ))
             .id();
 
         // Texte du bouton de fermeture (X)
         commands.entity(close_button_entity).with_children(|parent| {
-            parent.spawn(TextBundle::from_section(
-                "X",
-                TextStyle {
-                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
-                    font_size: 14.0,
-                    color: Color::WHITE,
-                },
+            parent.spawn((
+                TextBundle::from_section("X", TextStyle::default()),
+                Themed::text("on-danger", "small", "bold"),
             ));
         });
 
@@ -173,18 +194,20 @@ impl UIBuilder {
 
         // Contenu de la fenêtre
         let content_entity = commands
-            .spawn(NodeBundle {
-                style: Style {
-                    width: Val::Percent(100.0),
-                    height: Val::Percent(100.0),
-                    flex_direction: FlexDirection::Column,
-                    padding: UiRect::all(Val::Px(10.0)),
-                    overflow: bevy::ui::Overflow::clip(),
+            .spawn((
+                NodeBundle {
+                    style: Style {
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(100.0),
+                        flex_direction: FlexDirection::Column,
+                        padding: UiRect::all(Val::Px(10.0)),
+                        overflow: bevy::ui::Overflow::clip(),
+                        ..default()
+                    },
                     ..default()
                 },
-                background_color: Color::rgb(0.1, 0.1, 0.1).into(),
-                ..default()
-            })
+                Themed::background("surface"),
+            ))
             .id();
 
         // Construire les enfants du contenu
@@ -197,8 +220,9 @@ impl UIBuilder {
         // Ajouter l'en-tête et le contenu à la fenêtre
         commands.entity(window_entity).push_children(&[header_entity, content_entity]);
 
-        // Enregistrer l'entité créée
-        self.entity_registry.insert(props.id.clone(), window_entity);
+        // Enregistrer l'entité créée et son conteneur d'enfants
+        self.registry.entities.insert(props.id.clone(), window_entity);
+        self.registry.containers.insert(props.id.clone(), content_entity);
 
         Some(window_entity)
     }
@@ -1,13 +1,17 @@
 // src/ui/builder/basic_components.rs
 use bevy::{
     prelude::*,
-    ui::{FocusPolicy, Style, UiRect, Val, JustifyContent, AlignItems, PositionType, FlexDirection},
+    ui::{FocusPolicy, RelativeCursorPosition, Style, UiRect, Val, JustifyContent, AlignItems, PositionType, FlexDirection},
 };
 use std::collections::HashMap;
 
+use crate::ui::color::ColorValue;
+use crate::ui::canvas::CanvasSurface;
 use crate::ui::components::*;
+use crate::ui::pointer::PointerHandlers;
+use crate::ui::svg::{placeholder_image, Svg, SvgImage};
 
-impl UIBuilder {
+impl UIBuilder<'_> {
     /// Construit un bouton
     pub fn build_button(&mut self, commands: &mut Commands, props: &ButtonProps, parent: Option<Entity>, asset_server: &Res<AssetServer>) -> Option<Entity> {
         // Calculer le style du bouton
@@ -37,7 +41,6 @@ impl UIBuilder {
             .spawn((
                 ButtonBundle {
                     style,
-                    background_color: Color::rgb(0.25, 0.5, 0.85).into(),
                     ..default()
                 },
                 UIElement {
@@ -75,16 +78,9 @@ impl UIBuilder {
             button_content.push(icon_entity);
         }
 
-        // Ajouter le texte
+        // Ajouter le texte ; sa couleur, sa taille et sa police viennent du thème
         let text_entity = commands
-            .spawn(TextBundle::from_section(
-                props.text.clone(),
-                TextStyle {
-                    font: asset_server.load("fonts/FiraSans-Regular.ttf"),
-                    font_size: 16.0,
-                    color: Color::WHITE,
-                },
-            ))
+            .spawn(TextBundle::from_section(props.text.clone(), TextStyle::default()))
             .id();
         button_content.push(text_entity);
 
@@ -97,7 +93,7 @@ impl UIBuilder {
         }
 
         // Enregistrer l'entité créée
-        self.entity_registry.insert(props.id.clone(), button_entity);
+        self.registry.entities.insert(props.id.clone(), button_entity);
 
         Some(button_entity)
     }
@@ -106,38 +102,11 @@ impl UIBuilder {
     pub fn build_text(&mut self, commands: &mut Commands, props: &TextProps, parent: Option<Entity>, asset_server: &Res<AssetServer>) -> Option<Entity> {
         // Configurer le style de texte
         let font_size = props.size.unwrap_or(16.0);
-        let color = match &props.color {
-            Some(color_str) => {
-                // Parser la couleur (exemple simple, à améliorer)
-                if color_str.starts_with("rgb(") {
-                    // Format rgb(r, g, b)
-                    let rgb_values: Vec<&str> = color_str
-                        .trim_start_matches("rgb(")
-                        .trim_end_matches(")")
-                        .split(',')
-                        .collect();
-                    if rgb_values.len() >= 3 {
-                        let r = rgb_values[0].trim().parse::<f32>().unwrap_or(255.0) / 255.0;
-                        let g = rgb_values[1].trim().parse::<f32>().unwrap_or(255.0) / 255.0;
-                        let b = rgb_values[2].trim().parse::<f32>().unwrap_or(255.0) / 255.0;
-                        Color::rgb(r, g, b)
-                    } else {
-                        Color::WHITE
-                    }
-                } else {
-                    // Couleurs nommées simples
-                    match color_str.to_lowercase().as_str() {
-                        "red" => Color::RED,
-                        "green" => Color::GREEN,
-                        "blue" => Color::BLUE,
-                        "yellow" => Color::YELLOW,
-                        "white" => Color::WHITE,
-                        "black" => Color::BLACK,
-                        _ => Color::WHITE,
-                    }
-                }
-            }
-            None => Color::WHITE,
+        // Sans couleur explicite, celle du thème est appliquée avec le style du composant
+        // (voir `build_component`), comme les jetons
+        let color = match props.color.as_deref().map(ColorValue::parse) {
+            Some(Ok(ColorValue::Literal(color))) => color,
+            _ => Color::default(),
         };
 
         // Définir l'alignement du texte
@@ -185,7 +154,7 @@ impl UIBuilder {
         }
 
         // Enregistrer l'entité créée
-        self.entity_registry.insert(props.id.clone(), text_entity);
+        self.registry.entities.insert(props.id.clone(), text_entity);
 
         Some(text_entity)
     }
@@ -235,14 +204,15 @@ impl UIBuilder {
         }
 
         // Enregistrer l'entité créée
-        self.entity_registry.insert(props.id.clone(), image_entity);
+        self.registry.entities.insert(props.id.clone(), image_entity);
 
         Some(image_entity)
     }
 
     /// Construit un canevas
     pub fn build_canvas(&mut self, commands: &mut Commands, props: &CanvasProps, parent: Option<Entity>, asset_server: &Res<AssetServer>) -> Option<Entity> {
-        // Créer un nœud pour le canevas
+        // Le dessin est rastérisé dans une image qui couvre le nœud, par-dessus son fond
+        let texture = asset_server.add(placeholder_image());
         let canvas_entity = commands
             .spawn((
                 NodeBundle {
@@ -252,30 +222,35 @@ impl UIBuilder {
                         margin: UiRect::all(Val::Px(4.0)),
                         ..default()
                     },
-                    background_color: Color::rgb(0.9, 0.9, 0.9).into(),
+                    // Le canevas masque au pointeur ce qu'il recouvre
+                    focus_policy: FocusPolicy::Block,
                     ..default()
                 },
                 UIElement {
                     id: props.id.clone(),
                     component_type: "canvas".to_string(),
                 },
+                CanvasSurface::new(props.on_draw.clone(), texture.clone()),
+                // Survol et position du pointeur calculés par bevy_ui (ordre d'affichage, découpe)
+                Interaction::default(),
+                RelativeCursorPosition::default(),
             ))
+            .with_children(|parent| {
+                parent.spawn(ImageBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(100.0),
+                        ..default()
+                    },
+                    image: UiImage::new(texture),
+                    ..default()
+                });
+            })
             .id();
 
-        // Ajouter les callbacks si spécifiés
-        if let Some(on_draw) = &props.on_draw {
-            commands.entity(canvas_entity).insert(EventCallback {
-                event_type: "draw".to_string(),
-                callback: on_draw.clone(),
-            });
-        }
-
-        if let Some(on_click) = &props.on_click {
-            commands.entity(canvas_entity).insert(EventCallback {
-                event_type: "click".to_string(),
-                callback: on_click.clone(),
-            });
-        }
+        // Les événements de pointeur sont transmis par `canvas_pointer_system`
+        commands.entity(canvas_entity).insert(PointerHandlers::new(props));
 
         // Si un parent est spécifié, ajouter le canevas comme enfant
         if let Some(parent_entity) = parent {
@@ -283,18 +258,26 @@ impl UIBuilder {
         }
 
         // Enregistrer l'entité créée
-        self.entity_registry.insert(props.id.clone(), canvas_entity);
+        self.registry.entities.insert(props.id.clone(), canvas_entity);
 
         Some(canvas_entity)
     }
 
     /// Construit un SVG
     pub fn build_svg(&mut self, commands: &mut Commands, props: &SVGProps, parent: Option<Entity>, asset_server: &Res<AssetServer>) -> Option<Entity> {
-        // Pour simplifier, on charge simplement une image SVG via Bevy
-        // Dans une implémentation complète, on utiliserait resvg pour rendre le SVG
-
-        // Charger le SVG comme une image
-        let svg_handle = asset_server.load(&props.source);
+        // Le document est rastérisé par `rasterize_svg_system` dans une image propre au nœud,
+        // transparente jusqu'au premier rendu
+        let document = match (&props.markup, &props.source) {
+            (Some(markup), _) => match Svg::from_data(markup.clone().into_bytes()) {
+                Ok(svg) => asset_server.add(svg),
+                Err(error) => {
+                    warn!("SVG `{}`: {}", props.id, error);
+                    Handle::default()
+                }
+            },
+            (None, Some(source)) => asset_server.load(source),
+            (None, None) => Handle::default(),
+        };
 
         // Configurer le style du SVG
         let mut style = Style {
@@ -320,13 +303,18 @@ impl UIBuilder {
             .spawn((
                 ImageBundle {
                     style,
-                    image: UiImage::new(svg_handle),
+                    image: UiImage::new(asset_server.add(placeholder_image())),
                     ..default()
                 },
                 UIElement {
                     id: props.id.clone(),
                     component_type: "svg".to_string(),
                 },
+                SvgImage {
+                    handle: document,
+                    fill: props.fill.as_deref().and_then(|color| ColorValue::parse(color).ok()),
+                    stroke: props.stroke.as_deref().and_then(|color| ColorValue::parse(color).ok()),
+                },
             ))
             .id();
 
@@ -336,7 +324,7 @@ impl UIBuilder {
         }
 
         // Enregistrer l'entité créée
-        self.entity_registry.insert(props.id.clone(), svg_entity);
+        self.registry.entities.insert(props.id.clone(), svg_entity);
 
         Some(svg_entity)
     }
@@ -1,14 +1,15 @@
 // src/ui/builder/interactive_components.rs
 use bevy::{
     prelude::*,
-    render::mesh::shape,
     ui::{Style, UiRect, Val, JustifyContent, AlignItems, PositionType},
 };
 use std::collections::HashMap;
 
 use crate::ui::components::*;
+use crate::ui::theme::Themed;
+use crate::ui::viewport3d::{assign_layer, render_target_image, Viewport3D, ViewportContent};
 
-impl UIBuilder {
+impl UIBuilder<'_> {
     /// Construit un champ de saisie
     pub fn build_input(&mut self, commands: &mut Commands, props: &InputProps, parent: Option<Entity>, asset_server: &Res<AssetServer>) -> Option<Entity> {
         // Largeur du champ
@@ -27,7 +28,6 @@ impl UIBuilder {
                         align_items: AlignItems::Center,
                         ..default()
                     },
-                    background_color: Color::rgb(0.2, 0.2, 0.2).into(),
                     ..default()
                 },
                 UIElement {
@@ -43,19 +43,15 @@ impl UIBuilder {
         });
 
         let text_color = if props.value.is_some() {
-            Color::WHITE
+            "text"
         } else {
-            Color::rgb(0.5, 0.5, 0.5) // Gris pour le placeholder
+            "text-muted" // Gris pour le placeholder
         };
 
         let text_entity = commands
-            .spawn(TextBundle::from_section(
-                text_value,
-                TextStyle {
-                    font: asset_server.load("fonts/FiraSans-Regular.ttf"),
-                    font_size: 16.0,
-                    color: text_color,
-                },
+            .spawn((
+                TextBundle::from_section(text_value, TextStyle::default()),
+                Themed::text(text_color, "body", "regular"),
             ))
             .id();
 
@@ -83,16 +79,33 @@ impl UIBuilder {
         }
 
         // Enregistrer l'entité créée
-        self.entity_registry.insert(props.id.clone(), input_entity);
+        self.registry.entities.insert(props.id.clone(), input_entity);
 
         Some(input_entity)
     }
 
     /// Construit un viewport 3D
     pub fn build_viewport3d(&mut self, commands: &mut Commands, props: &Viewport3DProps, parent: Option<Entity>, asset_server: &Res<AssetServer>) -> Option<Entity> {
-        // Créer un nœud pour le viewport 3D (conteneur)
-        let viewport_entity = commands
+        // La caméra du viewport rend dans une image qui couvre le nœud, par-dessus son fond ;
+        // elle est créée avec la scène par `spawn_viewport_system`, la scène glTF est chargée ici
+        let texture = asset_server.add(render_target_image(UVec2::ONE));
+        let viewport_entity = commands.spawn_empty().id();
+
+        // Le contenu 3D vit hors de l'arbre d'UI, sous une racine détruite avec le nœud
+        let nodes_entity = commands.spawn((SpatialBundle::default(), Name::new("nodes"))).id();
+        let root_entity = commands
             .spawn((
+                SpatialBundle::default(),
+                ViewportContent::new(viewport_entity, nodes_entity),
+                Name::new(format!("viewport3d {}", props.id)),
+            ))
+            .add_child(nodes_entity)
+            .id();
+        commands.add(move |world: &mut World| assign_layer(world, root_entity));
+
+        commands
+            .entity(viewport_entity)
+            .insert((
                 NodeBundle {
                     style: Style {
                         width: Val::Px(props.width),
@@ -100,182 +113,43 @@ impl UIBuilder {
                         margin: UiRect::all(Val::Px(4.0)),
                         ..default()
                     },
-                    background_color: Color::rgb(0.1, 0.1, 0.1).into(),
                     ..default()
                 },
                 UIElement {
                     id: props.id.clone(),
                     component_type: "viewport3d".to_string(),
                 },
+                Viewport3D::new(props, texture.clone(), root_entity, props.scene.as_ref().map(|scene| asset_server.load(scene))),
             ))
-            .id();
-
-        // Créer une caméra pour la vue 3D
-        let camera_position = if let Some(camera) = &props.camera {
-            Vec3::new(
-                camera.position[0],
-                camera.position[1],
-                camera.position[2],
-            )
-        } else {
-            Vec3::new(0.0, 5.0, 10.0) // Position par défaut
-        };
-
-        let camera_target = if let Some(camera) = &props.camera {
-            Vec3::new(
-                camera.target[0],
-                camera.target[1],
-                camera.target[2],
-            )
-        } else {
-            Vec3::ZERO // Cible par défaut
-        };
-
-        // Créer la caméra 3D
-        // Note: Dans une implémentation complète, il faudrait gérer un RenderTarget spécifique
-        // pour cette vue, plutôt que d'utiliser la caméra principale
-        let camera_entity = commands
-            .spawn(Camera3dBundle {
-                transform: Transform::from_translation(camera_position)
-                    .looking_at(camera_target, Vec3::Y),
-                ..default()
-            })
-            .id();
-
-        // Créer une lumière pour la scène 3D
-        let light_entity = commands
-            .spawn(PointLightBundle {
-                point_light: PointLight {
-                    intensity: 1500.0,
-                    shadows_enabled: true,
-                    ..default()
-                },
-                transform: Transform::from_xyz(4.0, 8.0, 4.0),
-                ..default()
-            })
-            .id();
-
-        // Créer un objet 3D simple pour la démonstration (sphère)
-        let sphere_entity = commands
-            .spawn(PbrBundle {
-                mesh: commands.world.resource::<Assets<Mesh>>().add(shape::UVSphere {
-                    radius: 1.0,
-                    sectors: 32,
-                    stacks: 16,
-                }.into()),
-                material: commands.world.resource::<Assets<StandardMaterial>>().add(StandardMaterial {
-                    base_color: Color::rgb(0.8, 0.2, 0.3),
+            .with_children(|parent| {
+                parent.spawn(ImageBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(100.0),
+                        ..default()
+                    },
+                    image: UiImage::new(texture),
                     ..default()
-                }),
-                transform: Transform::from_xyz(0.0, 0.0, 0.0),
-                ..default()
-            })
-            .id();
+                });
+            });
 
-        // Ajouter les entités 3D au viewport
-        commands.entity(viewport_entity).push_children(&[camera_entity, light_entity, sphere_entity]);
+        // Construire les nœuds 3D déclarés dans le bloc du viewport
+        for child in &props.children {
+            if let Some(child_entity) = self.build_component(commands, child, Some(nodes_entity), asset_server) {
+                commands.entity(nodes_entity).add_child(child_entity);
+            }
+        }
 
         // Si un parent est spécifié, ajouter le viewport comme enfant
         if let Some(parent_entity) = parent {
             commands.entity(parent_entity).add_child(viewport_entity);
         }
 
-        // Enregistrer l'entité créée
-        self.entity_registry.insert(props.id.clone(), viewport_entity);
+        // Enregistrer l'entité créée et son conteneur d'enfants
+        self.registry.entities.insert(props.id.clone(), viewport_entity);
+        self.registry.containers.insert(props.id.clone(), nodes_entity);
 
         Some(viewport_entity)
     }
-
-    /// Méthode pour mettre à jour un composant existant
-    pub fn update_component(&mut self, commands: &mut Commands, component_id: &str, update: &UIComponentUpdate) -> Result<(), String> {
-        if let Some(entity) = self.entity_registry.get(component_id) {
-            match update {
-                UIComponentUpdate::SetText(text) => {
-                    // Trouver le composant Text enfant et mettre à jour son contenu
-                    if let Ok(mut text_query) = commands.world.query_filtered::<&mut Text, With<Parent>>(entity) {
-                        for mut text_component in text_query.iter_mut(commands.world) {
-                            if let Some(section) = text_component.sections.first_mut() {
-                                section.value = text.clone();
-                            }
-                        }
-                    }
-                },
-                UIComponentUpdate::SetImage(source) => {
-                    // Mettre à jour la source de l'image
-                    // Cette implémentation est simplifiée, il faudrait gérer le chargement des assets
-                },
-                UIComponentUpdate::SetPosition(x, y) => {
-                    // Mettre à jour la position d'un composant
-                    if let Ok(mut style) = commands.world.query::<&mut Style>().get_mut(commands.world, *entity) {
-                        style.left = Val::Px(*x);
-                        style.top = Val::Px(*y);
-                    }
-                },
-                UIComponentUpdate::SetSize(width, height) => {
-                    // Mettre à jour la taille d'un composant
-                    if let Ok(mut style) = commands.world.query::<&mut Style>().get_mut(commands.world, *entity) {
-                        style.width = Val::Px(*width);
-                        style.height = Val::Px(*height);
-                    }
-                },
-                UIComponentUpdate::SetVisible(visible) => {
-                    // Mettre à jour la visibilité d'un composant
-                    if *visible {
-                        commands.entity(*entity).insert(Visibility::Visible);
-                    } else {
-                        commands.entity(*entity).insert(Visibility::Hidden);
-                    }
-                },
-                UIComponentUpdate::SetStyle(style_map) => {
-                    // Mettre à jour le style d'un composant
-                    if let Ok(mut style) = commands.world.query::<&mut Style>().get_mut(commands.world, *entity) {
-                        for (key, value) in style_map {
-                            match key.as_str() {
-                                "backgroundColor" => {
-                                    if let Ok(mut background) = commands.world.query::<&mut BackgroundColor>().get_mut(commands.world, *entity) {
-                                        // Parser la couleur (simplifié)
-                                        if value.starts_with("rgb(") {
-                                            // Format rgb(r, g, b)
-                                            let rgb_values: Vec<&str> = value
-                                                .trim_start_matches("rgb(")
-                                                .trim_end_matches(")")
-                                                .split(',')
-                                                .collect();
-                                            if rgb_values.len() >= 3 {
-                                                let r = rgb_values[0].trim().parse::<f32>().unwrap_or(255.0) / 255.0;
-                                                let g = rgb_values[1].trim().parse::<f32>().unwrap_or(255.0) / 255.0;
-                                                let b = rgb_values[2].trim().parse::<f32>().unwrap_or(255.0) / 255.0;
-                                                *background = Color::rgb(r, g, b).into();
-                                            }
-                                        }
-                                    }
-                                },
-                                // Autres propriétés de style à gérer...
-                                _ => {
-                                    // Propriété non reconnue
-                                }
-                            }
-                        }
-                    }
-                },
-                UIComponentUpdate::Replace(new_component) => {
-                    // Remplacement complet d'un composant
-                    // Ce cas est plus complexe et nécessiterait de despawn l'entité existante
-                    // et de créer une nouvelle entité avec le nouveau composant
-                },
-            }
-            Ok(())
-        } else {
-            Err(format!("Composant avec ID '{}' non trouvé", component_id))
-        }
-    }
-
-    /// Exécute un callback Ruby
-    pub fn execute_ruby_callback(&self, callback_name: &str, args: HashMap<String, String>) -> Result<String, String> {
-        if let Some(callback_fn) = &self.ruby_callback {
-            callback_fn(callback_name.to_string(), args)
-        } else {
-            Err("Fonction de callback Ruby non définie".to_string())
-        }
-    }
-}
\ No newline at end of file
+}
@@ -7,7 +7,7 @@ use std::collections::HashMap;
 
 use crate::ui::components::*;
 
-impl UIBuilder {
+impl UIBuilder<'_> {
     /// Construit une vue défilante
     pub fn build_scrollview(&mut self, commands: &mut Commands, props: &ScrollViewProps, parent: Option<Entity>, asset_server: &Res<AssetServer>) -> Option<Entity> {
         // Créer un nœud pour la vue défilante
@@ -22,7 +22,6 @@ impl UIBuilder {
                         margin: UiRect::all(Val::Px(4.0)),
                         ..default()
                     },
-                    background_color: Color::rgb(0.15, 0.15, 0.15).into(),
                     ..default()
                 },
                 UIElement {
@@ -45,7 +44,7 @@ impl UIBuilder {
         }
 
         // Enregistrer l'entité créée
-        self.entity_registry.insert(props.id.clone(), scrollview_entity);
+        self.registry.entities.insert(props.id.clone(), scrollview_entity);
 
         Some(scrollview_entity)
     }
@@ -93,7 +92,7 @@ impl UIBuilder {
         }
 
         // Enregistrer l'entité créée
-        self.entity_registry.insert(props.id.clone(), list_entity);
+        self.registry.entities.insert(props.id.clone(), list_entity);
 
         Some(list_entity)
     }
@@ -125,43 +124,12 @@ impl UIBuilder {
             ))
             .id();
 
-        // Créer les rangées de la grille
-        let mut items = props.items.iter();
-        let mut row_index = 0;
-
-        // Continuer tant qu'il reste des éléments à placer
-        while let Some(_) = items.next().map(|_| {}).or_else(|| None) {
-            // Reculer d'un élément
-            items = props.items.iter().skip(row_index * columns);
-            
-            // Créer une rangée
-            let row_entity = commands
-                .spawn(NodeBundle {
-                    style: Style {
-                        flex_direction: FlexDirection::Row,
-                        gap: Size::all(Val::Px(spacing)),
-                        ..default()
-                    },
-                    ..default()
-                })
-                .id();
-
-            // Ajouter jusqu'à 'columns' éléments dans cette rangée
-            for (col_index, item) in items.clone().take(columns).enumerate() {
-                let item_index = row_index * columns + col_index;
-                if item_index < props.items.len() {
-                    if let Some(item_entity) = self.build_component(commands, item, Some(row_entity), asset_server) {
-                        commands.entity(row_entity).add_child(item_entity);
-                    }
-                }
-            }
-
-            // Ajouter la rangée à la grille
-            commands.entity(grid_entity).add_child(row_entity);
-            
-            // Passer à la rangée suivante
-            row_index += 1;
-        }
+        // Construire les éléments puis les répartir en rangées
+        let item_entities: Vec<Entity> = props.items
+            .iter()
+            .filter_map(|item| self.build_component(commands, item, None, asset_server))
+            .collect();
+        self.layout_grid_rows(commands, &props.id, grid_entity, columns, spacing, &item_entities);
 
         // Si un parent est spécifié, ajouter la grille comme enfant
         if let Some(parent_entity) = parent {
@@ -169,7 +137,7 @@ impl UIBuilder {
         }
 
         // Enregistrer l'entité créée
-        self.entity_registry.insert(props.id.clone(), grid_entity);
+        self.registry.entities.insert(props.id.clone(), grid_entity);
 
         Some(grid_entity)
     }
@@ -208,7 +176,7 @@ impl UIBuilder {
         }
 
         // Enregistrer l'entité créée
-        self.entity_registry.insert(props.id.clone(), stack_entity);
+        self.registry.entities.insert(props.id.clone(), stack_entity);
 
         Some(stack_entity)
     }
@@ -262,7 +230,7 @@ impl UIBuilder {
         }
 
         // Enregistrer l'entité créée
-        self.entity_registry.insert(props.id.clone(), row_entity);
+        self.registry.entities.insert(props.id.clone(), row_entity);
 
         Some(row_entity)
     }
@@ -316,8 +284,42 @@ impl UIBuilder {
         }
 
         // Enregistrer l'entité créée
-        self.entity_registry.insert(props.id.clone(), column_entity);
+        self.registry.entities.insert(props.id.clone(), column_entity);
 
         Some(column_entity)
     }
-}
\ No newline at end of file
+
+    /// Répartit les éléments d'une grille dans des rangées de `columns` éléments.
+    /// Les rangées précédentes éventuelles sont supprimées, les éléments sont conservés.
+    pub(super) fn layout_grid_rows(&mut self, commands: &mut Commands, grid_id: &str, grid_entity: Entity, columns: usize, spacing: f32, items: &[Entity]) {
+        let mut rows = Vec::new();
+
+        for chunk in items.chunks(columns.max(1)) {
+            // Créer une rangée
+            let row_entity = commands
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Row,
+                        gap: Size::all(Val::Px(spacing)),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .id();
+
+            // Déplacer les éléments dans la rangée
+            commands.entity(row_entity).push_children(chunk);
+            rows.push(row_entity);
+        }
+
+        // Ajouter les nouvelles rangées à la grille
+        commands.entity(grid_entity).replace_children(&rows);
+
+        // Les éléments ont été déplacés, les anciennes rangées sont vides
+        if let Some(previous_rows) = self.registry.grid_rows.insert(grid_id.to_string(), rows) {
+            for row in previous_rows {
+                commands.entity(row).despawn_recursive();
+            }
+        }
+    }
+}
@@ -2,6 +2,8 @@ pub mod base;
 pub mod basic_components;
 pub mod layout_components;
 pub mod interactive_components;
+pub mod scene_components;
+pub mod reconcile;
 
 // Ré-exporter UIBuilder pour faciliter l'import
 pub use base::UIBuilder;
\ No newline at end of file
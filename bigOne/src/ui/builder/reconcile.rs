@@ -0,0 +1,398 @@
+// src/ui/builder/reconcile.rs
+use bevy::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+use crate::ui::components::*;
+use crate::ui::registry::despawn_subtree;
+use super::base::UIBuilder;
+
+impl UIBuilder<'_> {
+    /// Réconcilie l'UI vivante avec un nouvel arbre de composants.
+    ///
+    /// Les composants sont appariés par ID : les entités inchangées sont conservées
+    /// (position des fenêtres, saisie, défilement...), les propriétés modifiées sont
+    /// renvoyées sous forme de mises à jour, et seuls les composants ajoutés, supprimés
+    /// ou impossibles à patcher sont (re)construits.
+    pub fn reconcile_ui(&mut self, commands: &mut Commands, old: &[UIComponent], new: &[UIComponent], asset_server: &Res<AssetServer>) -> Vec<UIUpdateEvent> {
+        // Index de l'ancien arbre par ID, pour retrouver aussi les composants déplacés
+        let mut old_index = HashMap::new();
+        for component in old {
+            index_components(component, &mut old_index);
+        }
+
+        // IDs présents dans le nouvel arbre
+        let mut new_ids = HashSet::new();
+        for component in new {
+            collect_ids(component, &mut new_ids);
+        }
+
+        // Supprimer les composants qui n'existent plus
+        for component in old {
+            self.prune_component(commands, component, &new_ids);
+        }
+
+        // Construire ou patcher le nouvel arbre
+        let mut updates = Vec::new();
+        for component in new {
+            self.reconcile_component(commands, component, &old_index, &mut updates, asset_server);
+        }
+
+        updates
+    }
+
+    /// Supprime récursivement les composants absents du nouvel arbre
+    fn prune_component(&mut self, commands: &mut Commands, component: &UIComponent, keep: &HashSet<String>) {
+        if keep.contains(component.id()) {
+            for child in component.children() {
+                self.prune_component(commands, child, keep);
+            }
+        } else {
+            self.discard_component(commands, component, keep);
+        }
+    }
+
+    /// Détruit l'entité d'un composant en détachant d'abord les descendants encore utilisés
+    fn discard_component(&mut self, commands: &mut Commands, component: &UIComponent, keep: &HashSet<String>) {
+        for child in component.children() {
+            if keep.contains(child.id()) {
+                if let Some(&child_entity) = self.registry.entities.get(child.id()) {
+                    commands.entity(child_entity).remove_parent();
+                }
+            } else {
+                self.forget_component(commands, child, keep);
+            }
+        }
+
+        self.registry.containers.remove(component.id());
+        self.registry.grid_rows.remove(component.id());
+        if let Some(entity) = self.registry.entities.remove(component.id()) {
+            commands.add(move |world: &mut World| despawn_subtree(world, entity));
+        }
+    }
+
+    /// Retire un sous-arbre des registres (ses entités disparaissent avec leur ancêtre)
+    fn forget_component(&mut self, commands: &mut Commands, component: &UIComponent, keep: &HashSet<String>) {
+        for child in component.children() {
+            if keep.contains(child.id()) {
+                if let Some(&child_entity) = self.registry.entities.get(child.id()) {
+                    commands.entity(child_entity).remove_parent();
+                }
+            } else {
+                self.forget_component(commands, child, keep);
+            }
+        }
+
+        self.registry.entities.remove(component.id());
+        self.registry.containers.remove(component.id());
+        self.registry.grid_rows.remove(component.id());
+    }
+
+    /// Construit ou patche un composant puis réconcilie ses enfants
+    fn reconcile_component(&mut self, commands: &mut Commands, component: &UIComponent, old_index: &HashMap<String, UIComponent>, updates: &mut Vec<UIUpdateEvent>, asset_server: &Res<AssetServer>) -> Option<Entity> {
+        let shell = component.without_children();
+        let previous = old_index.get(component.id());
+
+        let reused = match (previous, self.registry.entities.get(component.id()).copied()) {
+            (Some(previous), Some(entity)) if previous.type_name() == component.type_name() => {
+                // Les classes se changent en place, quel que soit le type du composant
+                let mut previous_shell = previous.without_children();
+                let mut patches = Vec::new();
+                if previous_shell.classes() != shell.classes() {
+                    patches.push(UIComponentUpdate::SetClasses(shell.classes().to_vec()));
+                    if let Some(classes) = previous_shell.classes_mut() {
+                        *classes = shell.classes().to_vec();
+                    }
+                }
+
+                let reusable = if previous_shell == shell {
+                    true
+                } else if let Some(prop_patches) = prop_updates(&previous_shell, &shell) {
+                    patches.extend(prop_patches);
+                    true
+                } else {
+                    false
+                };
+                reusable.then(|| {
+                    updates.extend(patches.into_iter().map(|update| UIUpdateEvent {
+                        id: component.id().to_string(),
+                        update,
+                    }));
+                    entity
+                })
+            },
+            _ => None,
+        };
+
+        let entity = match reused {
+            Some(entity) => entity,
+            None => {
+                // Reconstruire uniquement l'enveloppe : les enfants sont réconciliés ensuite
+                if let Some(previous) = previous {
+                    self.discard_shell(commands, previous);
+                }
+                self.build_component(commands, &shell, None, asset_server)?
+            }
+        };
+
+        // Éléments d'une grille conservée avant réconciliation, dans l'ancien ordre
+        let previous_items: Option<Vec<Option<Entity>>> = match (component, previous, reused) {
+            (UIComponent::Grid(_), Some(previous), Some(_)) => {
+                Some(previous.children().iter().map(|child| self.registry.get(child.id())).collect())
+            },
+            _ => None,
+        };
+
+        // Réconcilier les enfants, dans l'ordre du nouvel arbre
+        let children: Vec<Entity> = component
+            .children()
+            .iter()
+            .filter_map(|child| self.reconcile_component(commands, child, old_index, updates, asset_server))
+            .collect();
+
+        match component {
+            UIComponent::Grid(props) => {
+                // Les rangées intermédiaires ne sont recréées que si les éléments ou leur ordre
+                // changent ; une grille aux propriétés modifiées est reconstruite, ses colonnes avec
+                let unchanged = previous_items.map_or(false, |items| items.into_iter().eq(children.iter().copied().map(Some)));
+                if !unchanged || !self.registry.grid_rows.contains_key(&props.id) {
+                    self.layout_grid_rows(commands, &props.id, entity, props.columns, props.spacing.unwrap_or(5.0), &children);
+                }
+            },
+            _ if !children.is_empty() || previous.map_or(false, |previous| !previous.children().is_empty()) => {
+                let container = self.registry.containers.get(component.id()).copied().unwrap_or(entity);
+                commands.entity(container).replace_children(&children);
+            },
+            _ => {},
+        }
+
+        Some(entity)
+    }
+
+    /// Détruit l'entité d'un composant en détachant tous ses enfants DSL,
+    /// qui seront réattachés à la nouvelle enveloppe
+    fn discard_shell(&mut self, commands: &mut Commands, component: &UIComponent) {
+        for child in component.children() {
+            if let Some(&child_entity) = self.registry.entities.get(child.id()) {
+                commands.entity(child_entity).remove_parent();
+            }
+        }
+
+        self.registry.containers.remove(component.id());
+        self.registry.grid_rows.remove(component.id());
+        if let Some(entity) = self.registry.entities.remove(component.id()) {
+            commands.add(move |world: &mut World| despawn_subtree(world, entity));
+        }
+    }
+}
+
+/// Indexe un arbre de composants par ID
+fn index_components(component: &UIComponent, index: &mut HashMap<String, UIComponent>) {
+    for child in component.children() {
+        index_components(child, index);
+    }
+    index.insert(component.id().to_string(), component.clone());
+}
+
+/// Collecte les IDs d'un arbre de composants
+fn collect_ids(component: &UIComponent, ids: &mut HashSet<String>) {
+    ids.insert(component.id().to_string());
+    for child in component.children() {
+        collect_ids(child, ids);
+    }
+}
+
+/// Traduit les différences de propriétés entre deux enveloppes en mises à jour.
+/// Retourne `None` si une différence ne peut pas être appliquée en place.
+fn prop_updates(old: &UIComponent, new: &UIComponent) -> Option<Vec<UIComponentUpdate>> {
+    let mut updates = Vec::new();
+
+    match (old, new) {
+        (UIComponent::Window(old), UIComponent::Window(new)) => {
+            if (old.x, old.y) != (new.x, new.y) {
+                updates.push(UIComponentUpdate::SetPosition(new.x?, new.y?));
+            }
+            if (old.width, old.height) != (new.width, new.height) {
+                updates.push(UIComponentUpdate::SetSize(new.width, new.height));
+            }
+            let rest = WindowProps { x: new.x, y: new.y, width: new.width, height: new.height, ..old.clone() };
+            if rest != *new {
+                return None;
+            }
+        },
+        (UIComponent::Button(old), UIComponent::Button(new)) => {
+            if old.text != new.text {
+                updates.push(UIComponentUpdate::SetText(new.text.clone()));
+            }
+            if (old.width, old.height) != (new.width, new.height) {
+                updates.push(UIComponentUpdate::SetSize(new.width?, new.height?));
+            }
+            if old.disabled != new.disabled {
+                updates.push(UIComponentUpdate::SetDisabled(new.disabled.unwrap_or(false)));
+            }
+            let rest = ButtonProps { text: new.text.clone(), width: new.width, height: new.height, disabled: new.disabled, ..old.clone() };
+            if rest != *new {
+                return None;
+            }
+        },
+        (UIComponent::Text(old), UIComponent::Text(new)) => {
+            if old.content != new.content {
+                updates.push(UIComponentUpdate::SetText(new.content.clone()));
+            }
+            let rest = TextProps { content: new.content.clone(), ..old.clone() };
+            if rest != *new {
+                return None;
+            }
+        },
+        (UIComponent::Image(old), UIComponent::Image(new)) => {
+            if old.source != new.source {
+                updates.push(UIComponentUpdate::SetImage(new.source.clone()));
+            }
+            if (old.width, old.height) != (new.width, new.height) {
+                updates.push(UIComponentUpdate::SetSize(new.width?, new.height?));
+            }
+            let rest = ImageProps { source: new.source.clone(), width: new.width, height: new.height, ..old.clone() };
+            if rest != *new {
+                return None;
+            }
+        },
+        (UIComponent::SVG(old), UIComponent::SVG(new)) if old.markup.is_none() && new.markup.is_none() => {
+            if old.source != new.source {
+                updates.push(UIComponentUpdate::SetImage(new.source.clone()?));
+            }
+            if (old.width, old.height) != (new.width, new.height) {
+                updates.push(UIComponentUpdate::SetSize(new.width?, new.height?));
+            }
+            let rest = SVGProps { source: new.source.clone(), width: new.width, height: new.height, ..old.clone() };
+            if rest != *new {
+                return None;
+            }
+        },
+        (UIComponent::Node3D(old), UIComponent::Node3D(new)) => {
+            if old.transform != new.transform {
+                updates.push(UIComponentUpdate::SetTransform {
+                    position: Some(new.transform.position),
+                    rotation: Some(new.transform.rotation),
+                    scale: Some(new.transform.scale),
+                });
+            }
+            // Un champ de matériau retiré revient à sa valeur par défaut : reconstruire
+            if old.material != new.material {
+                let cleared = [
+                    old.material.color.is_some() && new.material.color.is_none(),
+                    old.material.metallic.is_some() && new.material.metallic.is_none(),
+                    old.material.roughness.is_some() && new.material.roughness.is_none(),
+                    old.material.texture.is_some() && new.material.texture.is_none(),
+                ];
+                if cleared.contains(&true) {
+                    return None;
+                }
+                updates.push(UIComponentUpdate::SetMaterial(new.material.clone()));
+            }
+            let rest = Node3DProps { transform: new.transform.clone(), material: new.material.clone(), ..old.clone() };
+            if rest != *new {
+                return None;
+            }
+        },
+        _ => return None,
+    }
+
+    Some(updates)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn button(text: &str) -> ButtonProps {
+        ButtonProps {
+            id: "save".to_string(),
+            text: text.to_string(),
+            width: Some(120.0),
+            height: Some(32.0),
+            on_click: Some("__handler_1".to_string()),
+            icon: None,
+            disabled: None,
+            style: None,
+            classes: Vec::new(),
+        }
+    }
+
+    fn text(content: &str) -> TextProps {
+        TextProps {
+            id: "status".to_string(),
+            content: content.to_string(),
+            size: None,
+            color: None,
+            align: None,
+            font: None,
+            style: None,
+            classes: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn patches_supported_properties_in_place() {
+        let old = UIComponent::Button(button("Enregistrer"));
+        let new = UIComponent::Button(ButtonProps {
+            width: Some(140.0),
+            disabled: Some(true),
+            ..button("Sauvegarder")
+        });
+
+        let updates = prop_updates(&old, &new).unwrap();
+
+        assert_eq!(updates.len(), 3);
+        assert!(matches!(&updates[0], UIComponentUpdate::SetText(text) if text == "Sauvegarder"));
+        assert!(matches!(updates[1], UIComponentUpdate::SetSize(width, height) if width == 140.0 && height == 32.0));
+        assert!(matches!(updates[2], UIComponentUpdate::SetDisabled(true)));
+    }
+
+    #[test]
+    fn rebuilds_when_a_property_cannot_be_patched() {
+        let old = UIComponent::Text(text("Prêt"));
+        let new = UIComponent::Text(TextProps { size: Some(20.0), ..text("Prêt") });
+        assert!(prop_updates(&old, &new).is_none());
+
+        // Une taille retirée ne se patche pas
+        let old = UIComponent::Button(button("OK"));
+        let new = UIComponent::Button(ButtonProps { width: None, ..button("OK") });
+        assert!(prop_updates(&old, &new).is_none());
+
+        // Un handler remplacé non plus
+        let new = UIComponent::Button(ButtonProps { on_click: Some("__handler_2".to_string()), ..button("OK") });
+        assert!(prop_updates(&old, &new).is_none());
+
+        assert!(prop_updates(&UIComponent::Text(text("OK")), &old).is_none());
+    }
+
+    #[test]
+    fn unchanged_components_need_no_update() {
+        let component = UIComponent::Text(text("Prêt"));
+        assert_eq!(prop_updates(&component, &component).map(|updates| updates.len()), Some(0));
+    }
+
+    #[test]
+    fn indexes_every_component_of_the_tree() {
+        let tree = UIComponent::Window(WindowProps {
+            id: "main".to_string(),
+            title: "Principale".to_string(),
+            width: 400.0,
+            height: 300.0,
+            x: None,
+            y: None,
+            resizable: None,
+            draggable: None,
+            children: vec![UIComponent::Text(text("Prêt")), UIComponent::Button(button("OK"))],
+            style: None,
+            classes: Vec::new(),
+        });
+
+        let mut index = HashMap::new();
+        index_components(&tree, &mut index);
+        let mut ids = HashSet::new();
+        collect_ids(&tree, &mut ids);
+
+        assert_eq!(index.len(), 3);
+        assert!(["main", "status", "save"].iter().all(|id| ids.contains(*id) && index.contains_key(*id)));
+    }
+}
@@ -0,0 +1,101 @@
+// src/ui/builder/scene_components.rs
+use bevy::{
+    prelude::*,
+    render::mesh::shape,
+};
+
+use crate::ui::color::parse_color;
+use crate::ui::components::*;
+use crate::ui::viewport3d::{euler_rotation, patch_material};
+use super::base::UIBuilder;
+
+impl UIBuilder<'_> {
+    /// Construit un nœud 3D et ses enfants ; il reçoit la couche de son viewport
+    /// par `viewport_layers_system` une fois rattaché
+    pub fn build_node3d(&mut self, commands: &mut Commands, props: &Node3DProps, parent: Option<Entity>, asset_server: &Res<AssetServer>) -> Option<Entity> {
+        let transform = Transform {
+            translation: Vec3::from(props.transform.position),
+            rotation: euler_rotation(props.transform.rotation),
+            scale: Vec3::from(props.transform.scale),
+        };
+        // Chaque nœud a son propre matériau, modifiable par `set_material`
+        let material = || {
+            let mut material = StandardMaterial::default();
+            patch_material(&mut material, &props.material, asset_server);
+            asset_server.add(material)
+        };
+        let light_color = props.material.color.as_deref().and_then(|color| parse_color(color).ok()).unwrap_or(Color::WHITE);
+
+        let node_entity = match &props.kind {
+            Node3DKind::Cube { size } => commands.spawn(PbrBundle {
+                mesh: asset_server.add(Mesh::from(shape::Cube { size: *size })),
+                material: material(),
+                transform,
+                ..default()
+            }),
+            Node3DKind::Sphere { radius } => commands.spawn(PbrBundle {
+                mesh: asset_server.add(Mesh::from(shape::UVSphere { radius: *radius, sectors: 32, stacks: 16 })),
+                material: material(),
+                transform,
+                ..default()
+            }),
+            Node3DKind::Plane { size } => commands.spawn(PbrBundle {
+                mesh: asset_server.add(Mesh::from(shape::Plane::from_size(*size))),
+                material: material(),
+                transform,
+                ..default()
+            }),
+            Node3DKind::Mesh { source } => commands.spawn(PbrBundle {
+                mesh: asset_server.load(source.clone()),
+                material: material(),
+                transform,
+                ..default()
+            }),
+            Node3DKind::PointLight { intensity, range } => commands.spawn(PointLightBundle {
+                point_light: PointLight {
+                    intensity: *intensity,
+                    range: *range,
+                    color: light_color,
+                    shadows_enabled: true,
+                    ..default()
+                },
+                transform,
+                ..default()
+            }),
+            // Éclaire dans la direction -Z du nœud, orientée par `rotation`
+            Node3DKind::DirectionalLight { illuminance } => commands.spawn(DirectionalLightBundle {
+                directional_light: DirectionalLight {
+                    illuminance: *illuminance,
+                    color: light_color,
+                    shadows_enabled: true,
+                    ..default()
+                },
+                transform,
+                ..default()
+            }),
+            Node3DKind::Group => commands.spawn(SpatialBundle { transform, ..default() }),
+        }
+        .insert((
+            SceneNode { id: props.id.clone() },
+            Name::new(props.id.clone()),
+        ))
+        .id();
+
+        // Construire les nœuds enfants
+        for child in &props.children {
+            if let Some(child_entity) = self.build_component(commands, child, Some(node_entity), asset_server) {
+                commands.entity(node_entity).add_child(child_entity);
+            }
+        }
+
+        // Si un parent est spécifié, ajouter le nœud comme enfant
+        if let Some(parent_entity) = parent {
+            commands.entity(parent_entity).add_child(node_entity);
+        }
+
+        // Enregistrer l'entité créée
+        self.registry.entities.insert(props.id.clone(), node_entity);
+
+        Some(node_entity)
+    }
+}
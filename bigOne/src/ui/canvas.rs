@@ -0,0 +1,588 @@
+// src/ui/canvas.rs
+//! Canevas : dessin impératif en Ruby, rastérisé par tiny-skia.
+//!
+//! ```ruby
+//! canvas(id: "chart", width: 300, height: 200, on_draw: ->(ctx) {
+//!   ctx.clear("$canvas")
+//!   ctx.fill_rect(10, 10, 80, 40, ctx.linear_gradient(10, 0, 90, 0, 0 => "tomato", 1 => "gold"))
+//!   ctx.path { |p| p.arc(150, 100, 40, 0, Math::PI) }
+//!   ctx.stroke_path("$text", line_width: 2, cap: :round)
+//!   ctx.text("Ventes", 150, 190, color: "$text", size: 14, align: :center)
+//! })
+//! ```
+//!
+//! `on_draw` reçoit un `OS::DrawContext` qui enregistre une liste d'affichage ; elle est
+//! rastérisée dans l'image du nœud, à sa taille calculée multipliée par le facteur
+//! d'échelle de la fenêtre. Le handler n'est rappelé que lorsque le canevas est invalidé
+//! (`OS.find(id).invalidate`), redimensionné ou que le script est rechargé. Un changement
+//! de thème rastérise de nouveau la liste sans rappeler Ruby.
+use bevy::asset::LoadState;
+use bevy::prelude::*;
+use bevy::render::render_resource::TextureFormat;
+use bevy::window::PrimaryWindow;
+use resvg::tiny_skia::{
+    self, ColorU8, FillRule, FilterQuality, GradientStop, LinearGradient, Mask, Path, PathBuilder, Pixmap,
+    PixmapPaint, Point, RadialGradient, Shader, SpreadMode, Stroke, StrokeDash, Transform,
+};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::f32::consts::{FRAC_PI_2, TAU};
+
+use crate::dsl::worker::RubyRequest;
+use crate::ui::color::{ColorTokens, ColorValue};
+use crate::ui::components::UIElement;
+use crate::ui::registry::UIRegistry;
+use crate::ui::svg;
+use crate::ui::systems::UIDSLState;
+
+/// Opération de la liste d'affichage, au format produit par `OS::DrawContext`.
+///
+/// Les coordonnées sont en pixels logiques, dans le repère courant.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum DrawCommand {
+    Save,
+    Restore,
+    /// Matrice `[a, b, c, d, e, f]` composée avec le repère courant, comme en CSS
+    Transform { matrix: [f32; 6] },
+    Clear { paint: Option<String> },
+    FillRect { x: f32, y: f32, width: f32, height: f32, paint: Paint },
+    StrokeRect {
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        paint: Paint,
+        #[serde(flatten)]
+        stroke: StrokeStyle,
+    },
+    FillPath {
+        path: Vec<PathSegment>,
+        paint: Paint,
+        #[serde(default)]
+        even_odd: bool,
+    },
+    StrokePath {
+        path: Vec<PathSegment>,
+        paint: Paint,
+        #[serde(flatten)]
+        stroke: StrokeStyle,
+    },
+    /// Restreint les dessins suivants au chemin, jusqu'au `restore` correspondant
+    Clip {
+        path: Vec<PathSegment>,
+        #[serde(default)]
+        even_odd: bool,
+    },
+    /// Texte posé sur sa ligne de base
+    Text {
+        text: String,
+        x: f32,
+        y: f32,
+        color: String,
+        size: f32,
+        font: Option<String>,
+        #[serde(default)]
+        align: TextAlign,
+    },
+    /// Image des assets, à sa taille naturelle sauf dimensions données
+    Image { source: String, x: f32, y: f32, width: Option<f32>, height: Option<f32> },
+}
+
+/// Élément d'un chemin
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum PathSegment {
+    MoveTo { x: f32, y: f32 },
+    LineTo { x: f32, y: f32 },
+    QuadTo { cx: f32, cy: f32, x: f32, y: f32 },
+    CubicTo { c1x: f32, c1y: f32, c2x: f32, c2y: f32, x: f32, y: f32 },
+    /// Arc de cercle, angles en radians, relié au point courant
+    Arc {
+        cx: f32,
+        cy: f32,
+        radius: f32,
+        start: f32,
+        end: f32,
+        #[serde(default)]
+        counterclockwise: bool,
+    },
+    Rect { x: f32, y: f32, width: f32, height: f32 },
+    Close,
+}
+
+/// Couleur (syntaxe CSS ou jeton de thème) ou dégradé
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum Paint {
+    Color(String),
+    Gradient(Gradient),
+}
+
+/// Dégradé ; chaque arrêt est une position entre 0 et 1 et une couleur
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Gradient {
+    Linear { x0: f32, y0: f32, x1: f32, y1: f32, stops: Vec<(f32, String)> },
+    Radial { cx: f32, cy: f32, radius: f32, stops: Vec<(f32, String)> },
+}
+
+/// Épaisseur, extrémités, jointures et pointillés d'un contour
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct StrokeStyle {
+    pub line_width: f32,
+    pub cap: StrokeCap,
+    pub join: StrokeJoin,
+    pub dash: Vec<f32>,
+}
+
+impl Default for StrokeStyle {
+    fn default() -> Self {
+        Self { line_width: 1.0, cap: StrokeCap::Butt, join: StrokeJoin::Miter, dash: Vec::new() }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StrokeCap {
+    Butt,
+    Round,
+    Square,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StrokeJoin {
+    Miter,
+    Round,
+    Bevel,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TextAlign {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+/// Liste d'affichage retournée par le `on_draw` d'un canevas
+#[derive(Event)]
+pub struct CanvasDrawn {
+    pub id: String,
+    pub result: Result<Vec<DrawCommand>, String>,
+}
+
+/// Surface de dessin d'un canevas ; le rendu remplit la texture de son image enfant
+#[derive(Component, Debug)]
+pub struct CanvasSurface {
+    on_draw: Option<String>,
+    texture: Handle<Image>,
+    /// Taille en pixels physiques et échelle du dernier dessin demandé à Ruby
+    requested: Option<(UVec2, f32)>,
+    invalid: bool,
+    /// Dessin demandé, pas encore reçu
+    pending: bool,
+    display_list: Vec<DrawCommand>,
+    /// Images de la liste, gardées chargées tant qu'elle est affichée
+    images: Vec<(String, Handle<Image>)>,
+    rasterized: bool,
+}
+
+impl CanvasSurface {
+    pub fn new(on_draw: Option<String>, texture: Handle<Image>) -> Self {
+        Self {
+            on_draw,
+            texture,
+            requested: None,
+            invalid: false,
+            pending: false,
+            display_list: Vec::new(),
+            images: Vec::new(),
+            rasterized: true,
+        }
+    }
+
+    /// Rappelle `on_draw` à la prochaine frame
+    pub fn invalidate(&mut self) {
+        self.invalid = true;
+    }
+}
+
+/// Demande à Ruby le dessin des canevas invalidés ou redimensionnés
+pub fn request_canvas_draw_system(
+    mut dsl_state: ResMut<UIDSLState>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut canvases: Query<(&UIElement, &Node, &mut CanvasSurface)>,
+) {
+    let Some(worker) = dsl_state.worker.as_mut() else {
+        return;
+    };
+    let scale = windows.get_single().map_or(1.0, |window| window.scale_factor() as f32);
+
+    for (element, node, mut surface) in &mut canvases {
+        // Un seul dessin en cours par canevas ; un redimensionnement entre-temps
+        // est traité à la réception
+        let size = (node.size() * scale).round().as_uvec2();
+        if size.x == 0 || size.y == 0 || surface.pending {
+            continue;
+        }
+        let requested = Some((size, scale));
+        if !surface.invalid && surface.requested == requested {
+            continue;
+        }
+        let Some(callback) = surface.on_draw.clone() else {
+            continue;
+        };
+        worker.send(RubyRequest::Draw {
+            id: element.id.clone(),
+            callback,
+            width: node.size().x,
+            height: node.size().y,
+        });
+        surface.requested = requested;
+        surface.invalid = false;
+        surface.pending = true;
+    }
+}
+
+/// Rastérise les listes d'affichage reçues, une fois leurs images chargées,
+/// et de nouveau quand les couleurs du thème changent
+pub fn rasterize_canvas_system(
+    mut drawn: EventReader<CanvasDrawn>,
+    registry: Res<UIRegistry>,
+    asset_server: Res<AssetServer>,
+    mut images: ResMut<Assets<Image>>,
+    tokens: Res<ColorTokens>,
+    mut canvases: Query<&mut CanvasSurface>,
+) {
+    for event in drawn.iter() {
+        let Some(mut surface) = registry.get(&event.id).and_then(|entity| canvases.get_mut(entity).ok()) else {
+            continue;
+        };
+        surface.pending = false;
+        match &event.result {
+            Ok(display_list) => {
+                surface.images = display_list
+                    .iter()
+                    .filter_map(|command| match command {
+                        DrawCommand::Image { source, .. } => Some((source.clone(), asset_server.load(source.clone()))),
+                        _ => None,
+                    })
+                    .collect();
+                surface.display_list = display_list.clone();
+                surface.rasterized = false;
+            },
+            Err(error) => error!("Erreur de dessin du canevas '{}': {}", event.id, error),
+        }
+    }
+
+    for mut surface in &mut canvases {
+        if surface.rasterized && !tokens.is_changed() {
+            continue;
+        }
+        let Some((size, scale)) = surface.requested else {
+            continue;
+        };
+        // Une image en échec est signalée au rendu plutôt qu'attendue
+        let loading = surface.images.iter().any(|(_, handle)| {
+            images.get(handle).is_none() && asset_server.get_load_state(handle.id()) != Some(LoadState::Failed)
+        });
+        if loading {
+            continue;
+        }
+
+        let sources: HashMap<String, Pixmap> = surface
+            .images
+            .iter()
+            .filter_map(|(source, handle)| Some((source.clone(), to_pixmap(images.get(handle)?)?)))
+            .collect();
+        let mut painter = Painter::new(size, scale, &tokens, &sources);
+        for command in &surface.display_list {
+            if let Err(error) = painter.draw(command) {
+                painter.errors.push(error);
+            }
+        }
+        let Painter { pixmap, mut errors, .. } = painter;
+        errors.dedup();
+        for error in errors {
+            warn!("Canevas: {}", error);
+        }
+        images.insert(surface.texture.id(), svg::pixmap_to_image(&pixmap));
+        surface.rasterized = true;
+    }
+}
+
+/// Repère et zone de découpe courants
+#[derive(Clone)]
+struct DrawState {
+    transform: Transform,
+    clip: Option<Mask>,
+}
+
+/// Exécute une liste d'affichage sur un pixmap
+struct Painter<'a> {
+    pixmap: Pixmap,
+    /// Taille en pixels logiques
+    logical: Vec2,
+    state: DrawState,
+    stack: Vec<DrawState>,
+    tokens: &'a ColorTokens,
+    images: &'a HashMap<String, Pixmap>,
+    errors: Vec<String>,
+}
+
+impl<'a> Painter<'a> {
+    fn new(size: UVec2, scale: f32, tokens: &'a ColorTokens, images: &'a HashMap<String, Pixmap>) -> Self {
+        Self {
+            pixmap: Pixmap::new(size.x, size.y).expect("taille de canevas non nulle"),
+            logical: size.as_vec2() / scale,
+            state: DrawState { transform: Transform::from_scale(scale, scale), clip: None },
+            stack: Vec::new(),
+            tokens,
+            images,
+            errors: Vec::new(),
+        }
+    }
+
+    fn draw(&mut self, command: &DrawCommand) -> Result<(), String> {
+        let transform = self.state.transform;
+        match command {
+            DrawCommand::Save => self.stack.push(self.state.clone()),
+            DrawCommand::Restore => {
+                if let Some(state) = self.stack.pop() {
+                    self.state = state;
+                }
+            },
+            DrawCommand::Transform { matrix: [a, b, c, d, e, f] } => {
+                self.state.transform = transform.pre_concat(Transform::from_row(*a, *b, *c, *d, *e, *f));
+            },
+            DrawCommand::Clear { paint } => {
+                let color = match paint {
+                    Some(color) => self.color(color)?,
+                    None => tiny_skia::Color::TRANSPARENT,
+                };
+                self.pixmap.fill(color);
+            },
+            DrawCommand::FillRect { x, y, width, height, paint } => {
+                let rect = rect(*x, *y, *width, *height)?;
+                let paint = self.paint(paint)?;
+                self.pixmap.fill_rect(rect, &paint, transform, self.state.clip.as_ref());
+            },
+            DrawCommand::StrokeRect { x, y, width, height, paint, stroke } => {
+                let path = PathBuilder::from_rect(rect(*x, *y, *width, *height)?);
+                self.stroke(&path, paint, stroke)?;
+            },
+            DrawCommand::FillPath { path, paint, even_odd } => {
+                if let Some(path) = build_path(path) {
+                    let paint = self.paint(paint)?;
+                    self.pixmap.fill_path(&path, &paint, fill_rule(*even_odd), transform, self.state.clip.as_ref());
+                }
+            },
+            DrawCommand::StrokePath { path, paint, stroke } => {
+                if let Some(path) = build_path(path) {
+                    self.stroke(&path, paint, stroke)?;
+                }
+            },
+            DrawCommand::Clip { path, even_odd } => {
+                // Un chemin vide masque tout
+                let mask = match (build_path(path), self.state.clip.take()) {
+                    (Some(path), Some(mut mask)) => {
+                        mask.intersect_path(&path, fill_rule(*even_odd), true, transform);
+                        mask
+                    },
+                    (Some(path), None) => {
+                        let mut mask = self.empty_mask()?;
+                        mask.fill_path(&path, fill_rule(*even_odd), true, transform);
+                        mask
+                    },
+                    (None, _) => self.empty_mask()?,
+                };
+                self.state.clip = Some(mask);
+            },
+            DrawCommand::Text { text, x, y, color, size, font, align } => {
+                let color = self.color(color)?;
+                self.text(text, *x, *y, color, *size, font.as_deref(), *align)?;
+            },
+            DrawCommand::Image { source, x, y, width, height } => {
+                let image = self.images.get(source).ok_or_else(|| format!("image `{}` indisponible", source))?;
+                let (natural_width, natural_height) = (image.width() as f32, image.height() as f32);
+                let transform = transform.pre_translate(*x, *y).pre_scale(
+                    width.unwrap_or(natural_width) / natural_width,
+                    height.unwrap_or(natural_height) / natural_height,
+                );
+                let paint = PixmapPaint { quality: FilterQuality::Bilinear, ..default() };
+                self.pixmap.draw_pixmap(0, 0, image.as_ref(), &paint, transform, self.state.clip.as_ref());
+            },
+        }
+        Ok(())
+    }
+
+    fn stroke(&mut self, path: &Path, paint: &Paint, style: &StrokeStyle) -> Result<(), String> {
+        let paint = self.paint(paint)?;
+        let dash = match style.dash.as_slice() {
+            [] => None,
+            dash => Some(StrokeDash::new(dash.to_vec(), 0.0).ok_or("motif de pointillés invalide")?),
+        };
+        let stroke = Stroke {
+            width: style.line_width,
+            line_cap: match style.cap {
+                StrokeCap::Butt => tiny_skia::LineCap::Butt,
+                StrokeCap::Round => tiny_skia::LineCap::Round,
+                StrokeCap::Square => tiny_skia::LineCap::Square,
+            },
+            line_join: match style.join {
+                StrokeJoin::Miter => tiny_skia::LineJoin::Miter,
+                StrokeJoin::Round => tiny_skia::LineJoin::Round,
+                StrokeJoin::Bevel => tiny_skia::LineJoin::Bevel,
+            },
+            dash,
+            ..default()
+        };
+        self.pixmap.stroke_path(path, &paint, &stroke, self.state.transform, self.state.clip.as_ref());
+        Ok(())
+    }
+
+    /// Texte mis en forme par usvg, avec les polices du système et des assets
+    #[allow(clippy::too_many_arguments)]
+    fn text(&mut self, text: &str, x: f32, y: f32, color: tiny_skia::Color, size: f32, font: Option<&str>, align: TextAlign) -> Result<(), String> {
+        let anchor = match align {
+            TextAlign::Left => "start",
+            TextAlign::Center => "middle",
+            TextAlign::Right => "end",
+        };
+        let rgba = color.to_color_u8();
+        let markup = format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}"><text x="{}" y="{}" font-size="{}" font-family="{}" text-anchor="{}" fill="rgb({},{},{})" fill-opacity="{}">{}</text></svg>"#,
+            self.logical.x, self.logical.y, x, y, size, escape(font.unwrap_or("sans-serif")), anchor,
+            rgba.red(), rgba.green(), rgba.blue(), color.alpha(), escape(text),
+        );
+        let tree = svg::parse(markup.as_bytes()).map_err(|error| error.to_string())?;
+
+        // Rendu sur un calque, pour appliquer la zone de découpe
+        let mut layer = Pixmap::new(self.pixmap.width(), self.pixmap.height()).ok_or("calque de texte impossible")?;
+        resvg::Tree::from_usvg(&tree).render(self.state.transform, &mut layer.as_mut());
+        self.pixmap.draw_pixmap(0, 0, layer.as_ref(), &PixmapPaint::default(), Transform::identity(), self.state.clip.as_ref());
+        Ok(())
+    }
+
+    fn paint(&self, paint: &Paint) -> Result<tiny_skia::Paint<'static>, String> {
+        let shader = match paint {
+            Paint::Color(color) => Shader::SolidColor(self.color(color)?),
+            Paint::Gradient(Gradient::Linear { x0, y0, x1, y1, stops }) => LinearGradient::new(
+                Point::from_xy(*x0, *y0),
+                Point::from_xy(*x1, *y1),
+                self.stops(stops)?,
+                SpreadMode::Pad,
+                Transform::identity(),
+            )
+            .ok_or("dégradé linéaire sans arrêt ou de longueur nulle")?,
+            Paint::Gradient(Gradient::Radial { cx, cy, radius, stops }) => RadialGradient::new(
+                Point::from_xy(*cx, *cy),
+                Point::from_xy(*cx, *cy),
+                *radius,
+                self.stops(stops)?,
+                SpreadMode::Pad,
+                Transform::identity(),
+            )
+            .ok_or("dégradé radial sans arrêt ou de rayon nul")?,
+        };
+        Ok(tiny_skia::Paint { shader, anti_alias: true, ..default() })
+    }
+
+    fn stops(&self, stops: &[(f32, String)]) -> Result<Vec<GradientStop>, String> {
+        stops.iter().map(|(offset, color)| Ok(GradientStop::new(*offset, self.color(color)?))).collect()
+    }
+
+    fn color(&self, value: &str) -> Result<tiny_skia::Color, String> {
+        let color = ColorValue::parse(value)
+            .map_err(|reason| format!("couleur `{}` invalide: {}", value, reason))?
+            .resolve(Some(self.tokens))
+            .ok_or_else(|| format!("jeton de couleur inconnu `{}`", value))?;
+        let [red, green, blue, alpha] = color.as_rgba_f32().map(|channel| channel.clamp(0.0, 1.0));
+        Ok(tiny_skia::Color::from_rgba(red, green, blue, alpha).unwrap_or(tiny_skia::Color::TRANSPARENT))
+    }
+
+    fn empty_mask(&self) -> Result<Mask, String> {
+        Mask::new(self.pixmap.width(), self.pixmap.height()).ok_or_else(|| "zone de découpe impossible".to_string())
+    }
+}
+
+fn rect(x: f32, y: f32, width: f32, height: f32) -> Result<tiny_skia::Rect, String> {
+    tiny_skia::Rect::from_xywh(x, y, width, height).ok_or_else(|| format!("rectangle invalide {}x{}", width, height))
+}
+
+fn fill_rule(even_odd: bool) -> FillRule {
+    if even_odd {
+        FillRule::EvenOdd
+    } else {
+        FillRule::Winding
+    }
+}
+
+/// Chemin tiny-skia ; `None` pour un chemin vide
+fn build_path(segments: &[PathSegment]) -> Option<Path> {
+    let mut builder = PathBuilder::new();
+    for segment in segments {
+        match *segment {
+            PathSegment::MoveTo { x, y } => builder.move_to(x, y),
+            PathSegment::LineTo { x, y } => builder.line_to(x, y),
+            PathSegment::QuadTo { cx, cy, x, y } => builder.quad_to(cx, cy, x, y),
+            PathSegment::CubicTo { c1x, c1y, c2x, c2y, x, y } => builder.cubic_to(c1x, c1y, c2x, c2y, x, y),
+            PathSegment::Arc { cx, cy, radius, start, end, counterclockwise } => {
+                arc(&mut builder, cx, cy, radius, start, end, counterclockwise)
+            },
+            PathSegment::Rect { x, y, width, height } => {
+                if let Some(rect) = tiny_skia::Rect::from_xywh(x, y, width, height) {
+                    builder.push_rect(rect);
+                }
+            },
+            PathSegment::Close => builder.close(),
+        }
+    }
+    builder.finish()
+}
+
+/// Arc de cercle approché par des cubiques d'au plus un quart de tour
+fn arc(builder: &mut PathBuilder, cx: f32, cy: f32, radius: f32, start: f32, end: f32, counterclockwise: bool) {
+    // Balayage borné à un tour, dans le sens demandé, comme `arc` en HTML
+    let turn = |delta: f32| if delta >= TAU { TAU } else { delta.rem_euclid(TAU) };
+    let sweep = if counterclockwise { -turn(start - end) } else { turn(end - start) };
+    let point = |angle: f32| (cx + radius * angle.cos(), cy + radius * angle.sin());
+
+    let (x, y) = point(start);
+    if builder.is_empty() {
+        builder.move_to(x, y);
+    } else {
+        builder.line_to(x, y);
+    }
+
+    let segments = (sweep.abs() / FRAC_PI_2).ceil().max(1.0) as usize;
+    let step = sweep / segments as f32;
+    let k = 4.0 / 3.0 * (step / 4.0).tan() * radius;
+    for index in 0..segments {
+        let from = start + step * index as f32;
+        let to = from + step;
+        let ((x0, y0), (x1, y1)) = (point(from), point(to));
+        builder.cubic_to(x0 - k * from.sin(), y0 + k * from.cos(), x1 + k * to.sin(), y1 - k * to.cos(), x1, y1);
+    }
+}
+
+/// Image Bevy RGBA 8 bits en pixmap tiny-skia (alpha prémultiplié)
+fn to_pixmap(image: &Image) -> Option<Pixmap> {
+    if !matches!(image.texture_descriptor.format, TextureFormat::Rgba8UnormSrgb | TextureFormat::Rgba8Unorm) {
+        return None;
+    }
+    let size = image.texture_descriptor.size;
+    let mut pixmap = Pixmap::new(size.width, size.height)?;
+    for (pixel, rgba) in pixmap.pixels_mut().iter_mut().zip(image.data.chunks_exact(4)) {
+        *pixel = ColorU8::from_rgba(rgba[0], rgba[1], rgba[2], rgba[3]).premultiply();
+    }
+    Some(pixmap)
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
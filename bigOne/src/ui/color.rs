@@ -0,0 +1,391 @@
+// src/ui/color.rs
+//! Couleurs CSS du DSL.
+//!
+//! Formats acceptés : `#rgb`, `#rgba`, `#rrggbb`, `#rrggbbaa`, `rgb()`, `rgba()`,
+//! `hsl()`, `hsla()` (virgules ou espaces, alpha après `/` ou en 4e valeur, en
+//! nombre ou en pourcentage), les 148 couleurs nommées CSS, `transparent`, et les
+//! jetons de thème `$nom` ou `var(--nom)`, résolus au moment de l'application.
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+/// Couleur lue dans un script : littérale, ou référence à un jeton de thème
+#[derive(Debug, Clone, PartialEq)]
+pub enum ColorValue {
+    Literal(Color),
+    Token(String),
+}
+
+impl ColorValue {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let value = value.trim();
+        if let Some(token) = token_name(value) {
+            if token.is_empty() || !token.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.') {
+                return Err(format!("nom de jeton invalide `{}`", value));
+            }
+            return Ok(ColorValue::Token(token.to_string()));
+        }
+        parse_color(value).map(ColorValue::Literal)
+    }
+
+    /// Couleur effective ; `None` pour un jeton que le thème ne définit pas
+    pub fn resolve(&self, tokens: Option<&ColorTokens>) -> Option<Color> {
+        match self {
+            ColorValue::Literal(color) => Some(*color),
+            ColorValue::Token(name) => tokens.and_then(|tokens| tokens.get(name)),
+        }
+    }
+}
+
+/// Couleurs nommées du thème actif, référencées par `$nom` dans les styles
+#[derive(Resource, Debug, Clone, Default, PartialEq)]
+pub struct ColorTokens(pub HashMap<String, Color>);
+
+impl ColorTokens {
+    pub fn get(&self, name: &str) -> Option<Color> {
+        self.0.get(name).copied()
+    }
+}
+
+fn token_name(value: &str) -> Option<&str> {
+    if let Some(name) = value.strip_prefix('$') {
+        return Some(name);
+    }
+    value
+        .strip_prefix("var(")
+        .and_then(|rest| rest.strip_suffix(')'))
+        .map(|inner| inner.trim().trim_start_matches("--"))
+}
+
+/// Couleur littérale (sans jeton de thème)
+pub fn parse_color(value: &str) -> Result<Color, String> {
+    let value = value.trim();
+    let lower = value.to_ascii_lowercase();
+
+    if let Some(hex) = lower.strip_prefix('#') {
+        return parse_hex(hex).ok_or_else(|| format!("couleur hexadécimale invalide `{}`", value));
+    }
+    if let Some((function, arguments)) = lower.split_once('(') {
+        let arguments = arguments
+            .strip_suffix(')')
+            .ok_or_else(|| format!("parenthèse fermante manquante dans `{}`", value))?;
+        return match function.trim() {
+            "rgb" | "rgba" => parse_rgb(arguments),
+            "hsl" | "hsla" => parse_hsl(arguments),
+            other => Err(format!("fonction de couleur inconnue `{}`", other)),
+        }
+        .map_err(|reason| format!("{} dans `{}`", reason, value));
+    }
+    named_color(&lower).ok_or_else(|| format!("couleur inconnue `{}`", value))
+}
+
+fn parse_hex(hex: &str) -> Option<Color> {
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let digits: Vec<u8> = match hex.len() {
+        // Forme courte : chaque chiffre est doublé (#abc = #aabbcc)
+        3 | 4 => hex.chars().map(|c| c.to_digit(16).map(|d| (d * 17) as u8)).collect::<Option<_>>()?,
+        6 | 8 => (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+            .collect::<Option<_>>()?,
+        _ => return None,
+    };
+    let alpha = digits.get(3).copied().unwrap_or(255);
+    Some(Color::rgba_u8(digits[0], digits[1], digits[2], alpha))
+}
+
+/// Arguments d'une fonction de couleur : `r, g, b[, a]` ou `r g b[ / a]`
+fn split_arguments(arguments: &str) -> Result<(Vec<&str>, Option<&str>), String> {
+    let (channels, alpha) = match arguments.split_once('/') {
+        Some((channels, alpha)) => (channels, Some(alpha.trim())),
+        None => (arguments, None),
+    };
+    let mut channels: Vec<&str> = channels
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|part| !part.is_empty())
+        .collect();
+
+    match (channels.len(), alpha) {
+        (3, alpha) => Ok((channels, alpha)),
+        (4, None) => {
+            let alpha = channels.pop();
+            Ok((channels, alpha))
+        },
+        _ => Err("3 composantes attendues, plus une opacité facultative".to_string()),
+    }
+}
+
+fn parse_rgb(arguments: &str) -> Result<Color, String> {
+    let (channels, alpha) = split_arguments(arguments)?;
+    let mut rgb = [0.0; 3];
+    for (slot, channel) in rgb.iter_mut().zip(&channels) {
+        *slot = match channel.strip_suffix('%') {
+            Some(percent) => number(percent)? / 100.0,
+            None => number(channel)? / 255.0,
+        }
+        .clamp(0.0, 1.0);
+    }
+    Ok(Color::rgba(rgb[0], rgb[1], rgb[2], parse_alpha(alpha)?))
+}
+
+fn parse_hsl(arguments: &str) -> Result<Color, String> {
+    let (channels, alpha) = split_arguments(arguments)?;
+    let hue = number(channels[0].trim_end_matches("deg"))?.rem_euclid(360.0);
+    let saturation = percentage(channels[1])?;
+    let lightness = percentage(channels[2])?;
+    Ok(Color::hsla(hue, saturation, lightness, parse_alpha(alpha)?))
+}
+
+fn parse_alpha(alpha: Option<&str>) -> Result<f32, String> {
+    let Some(alpha) = alpha else {
+        return Ok(1.0);
+    };
+    let alpha = match alpha.strip_suffix('%') {
+        Some(percent) => number(percent)? / 100.0,
+        None => number(alpha)?,
+    };
+    Ok(alpha.clamp(0.0, 1.0))
+}
+
+fn percentage(value: &str) -> Result<f32, String> {
+    let percent = value
+        .strip_suffix('%')
+        .ok_or_else(|| format!("pourcentage attendu, reçu `{}`", value))?;
+    Ok((number(percent)? / 100.0).clamp(0.0, 1.0))
+}
+
+fn number(value: &str) -> Result<f32, String> {
+    value
+        .trim()
+        .parse::<f32>()
+        .map_err(|_| format!("nombre invalide `{}`", value.trim()))
+}
+
+/// Couleurs nommées CSS (niveau 4)
+fn named_color(name: &str) -> Option<Color> {
+    let rgb: u32 = match name {
+        "transparent" => return Some(Color::NONE),
+        "aliceblue" => 0xf0f8ff,
+        "antiquewhite" => 0xfaebd7,
+        "aqua" | "cyan" => 0x00ffff,
+        "aquamarine" => 0x7fffd4,
+        "azure" => 0xf0ffff,
+        "beige" => 0xf5f5dc,
+        "bisque" => 0xffe4c4,
+        "black" => 0x000000,
+        "blanchedalmond" => 0xffebcd,
+        "blue" => 0x0000ff,
+        "blueviolet" => 0x8a2be2,
+        "brown" => 0xa52a2a,
+        "burlywood" => 0xdeb887,
+        "cadetblue" => 0x5f9ea0,
+        "chartreuse" => 0x7fff00,
+        "chocolate" => 0xd2691e,
+        "coral" => 0xff7f50,
+        "cornflowerblue" => 0x6495ed,
+        "cornsilk" => 0xfff8dc,
+        "crimson" => 0xdc143c,
+        "darkblue" => 0x00008b,
+        "darkcyan" => 0x008b8b,
+        "darkgoldenrod" => 0xb8860b,
+        "darkgray" | "darkgrey" => 0xa9a9a9,
+        "darkgreen" => 0x006400,
+        "darkkhaki" => 0xbdb76b,
+        "darkmagenta" => 0x8b008b,
+        "darkolivegreen" => 0x556b2f,
+        "darkorange" => 0xff8c00,
+        "darkorchid" => 0x9932cc,
+        "darkred" => 0x8b0000,
+        "darksalmon" => 0xe9967a,
+        "darkseagreen" => 0x8fbc8f,
+        "darkslateblue" => 0x483d8b,
+        "darkslategray" | "darkslategrey" => 0x2f4f4f,
+        "darkturquoise" => 0x00ced1,
+        "darkviolet" => 0x9400d3,
+        "deeppink" => 0xff1493,
+        "deepskyblue" => 0x00bfff,
+        "dimgray" | "dimgrey" => 0x696969,
+        "dodgerblue" => 0x1e90ff,
+        "firebrick" => 0xb22222,
+        "floralwhite" => 0xfffaf0,
+        "forestgreen" => 0x228b22,
+        "fuchsia" | "magenta" => 0xff00ff,
+        "gainsboro" => 0xdcdcdc,
+        "ghostwhite" => 0xf8f8ff,
+        "gold" => 0xffd700,
+        "goldenrod" => 0xdaa520,
+        "gray" | "grey" => 0x808080,
+        "green" => 0x008000,
+        "greenyellow" => 0xadff2f,
+        "honeydew" => 0xf0fff0,
+        "hotpink" => 0xff69b4,
+        "indianred" => 0xcd5c5c,
+        "indigo" => 0x4b0082,
+        "ivory" => 0xfffff0,
+        "khaki" => 0xf0e68c,
+        "lavender" => 0xe6e6fa,
+        "lavenderblush" => 0xfff0f5,
+        "lawngreen" => 0x7cfc00,
+        "lemonchiffon" => 0xfffacd,
+        "lightblue" => 0xadd8e6,
+        "lightcoral" => 0xf08080,
+        "lightcyan" => 0xe0ffff,
+        "lightgoldenrodyellow" => 0xfafad2,
+        "lightgray" | "lightgrey" => 0xd3d3d3,
+        "lightgreen" => 0x90ee90,
+        "lightpink" => 0xffb6c1,
+        "lightsalmon" => 0xffa07a,
+        "lightseagreen" => 0x20b2aa,
+        "lightskyblue" => 0x87cefa,
+        "lightslategray" | "lightslategrey" => 0x778899,
+        "lightsteelblue" => 0xb0c4de,
+        "lightyellow" => 0xffffe0,
+        "lime" => 0x00ff00,
+        "limegreen" => 0x32cd32,
+        "linen" => 0xfaf0e6,
+        "maroon" => 0x800000,
+        "mediumaquamarine" => 0x66cdaa,
+        "mediumblue" => 0x0000cd,
+        "mediumorchid" => 0xba55d3,
+        "mediumpurple" => 0x9370db,
+        "mediumseagreen" => 0x3cb371,
+        "mediumslateblue" => 0x7b68ee,
+        "mediumspringgreen" => 0x00fa9a,
+        "mediumturquoise" => 0x48d1cc,
+        "mediumvioletred" => 0xc71585,
+        "midnightblue" => 0x191970,
+        "mintcream" => 0xf5fffa,
+        "mistyrose" => 0xffe4e1,
+        "moccasin" => 0xffe4b5,
+        "navajowhite" => 0xffdead,
+        "navy" => 0x000080,
+        "oldlace" => 0xfdf5e6,
+        "olive" => 0x808000,
+        "olivedrab" => 0x6b8e23,
+        "orange" => 0xffa500,
+        "orangered" => 0xff4500,
+        "orchid" => 0xda70d6,
+        "palegoldenrod" => 0xeee8aa,
+        "palegreen" => 0x98fb98,
+        "paleturquoise" => 0xafeeee,
+        "palevioletred" => 0xdb7093,
+        "papayawhip" => 0xffefd5,
+        "peachpuff" => 0xffdab9,
+        "peru" => 0xcd853f,
+        "pink" => 0xffc0cb,
+        "plum" => 0xdda0dd,
+        "powderblue" => 0xb0e0e6,
+        "purple" => 0x800080,
+        "rebeccapurple" => 0x663399,
+        "red" => 0xff0000,
+        "rosybrown" => 0xbc8f8f,
+        "royalblue" => 0x4169e1,
+        "saddlebrown" => 0x8b4513,
+        "salmon" => 0xfa8072,
+        "sandybrown" => 0xf4a460,
+        "seagreen" => 0x2e8b57,
+        "seashell" => 0xfff5ee,
+        "sienna" => 0xa0522d,
+        "silver" => 0xc0c0c0,
+        "skyblue" => 0x87ceeb,
+        "slateblue" => 0x6a5acd,
+        "slategray" | "slategrey" => 0x708090,
+        "snow" => 0xfffafa,
+        "springgreen" => 0x00ff7f,
+        "steelblue" => 0x4682b4,
+        "tan" => 0xd2b48c,
+        "teal" => 0x008080,
+        "thistle" => 0xd8bfd8,
+        "tomato" => 0xff6347,
+        "turquoise" => 0x40e0d0,
+        "violet" => 0xee82ee,
+        "wheat" => 0xf5deb3,
+        "white" => 0xffffff,
+        "whitesmoke" => 0xf5f5f5,
+        "yellow" => 0xffff00,
+        "yellowgreen" => 0x9acd32,
+        _ => return None,
+    };
+    Some(Color::rgb_u8((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_color(value: &str, expected: [f32; 4]) {
+        let color = parse_color(value).unwrap_or_else(|error| panic!("{}", error));
+        let rgba = color.as_rgba_f32();
+        for (channel, expected) in rgba.iter().zip(expected) {
+            assert!((channel - expected).abs() < 0.01, "{} : {:?}, attendu {:?}", value, rgba, expected);
+        }
+    }
+
+    #[test]
+    fn parses_hex_forms() {
+        assert_eq!(parse_color("#ff8000"), Ok(Color::rgb_u8(255, 128, 0)));
+        assert_eq!(parse_color("#F80"), Ok(Color::rgb_u8(255, 136, 0)));
+        assert_eq!(parse_color("#ff800080"), Ok(Color::rgba_u8(255, 128, 0, 128)));
+        assert_eq!(parse_color("#f808"), Ok(Color::rgba_u8(255, 136, 0, 136)));
+        for invalid in ["#ff80", "#ff800", "#gg0000", "#"] {
+            assert!(parse_color(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn parses_rgb_functions() {
+        assert_color("rgb(255, 0, 0)", [1.0, 0.0, 0.0, 1.0]);
+        assert_color("rgba(0, 255, 0, 0.5)", [0.0, 1.0, 0.0, 0.5]);
+        assert_color("rgb(0 0 255 / 25%)", [0.0, 0.0, 1.0, 0.25]);
+        assert_color("rgb(100%, 50%, 0%)", [1.0, 0.5, 0.0, 1.0]);
+        assert_color("RGB(300, -10, 0)", [1.0, 0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn parses_hsl_functions() {
+        assert_color("hsl(0, 100%, 50%)", [1.0, 0.0, 0.0, 1.0]);
+        assert_color("hsl(120deg 100% 25%)", [0.0, 0.5, 0.0, 1.0]);
+        assert_color("hsla(240, 100%, 50%, 0.5)", [0.0, 0.0, 1.0, 0.5]);
+        assert_color("hsl(-120 100% 50% / 50%)", [0.0, 0.0, 1.0, 0.5]);
+        assert!(parse_color("hsl(0, 100, 50)").is_err());
+    }
+
+    #[test]
+    fn parses_named_colors() {
+        assert_eq!(parse_color("rebeccapurple"), Ok(Color::rgb_u8(0x66, 0x33, 0x99)));
+        assert_eq!(parse_color(" DarkGrey "), Ok(Color::rgb_u8(0xa9, 0xa9, 0xa9)));
+        assert_eq!(parse_color("transparent"), Ok(Color::NONE));
+    }
+
+    #[test]
+    fn describes_invalid_colors() {
+        assert_eq!(parse_color("blurple"), Err("couleur inconnue `blurple`".to_string()));
+        assert_eq!(parse_color("cmyk(0, 0, 0, 0)"), Err("fonction de couleur inconnue `cmyk` dans `cmyk(0, 0, 0, 0)`".to_string()));
+        assert_eq!(parse_color("rgb(0, 0, 0"), Err("parenthèse fermante manquante dans `rgb(0, 0, 0`".to_string()));
+        assert!(parse_color("rgb(0, 0)").is_err());
+        assert!(parse_color("rgb(0, 0, 0, 0, 0)").is_err());
+        assert!(parse_color("rgb(a, b, c)").is_err());
+    }
+
+    #[test]
+    fn parses_theme_tokens() {
+        assert_eq!(ColorValue::parse("$primary"), Ok(ColorValue::Token("primary".to_string())));
+        assert_eq!(ColorValue::parse("var(--surface-alt)"), Ok(ColorValue::Token("surface-alt".to_string())));
+        assert_eq!(ColorValue::parse("$colors.accent_2"), Ok(ColorValue::Token("colors.accent_2".to_string())));
+        assert_eq!(ColorValue::parse("red"), Ok(ColorValue::Literal(Color::rgb_u8(255, 0, 0))));
+        assert!(ColorValue::parse("$").is_err());
+        assert!(ColorValue::parse("$pri mary").is_err());
+        assert!(ColorValue::parse("var(--)").is_err());
+    }
+
+    #[test]
+    fn resolves_tokens_against_the_theme() {
+        let tokens = ColorTokens(HashMap::from([("primary".to_string(), Color::rgb_u8(20, 120, 200))]));
+
+        assert_eq!(ColorValue::Token("primary".to_string()).resolve(Some(&tokens)), Some(Color::rgb_u8(20, 120, 200)));
+        assert_eq!(ColorValue::Token("missing".to_string()).resolve(Some(&tokens)), None);
+        assert_eq!(ColorValue::Token("primary".to_string()).resolve(None), None);
+        assert_eq!(ColorValue::Literal(Color::BLACK).resolve(None), Some(Color::BLACK));
+    }
+}
@@ -8,10 +8,12 @@ use bevy::{
 use serde::{Serialize, Deserialize};
 use std::collections::HashMap;
 
+use crate::ui::theme::ThemeDefinition;
+
 // ==================== Composants de base ====================
 
 /// Définition des composants UI supportés dans notre DSL
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum UIComponent {
     Window(WindowProps),
     Button(ButtonProps),
@@ -27,11 +29,224 @@ pub enum UIComponent {
     Stack(StackProps),
     Row(RowProps),
     Column(ColumnProps),
+    /// Nœud de la scène d'un `viewport3d` (`cube`, `point_light`, `group`...)
+    Node3D(Node3DProps),
+}
+
+impl UIComponent {
+    /// Identifiant DSL du composant
+    pub fn id(&self) -> &str {
+        match self {
+            UIComponent::Window(props) => &props.id,
+            UIComponent::Button(props) => &props.id,
+            UIComponent::Text(props) => &props.id,
+            UIComponent::Image(props) => &props.id,
+            UIComponent::Canvas(props) => &props.id,
+            UIComponent::SVG(props) => &props.id,
+            UIComponent::ScrollView(props) => &props.id,
+            UIComponent::List(props) => &props.id,
+            UIComponent::Grid(props) => &props.id,
+            UIComponent::Input(props) => &props.id,
+            UIComponent::Viewport3D(props) => &props.id,
+            UIComponent::Stack(props) => &props.id,
+            UIComponent::Row(props) => &props.id,
+            UIComponent::Column(props) => &props.id,
+            UIComponent::Node3D(props) => &props.id,
+        }
+    }
+
+    /// Propriété `style:` du composant
+    pub fn style(&self) -> Option<&HashMap<String, String>> {
+        match self {
+            UIComponent::Window(props) => props.style.as_ref(),
+            UIComponent::Button(props) => props.style.as_ref(),
+            UIComponent::Text(props) => props.style.as_ref(),
+            UIComponent::Image(props) => props.style.as_ref(),
+            UIComponent::Canvas(props) => props.style.as_ref(),
+            UIComponent::SVG(props) => props.style.as_ref(),
+            UIComponent::ScrollView(props) => props.style.as_ref(),
+            UIComponent::List(props) => props.style.as_ref(),
+            UIComponent::Grid(props) => props.style.as_ref(),
+            UIComponent::Input(props) => props.style.as_ref(),
+            UIComponent::Viewport3D(props) => props.style.as_ref(),
+            UIComponent::Stack(props) => props.style.as_ref(),
+            UIComponent::Row(props) => props.style.as_ref(),
+            UIComponent::Column(props) => props.style.as_ref(),
+            UIComponent::Node3D(_) => None,
+        }
+    }
+
+    /// Classes du composant (propriété `class:`)
+    pub fn classes(&self) -> &[String] {
+        match self {
+            UIComponent::Window(props) => &props.classes,
+            UIComponent::Button(props) => &props.classes,
+            UIComponent::Text(props) => &props.classes,
+            UIComponent::Image(props) => &props.classes,
+            UIComponent::Canvas(props) => &props.classes,
+            UIComponent::SVG(props) => &props.classes,
+            UIComponent::ScrollView(props) => &props.classes,
+            UIComponent::List(props) => &props.classes,
+            UIComponent::Grid(props) => &props.classes,
+            UIComponent::Input(props) => &props.classes,
+            UIComponent::Viewport3D(props) => &props.classes,
+            UIComponent::Stack(props) => &props.classes,
+            UIComponent::Row(props) => &props.classes,
+            UIComponent::Column(props) => &props.classes,
+            UIComponent::Node3D(_) => &[],
+        }
+    }
+
+    /// Classes modifiables, pour les composants d'UI (les nœuds 3D n'en ont pas)
+    pub fn classes_mut(&mut self) -> Option<&mut Vec<String>> {
+        match self {
+            UIComponent::Window(props) => Some(&mut props.classes),
+            UIComponent::Button(props) => Some(&mut props.classes),
+            UIComponent::Text(props) => Some(&mut props.classes),
+            UIComponent::Image(props) => Some(&mut props.classes),
+            UIComponent::Canvas(props) => Some(&mut props.classes),
+            UIComponent::SVG(props) => Some(&mut props.classes),
+            UIComponent::ScrollView(props) => Some(&mut props.classes),
+            UIComponent::List(props) => Some(&mut props.classes),
+            UIComponent::Grid(props) => Some(&mut props.classes),
+            UIComponent::Input(props) => Some(&mut props.classes),
+            UIComponent::Viewport3D(props) => Some(&mut props.classes),
+            UIComponent::Stack(props) => Some(&mut props.classes),
+            UIComponent::Row(props) => Some(&mut props.classes),
+            UIComponent::Column(props) => Some(&mut props.classes),
+            UIComponent::Node3D(_) => None,
+        }
+    }
+
+    /// Le composant est-il désactivé ?
+    pub fn disabled(&self) -> bool {
+        match self {
+            UIComponent::Button(props) => props.disabled.unwrap_or(false),
+            UIComponent::Input(props) => props.disabled.unwrap_or(false),
+            _ => false,
+        }
+    }
+
+    /// Nom du type tel qu'écrit dans le DSL (ex: "viewport3d")
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            UIComponent::Window(_) => "window",
+            UIComponent::Button(_) => "button",
+            UIComponent::Text(_) => "text",
+            UIComponent::Image(_) => "image",
+            UIComponent::Canvas(_) => "canvas",
+            UIComponent::SVG(_) => "svg",
+            UIComponent::ScrollView(_) => "scrollview",
+            UIComponent::List(_) => "list",
+            UIComponent::Grid(_) => "grid",
+            UIComponent::Input(_) => "input",
+            UIComponent::Viewport3D(_) => "viewport3d",
+            UIComponent::Stack(_) => "stack",
+            UIComponent::Row(_) => "row",
+            UIComponent::Column(_) => "column",
+            UIComponent::Node3D(props) => props.kind.type_name(),
+        }
+    }
+
+    /// Enfants directs du composant (vide pour les composants feuilles)
+    pub fn children(&self) -> &[UIComponent] {
+        match self {
+            UIComponent::Window(props) => &props.children,
+            UIComponent::ScrollView(props) => &props.children,
+            UIComponent::List(props) => &props.items,
+            UIComponent::Grid(props) => &props.items,
+            UIComponent::Stack(props) => &props.children,
+            UIComponent::Row(props) => &props.children,
+            UIComponent::Column(props) => &props.children,
+            UIComponent::Viewport3D(props) => &props.children,
+            UIComponent::Node3D(props) => &props.children,
+            _ => &[],
+        }
+    }
+
+    /// Enfants directs modifiables, pour les composants qui en acceptent
+    pub fn children_mut(&mut self) -> Option<&mut Vec<UIComponent>> {
+        match self {
+            UIComponent::Window(props) => Some(&mut props.children),
+            UIComponent::ScrollView(props) => Some(&mut props.children),
+            UIComponent::List(props) => Some(&mut props.items),
+            UIComponent::Grid(props) => Some(&mut props.items),
+            UIComponent::Stack(props) => Some(&mut props.children),
+            UIComponent::Row(props) => Some(&mut props.children),
+            UIComponent::Column(props) => Some(&mut props.children),
+            UIComponent::Viewport3D(props) => Some(&mut props.children),
+            UIComponent::Node3D(props) => Some(&mut props.children),
+            _ => None,
+        }
+    }
+
+    /// IDs du composant et de tous ses descendants
+    pub fn subtree_ids(&self) -> Vec<String> {
+        let mut ids = vec![self.id().to_string()];
+        for child in self.children() {
+            ids.extend(child.subtree_ids());
+        }
+        ids
+    }
+
+    /// Copie du composant sans ses enfants, utilisée pour comparer les seules propriétés
+    pub fn without_children(&self) -> UIComponent {
+        let mut shell = self.clone();
+        match &mut shell {
+            UIComponent::Window(props) => props.children.clear(),
+            UIComponent::ScrollView(props) => props.children.clear(),
+            UIComponent::List(props) => props.items.clear(),
+            UIComponent::Grid(props) => props.items.clear(),
+            UIComponent::Stack(props) => props.children.clear(),
+            UIComponent::Row(props) => props.children.clear(),
+            UIComponent::Column(props) => props.children.clear(),
+            UIComponent::Viewport3D(props) => props.children.clear(),
+            UIComponent::Node3D(props) => props.children.clear(),
+            _ => {}
+        }
+        shell
+    }
+}
+
+/// Recherche un composant par ID dans un arbre
+pub fn find_component<'a>(tree: &'a [UIComponent], id: &str) -> Option<&'a UIComponent> {
+    tree.iter().find_map(|component| {
+        if component.id() == id {
+            Some(component)
+        } else {
+            find_component(component.children(), id)
+        }
+    })
+}
+
+/// Recherche un composant par ID dans un arbre, pour le modifier
+pub fn find_component_mut<'a>(tree: &'a mut [UIComponent], id: &str) -> Option<&'a mut UIComponent> {
+    for component in tree.iter_mut() {
+        if component.id() == id {
+            return Some(component);
+        }
+        if let Some(children) = component.children_mut() {
+            if let Some(found) = find_component_mut(children, id) {
+                return Some(found);
+            }
+        }
+    }
+    None
+}
+
+/// Retire un composant d'un arbre et le retourne
+pub fn remove_component(tree: &mut Vec<UIComponent>, id: &str) -> Option<UIComponent> {
+    if let Some(index) = tree.iter().position(|component| component.id() == id) {
+        return Some(tree.remove(index));
+    }
+    tree.iter_mut()
+        .filter_map(|component| component.children_mut())
+        .find_map(|children| remove_component(children, id))
 }
 
 // ==================== Propriétés des composants ====================
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct WindowProps {
     pub id: String,
     pub title: String,
@@ -42,9 +257,11 @@ pub struct WindowProps {
     pub resizable: Option<bool>,
     pub draggable: Option<bool>,
     pub children: Vec<UIComponent>,
+    pub style: Option<HashMap<String, String>>,
+    pub classes: Vec<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ButtonProps {
     pub id: String,
     pub text: String,
@@ -52,10 +269,12 @@ pub struct ButtonProps {
     pub height: Option<f32>,
     pub on_click: Option<String>,
     pub icon: Option<String>,
+    pub disabled: Option<bool>,
     pub style: Option<HashMap<String, String>>,
+    pub classes: Vec<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TextProps {
     pub id: String,
     pub content: String,
@@ -63,60 +282,86 @@ pub struct TextProps {
     pub color: Option<String>,
     pub align: Option<String>,
     pub font: Option<String>,
+    pub style: Option<HashMap<String, String>>,
+    pub classes: Vec<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ImageProps {
     pub id: String,
     pub source: String,
     pub width: Option<f32>,
     pub height: Option<f32>,
     pub scale: Option<f32>,
+    pub style: Option<HashMap<String, String>>,
+    pub classes: Vec<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct CanvasProps {
     pub id: String,
     pub width: f32,
     pub height: f32,
     pub on_draw: Option<String>,
     pub on_click: Option<String>,
+    /// Handlers de pointeur, appelés avec la position locale au canevas
+    pub on_pointer_down: Option<String>,
+    pub on_pointer_move: Option<String>,
+    pub on_pointer_up: Option<String>,
+    pub on_wheel: Option<String>,
+    pub on_double_click: Option<String>,
+    pub style: Option<HashMap<String, String>>,
+    pub classes: Vec<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SVGProps {
     pub id: String,
-    pub source: String,
+    /// Fichier `.svg`, relatif aux assets
+    pub source: Option<String>,
+    /// Balisage SVG en ligne, prioritaire sur `source`
+    pub markup: Option<String>,
+    /// Couleurs imposées aux remplissages et aux contours (teinte d'icône)
+    pub fill: Option<String>,
+    pub stroke: Option<String>,
     pub width: Option<f32>,
     pub height: Option<f32>,
+    pub style: Option<HashMap<String, String>>,
+    pub classes: Vec<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ScrollViewProps {
     pub id: String,
     pub width: f32,
     pub height: f32,
     pub children: Vec<UIComponent>,
+    pub style: Option<HashMap<String, String>>,
+    pub classes: Vec<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ListProps {
     pub id: String,
     pub items: Vec<UIComponent>,
     pub direction: Option<String>, // "vertical" or "horizontal"
     pub spacing: Option<f32>,
+    pub style: Option<HashMap<String, String>>,
+    pub classes: Vec<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct GridProps {
     pub id: String,
     pub columns: usize,
     pub rows: Option<usize>,
     pub items: Vec<UIComponent>,
     pub spacing: Option<f32>,
+    pub style: Option<HashMap<String, String>>,
+    pub classes: Vec<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct InputProps {
     pub id: String,
     pub placeholder: Option<String>,
@@ -124,43 +369,121 @@ pub struct InputProps {
     pub width: Option<f32>,
     pub on_change: Option<String>,
     pub on_submit: Option<String>,
+    pub disabled: Option<bool>,
+    pub style: Option<HashMap<String, String>>,
+    pub classes: Vec<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Viewport3DProps {
     pub id: String,
     pub width: f32,
     pub height: f32,
+    /// Scène glTF des assets (`"models/robot.glb#Scene0"`)
     pub scene: Option<String>,
+    /// Cadre la caméra sur la scène une fois chargée
+    pub auto_frame: Option<bool>,
+    pub on_load: Option<String>,
+    pub on_error: Option<String>,
     pub camera: Option<CameraProps>,
+    /// Nœuds 3D déclarés dans le bloc du viewport
+    pub children: Vec<UIComponent>,
+    pub style: Option<HashMap<String, String>>,
+    pub classes: Vec<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct CameraProps {
     pub position: [f32; 3],
     pub target: [f32; 3],
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Node3DProps {
+    pub id: String,
+    pub kind: Node3DKind,
+    pub transform: Transform3DProps,
+    pub material: MaterialProps,
+    pub children: Vec<UIComponent>,
+}
+
+/// Nature d'un nœud 3D et ses propriétés propres
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Node3DKind {
+    Cube { size: f32 },
+    Sphere { radius: f32 },
+    Plane { size: f32 },
+    /// Maillage des assets (`"models/rock.glb#Mesh0/Primitive0"`)
+    Mesh { source: String },
+    PointLight { intensity: f32, range: f32 },
+    DirectionalLight { illuminance: f32 },
+    Group,
+}
+
+/// Types DSL des nœuds 3D
+pub const NODE3D_TYPES: [&str; 7] = ["cube", "sphere", "plane", "mesh", "point_light", "directional_light", "group"];
+
+impl Node3DKind {
+    /// Nom du type tel qu'écrit dans le DSL
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Node3DKind::Cube { .. } => "cube",
+            Node3DKind::Sphere { .. } => "sphere",
+            Node3DKind::Plane { .. } => "plane",
+            Node3DKind::Mesh { .. } => "mesh",
+            Node3DKind::PointLight { .. } => "point_light",
+            Node3DKind::DirectionalLight { .. } => "directional_light",
+            Node3DKind::Group => "group",
+        }
+    }
+}
+
+/// Position, rotation (angles d'Euler XYZ en degrés) et échelle d'un nœud 3D
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Transform3DProps {
+    pub position: [f32; 3],
+    pub rotation: [f32; 3],
+    pub scale: [f32; 3],
+}
+
+/// Matériau d'un nœud 3D ; la couleur est aussi celle des lumières.
+///
+/// Dans une mise à jour `set_material`, les champs absents restent inchangés.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MaterialProps {
+    pub color: Option<String>,
+    pub metallic: Option<f32>,
+    pub roughness: Option<f32>,
+    /// Image des assets appliquée comme couleur de base
+    pub texture: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct StackProps {
     pub id: String,
     pub children: Vec<UIComponent>,
+    pub style: Option<HashMap<String, String>>,
+    pub classes: Vec<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RowProps {
     pub id: String,
     pub children: Vec<UIComponent>,
     pub spacing: Option<f32>,
     pub align: Option<String>, // "start", "center", "end", "spaceBetween", "spaceAround"
+    pub style: Option<HashMap<String, String>>,
+    pub classes: Vec<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ColumnProps {
     pub id: String,
     pub children: Vec<UIComponent>,
     pub spacing: Option<f32>,
     pub align: Option<String>, // "start", "center", "end", "spaceBetween", "spaceAround"
+    pub style: Option<HashMap<String, String>>,
+    pub classes: Vec<String>,
 }
 
 // ==================== Composants Bevy ====================
@@ -201,6 +524,12 @@ pub enum ResizeEdge {
     TopLeft,
 }
 
+/// Nœud 3D créé par le DSL, dans la scène d'un viewport
+#[derive(Component)]
+pub struct SceneNode {
+    pub id: String,
+}
+
 /// Stockage des callbacks pour les événements
 #[derive(Component)]
 pub struct EventCallback {
@@ -216,7 +545,6 @@ pub fn setup_ui_systems(app: &mut App) {
             window_drag_system,
             window_resize_system,
             button_click_system,
-            update_canvas_system,
             handle_input_changes,
         ));
 }
@@ -303,13 +631,6 @@ fn button_click_system(
     }
 }
 
-// Système pour mettre à jour les canvas à partir du code Ruby
-fn update_canvas_system(
-    // Implémentation du système de canvas
-    // ...
-) {
-}
-
 // Système pour gérer les changements dans les champs de texte
 fn handle_input_changes(
     // Implémentation du système de gestion des inputs
@@ -323,7 +644,8 @@ fn handle_input_changes(
 #[derive(Event)]
 pub struct RubyCallbackEvent {
     pub callback: String,
-    pub arguments: HashMap<String, String>,
+    /// Arguments typés, passés au handler sous forme de hash Ruby
+    pub arguments: serde_json::Value,
 }
 
 /// Événement pour mettre à jour un composant UI
@@ -343,4 +665,39 @@ pub enum UIComponentUpdate {
     SetVisible(bool),
     SetStyle(HashMap<String, String>),
     Replace(UIComponent),
-}
\ No newline at end of file
+    /// Ajoute un enfant au composant, à la position donnée ou à la fin
+    InsertChild { component: UIComponent, index: Option<usize> },
+    /// Retire le composant et ses descendants
+    Remove,
+    /// Ouvre une nouvelle fenêtre (l'ID de l'événement est celui de la fenêtre)
+    OpenWindow(UIComponent),
+    /// Ferme une fenêtre
+    CloseWindow,
+    /// Donne le focus au composant
+    Focus,
+    /// Redessine un canevas (rappelle son `on_draw`)
+    Invalidate,
+    /// Remplace les classes du composant
+    SetClasses(Vec<String>),
+    SetDisabled(bool),
+    /// Active un thème ; l'ID de l'événement est vide, la mise à jour vise toute l'UI
+    SetTheme(String),
+    /// Ajoute ou remplace un thème (ID vide, comme `SetTheme`)
+    DefineTheme(ThemeDefinition),
+    /// Déplace, tourne ou redimensionne un nœud 3D ; les champs absents restent inchangés
+    SetTransform { position: Option<[f32; 3]>, rotation: Option<[f32; 3]>, scale: Option<[f32; 3]> },
+    /// Modifie le matériau d'un nœud 3D, ou la couleur d'une lumière
+    SetMaterial(MaterialProps),
+}
+
+/// Composant ayant le focus (au plus un à la fois)
+#[derive(Component)]
+pub struct Focused;
+
+/// Classes du composant (propriété `class:`), visées par les feuilles de style
+#[derive(Component, Debug, Clone, Default)]
+pub struct StyleClasses(pub Vec<String>);
+
+/// Composant désactivé (`disabled: true`) : il ne déclenche plus ses handlers
+#[derive(Component)]
+pub struct Disabled;
\ No newline at end of file
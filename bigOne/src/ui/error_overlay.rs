@@ -0,0 +1,139 @@
+// src/ui/error_overlay.rs
+use bevy::prelude::*;
+
+use crate::dsl::parser::ScriptError;
+
+/// Marqueur de la bannière d'erreur affichée par-dessus l'UI
+#[derive(Component)]
+pub struct ErrorOverlay;
+
+/// Marqueur du bouton de fermeture de la bannière d'erreur
+#[derive(Component)]
+pub struct DismissErrorOverlay;
+
+/// Affiche une erreur de script par-dessus l'UI existante, qui reste intacte
+pub fn show_error_overlay(
+    commands: &mut Commands,
+    asset_server: &Res<AssetServer>,
+    overlays: &Query<Entity, With<ErrorOverlay>>,
+    script_path: &str,
+    error: &ScriptError,
+) {
+    // Une seule bannière à la fois
+    clear_error_overlay(commands, overlays);
+
+    // L'erreur peut provenir d'un script importé par le script principal
+    let file = error.file.as_deref().unwrap_or(script_path);
+    let location = match error.line {
+        Some(line) => format!("{}, ligne {}", file, line),
+        None => file.to_string(),
+    };
+
+    let overlay_entity = commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    left: Val::Px(20.0),
+                    right: Val::Px(20.0),
+                    top: Val::Px(20.0),
+                    flex_direction: FlexDirection::Row,
+                    justify_content: JustifyContent::SpaceBetween,
+                    align_items: AlignItems::FlexStart,
+                    padding: UiRect::all(Val::Px(10.0)),
+                    border: UiRect::all(Val::Px(1.0)),
+                    ..default()
+                },
+                background_color: Color::rgba(0.35, 0.05, 0.05, 0.95).into(),
+                border_color: Color::rgb(0.9, 0.3, 0.3).into(),
+                z_index: ZIndex::Global(i32::MAX),
+                ..default()
+            },
+            ErrorOverlay,
+        ))
+        .id();
+
+    // Une violation du sandbox nomme l'API refusée plutôt que la classe Ruby
+    let title = match &error.violation {
+        Some(api) => format!("Accès refusé par le sandbox : {}\n", api),
+        None => format!("{}\n", error.class),
+    };
+
+    // Classe et message de l'exception, puis emplacement dans le script
+    let message_entity = commands
+        .spawn(TextBundle::from_sections([
+            TextSection::new(
+                title,
+                TextStyle {
+                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                    font_size: 16.0,
+                    color: Color::rgb(1.0, 0.7, 0.7),
+                },
+            ),
+            TextSection::new(
+                format!("{}\n", error.message),
+                TextStyle {
+                    font: asset_server.load("fonts/FiraSans-Regular.ttf"),
+                    font_size: 14.0,
+                    color: Color::WHITE,
+                },
+            ),
+            TextSection::new(
+                location,
+                TextStyle {
+                    font: asset_server.load("fonts/FiraSans-Regular.ttf"),
+                    font_size: 12.0,
+                    color: Color::rgb(0.8, 0.8, 0.8),
+                },
+            ),
+        ]))
+        .id();
+
+    // Bouton de fermeture
+    let dismiss_entity = commands
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    width: Val::Px(20.0),
+                    height: Val::Px(20.0),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: Color::rgb(0.6, 0.15, 0.15).into(),
+                ..default()
+            },
+            DismissErrorOverlay,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "X",
+                TextStyle {
+                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                    font_size: 14.0,
+                    color: Color::WHITE,
+                },
+            ));
+        })
+        .id();
+
+    commands.entity(overlay_entity).push_children(&[message_entity, dismiss_entity]);
+}
+
+/// Retire la bannière d'erreur si elle est affichée
+pub fn clear_error_overlay(commands: &mut Commands, overlays: &Query<Entity, With<ErrorOverlay>>) {
+    for entity in overlays.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+/// Système pour fermer la bannière d'erreur via son bouton
+pub fn dismiss_error_overlay_system(
+    mut commands: Commands,
+    buttons: Query<&Interaction, (Changed<Interaction>, With<DismissErrorOverlay>)>,
+    overlays: Query<Entity, With<ErrorOverlay>>,
+) {
+    if buttons.iter().any(|interaction| *interaction == Interaction::Pressed) {
+        clear_error_overlay(&mut commands, &overlays);
+    }
+}
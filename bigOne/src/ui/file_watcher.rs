@@ -0,0 +1,114 @@
+// src/ui/file_watcher.rs
+use bevy::prelude::*;
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use winit::event_loop::EventLoopProxy;
+
+/// Délai de stabilisation : les rafales d'écritures d'un éditeur (fichier temporaire,
+/// renommage, écriture) ne déclenchent qu'un seul rechargement
+pub const DEFAULT_DEBOUNCE: Duration = Duration::from_millis(150);
+
+/// Extensions des assets rechargés individuellement
+const ASSET_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "bmp", "gif", "ttf", "otf", "svg"];
+
+/// Un asset utilisé par l'UI a changé sur le disque
+#[derive(Event, Debug, Clone)]
+pub struct AssetChangedEvent {
+    /// Chemin relatif au répertoire des assets (ex: "icons/close.svg")
+    pub path: String,
+}
+
+/// Surveillance des répertoires de scripts et d'assets.
+///
+/// Les notifications arrivent sur le thread de `notify`, qui réveille la boucle
+/// d'événements : aucun accès au disque n'est fait tant que rien n'a changé.
+#[derive(Resource)]
+pub struct FileWatcher {
+    // Le watcher s'arrête quand il est libéré
+    _watcher: Mutex<RecommendedWatcher>,
+    events: Mutex<Receiver<PathBuf>>,
+    /// Fichiers modifiés => instant de la dernière notification reçue
+    pending: HashMap<PathBuf, Instant>,
+    debounce: Duration,
+}
+
+impl FileWatcher {
+    /// Surveille récursivement les répertoires donnés ; `wake` réveille la boucle
+    /// d'événements de winit à chaque notification
+    pub fn new(dirs: &[PathBuf], debounce: Duration, wake: Option<EventLoopProxy<()>>) -> notify::Result<Self> {
+        let (sender, events) = channel();
+        let wake = wake.map(Mutex::new);
+
+        let mut watcher = notify::recommended_watcher(move |result: notify::Result<notify::Event>| {
+            let Ok(event) = result else {
+                return;
+            };
+            // Les simples lectures ne changent rien
+            if matches!(event.kind, EventKind::Access(_)) {
+                return;
+            }
+            for path in event.paths {
+                let _ = sender.send(path);
+            }
+            if let Some(proxy) = &wake {
+                if let Ok(proxy) = proxy.lock() {
+                    let _ = proxy.send_event(());
+                }
+            }
+        })?;
+
+        for dir in dirs {
+            watcher.watch(dir, RecursiveMode::Recursive)?;
+        }
+
+        Ok(Self {
+            _watcher: Mutex::new(watcher),
+            events: Mutex::new(events),
+            pending: HashMap::new(),
+            debounce,
+        })
+    }
+
+    /// Retourne les fichiers dont les modifications sont stables depuis le délai de
+    /// stabilisation ; les autres restent en attente
+    pub fn poll(&mut self) -> Vec<PathBuf> {
+        let now = Instant::now();
+        if let Ok(events) = self.events.lock() {
+            for path in events.try_iter() {
+                self.pending.insert(path, now);
+            }
+        }
+
+        let debounce = self.debounce;
+        let ready: Vec<PathBuf> = self
+            .pending
+            .iter()
+            .filter(|(_, last_event)| now.duration_since(**last_event) >= debounce)
+            .map(|(path, _)| path.clone())
+            .collect();
+
+        for path in &ready {
+            self.pending.remove(path);
+        }
+        ready
+    }
+
+    /// Des modifications attendent la fin du délai de stabilisation
+    pub fn has_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+}
+
+/// Chemin d'un asset rechargeable, relatif au répertoire des assets
+pub fn asset_path(path: &Path, asset_dir: &Path) -> Option<String> {
+    let extension = path.extension()?.to_str()?.to_lowercase();
+    if !ASSET_EXTENSIONS.contains(&extension.as_str()) {
+        return None;
+    }
+    let relative = path.strip_prefix(asset_dir).ok()?;
+    Some(relative.to_string_lossy().replace('\\', "/"))
+}
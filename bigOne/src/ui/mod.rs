@@ -1,3 +1,16 @@
 pub mod components;
 pub mod builder;
-pub mod systems;
\ No newline at end of file
+pub mod systems;
+pub mod error_overlay;
+pub mod file_watcher;
+pub mod registry;
+pub mod updates;
+pub mod style;
+pub mod color;
+pub mod stylesheet;
+pub mod theme;
+pub mod svg;
+pub mod canvas;
+pub mod pointer;
+pub mod viewport3d;
+
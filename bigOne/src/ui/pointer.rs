@@ -0,0 +1,222 @@
+// src/ui/pointer.rs
+//! Événements de pointeur des canevas.
+//!
+//! ```ruby
+//! canvas(id: "sketch", width: 400, height: 300,
+//!        on_pointer_down: ->(e) { start_stroke(e["x"], e["y"]) },
+//!        on_pointer_move: ->(e) { extend_stroke(e["x"], e["y"]) if e["buttons"].include?("left") },
+//!        on_wheel: ->(e) { zoom(e["delta_y"]) })
+//! ```
+//!
+//! Chaque événement porte la position en pixels logiques relatifs au coin supérieur
+//! gauche du canevas (`x`, `y`), les boutons enfoncés (`buttons`) et les touches de
+//! modification (`modifiers`). Un appui sur le canevas capture le pointeur : les
+//! déplacements et le relâchement lui sont transmis même hors de ses limites,
+//! jusqu'au relâchement du dernier bouton.
+use bevy::{
+    prelude::*,
+    input::mouse::{MouseButtonInput, MouseScrollUnit, MouseWheel},
+    ui::RelativeCursorPosition,
+};
+
+use crate::ui::components::{CanvasProps, Disabled, RubyCallbackEvent, UIElement};
+
+/// Délai maximal entre les deux appuis d'un double-clic, en secondes
+const DOUBLE_CLICK_DELAY: f64 = 0.5;
+/// Déplacement maximal entre les deux appuis d'un double-clic, en pixels logiques
+const DOUBLE_CLICK_DISTANCE: f32 = 4.0;
+
+/// Handlers de pointeur d'un canevas et état de la capture
+#[derive(Component, Debug, Default)]
+pub struct PointerHandlers {
+    on_pointer_down: Option<String>,
+    on_pointer_move: Option<String>,
+    on_pointer_up: Option<String>,
+    on_wheel: Option<String>,
+    on_double_click: Option<String>,
+    on_click: Option<String>,
+    /// Boutons appuyés sur le canevas, qui capturent le pointeur
+    captured: Vec<MouseButton>,
+    /// Dernière position transmise à `on_pointer_move`
+    last_position: Option<Vec2>,
+    /// Dernier appui : bouton, instant et position, pour détecter le double-clic
+    last_down: Option<(MouseButton, f64, Vec2)>,
+}
+
+impl PointerHandlers {
+    pub fn new(props: &CanvasProps) -> Self {
+        Self {
+            on_pointer_down: props.on_pointer_down.clone(),
+            on_pointer_move: props.on_pointer_move.clone(),
+            on_pointer_up: props.on_pointer_up.clone(),
+            on_wheel: props.on_wheel.clone(),
+            on_double_click: props.on_double_click.clone(),
+            on_click: props.on_click.clone(),
+            ..default()
+        }
+    }
+
+    /// Aucun handler : le canevas ignore le pointeur
+    pub fn is_empty(&self) -> bool {
+        [&self.on_pointer_down, &self.on_pointer_move, &self.on_pointer_up, &self.on_wheel, &self.on_double_click, &self.on_click]
+            .iter()
+            .all(|handler| handler.is_none())
+    }
+
+    /// Enregistre un appui ; vrai s'il complète un double-clic, qui ne compte
+    /// alors plus comme premier appui du suivant
+    fn is_double_click(&mut self, button: MouseButton, now: f64, position: Vec2) -> bool {
+        let double = self.last_down.map_or(false, |(previous, at, previous_position)| {
+            previous == button && now - at <= DOUBLE_CLICK_DELAY && previous_position.distance(position) <= DOUBLE_CLICK_DISTANCE
+        });
+        self.last_down = if double { None } else { Some((button, now, position)) };
+        double
+    }
+}
+
+/// Transmet à Ruby les appuis, déplacements, relâchements et défilements
+/// du pointeur sur les canevas.
+///
+/// Le survol vient de bevy_ui : un canevas recouvert par un autre nœud, découpé
+/// par son parent ou masqué ne reçoit pas le pointeur.
+pub fn canvas_pointer_system(
+    mut button_events: EventReader<MouseButtonInput>,
+    mut wheel_events: EventReader<MouseWheel>,
+    mouse: Res<Input<MouseButton>>,
+    keys: Res<Input<KeyCode>>,
+    time: Res<Time>,
+    mut canvases: Query<(&UIElement, &Node, &Interaction, &RelativeCursorPosition, &mut PointerHandlers), Without<Disabled>>,
+    mut ruby_callback_events: EventWriter<RubyCallbackEvent>,
+) {
+    let button_events: Vec<&MouseButtonInput> = button_events.iter().collect();
+    let wheel_events: Vec<&MouseWheel> = wheel_events.iter().collect();
+    let modifiers = modifiers(&keys);
+    let now = time.elapsed_seconds_f64();
+
+    for (element, node, interaction, cursor, mut handlers) in &mut canvases {
+        if handlers.is_empty() {
+            continue;
+        }
+        // Un bouton maintenu garde `Pressed` hors du nœud : le curseur doit aussi y être
+        let hovered = *interaction != Interaction::None && cursor.mouse_over();
+        // Hors de la fenêtre, la dernière position connue est conservée
+        let local = cursor
+            .normalized
+            .map(|normalized| normalized * node.size())
+            .or(handlers.last_position)
+            .unwrap_or_default();
+
+        let mut send = |handler: &Option<String>, event: &str, extra: serde_json::Value| {
+            let Some(callback) = handler else {
+                return;
+            };
+            let mut arguments = serde_json::json!({
+                "id": element.id,
+                "event": event,
+                "x": local.x,
+                "y": local.y,
+                "buttons": mouse.get_pressed().map(|&button| button_name(button)).collect::<Vec<_>>(),
+                "modifiers": modifiers,
+            });
+            if let (Some(arguments), serde_json::Value::Object(extra)) = (arguments.as_object_mut(), extra) {
+                arguments.extend(extra);
+            }
+            ruby_callback_events.send(RubyCallbackEvent { callback: callback.clone(), arguments });
+        };
+
+        for event in &button_events {
+            let button = serde_json::json!({ "button": button_name(event.button) });
+            if event.state.is_pressed() {
+                if !hovered {
+                    continue;
+                }
+                handlers.captured.push(event.button);
+                send(&handlers.on_pointer_down, "pointer_down", button.clone());
+
+                if handlers.is_double_click(event.button, now, local) {
+                    send(&handlers.on_double_click, "double_click", button);
+                }
+            } else {
+                // Seul un appui commencé sur le canevas y est relâché
+                let Some(index) = handlers.captured.iter().position(|&captured| captured == event.button) else {
+                    continue;
+                };
+                handlers.captured.remove(index);
+                send(&handlers.on_pointer_up, "pointer_up", button.clone());
+                if hovered && event.button == MouseButton::Left {
+                    send(&handlers.on_click, "click", button);
+                }
+            }
+        }
+
+        // Relâchement non reçu (hors de la fenêtre, perte du focus) : la capture prend fin
+        let released: Vec<MouseButton> = handlers.captured.iter().copied().filter(|&button| !mouse.pressed(button)).collect();
+        for button in released {
+            handlers.captured.retain(|&captured| captured != button);
+            send(&handlers.on_pointer_up, "pointer_up", serde_json::json!({ "button": button_name(button) }));
+        }
+
+        // Un seul déplacement par frame, sur le canevas ou pendant une capture
+        if (hovered || !handlers.captured.is_empty()) && handlers.last_position != Some(local) {
+            send(&handlers.on_pointer_move, "pointer_move", serde_json::json!({}));
+            handlers.last_position = Some(local);
+        } else if !hovered && handlers.captured.is_empty() {
+            handlers.last_position = None;
+        }
+
+        if hovered {
+            for event in &wheel_events {
+                let unit = match event.unit {
+                    MouseScrollUnit::Line => "line",
+                    MouseScrollUnit::Pixel => "pixel",
+                };
+                send(&handlers.on_wheel, "wheel", serde_json::json!({ "delta_x": event.x, "delta_y": event.y, "unit": unit }));
+            }
+        }
+    }
+}
+
+/// Touches de modification enfoncées
+fn modifiers(keys: &Input<KeyCode>) -> serde_json::Value {
+    serde_json::json!({
+        "shift": keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]),
+        "ctrl": keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]),
+        "alt": keys.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]),
+        "meta": keys.any_pressed([KeyCode::SuperLeft, KeyCode::SuperRight]),
+    })
+}
+
+fn button_name(button: MouseButton) -> String {
+    match button {
+        MouseButton::Left => "left".to_string(),
+        MouseButton::Right => "right".to_string(),
+        MouseButton::Middle => "middle".to_string(),
+        MouseButton::Other(index) => format!("button{}", index),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_double_clicks() {
+        let mut handlers = PointerHandlers::default();
+
+        assert!(!handlers.is_double_click(MouseButton::Left, 1.0, Vec2::new(10.0, 10.0)));
+        assert!(handlers.is_double_click(MouseButton::Left, 1.3, Vec2::new(12.0, 11.0)));
+        // Le troisième appui recommence un double-clic
+        assert!(!handlers.is_double_click(MouseButton::Left, 1.5, Vec2::new(12.0, 11.0)));
+    }
+
+    #[test]
+    fn ignores_slow_distant_or_mixed_presses() {
+        let mut handlers = PointerHandlers::default();
+
+        handlers.is_double_click(MouseButton::Left, 0.0, Vec2::ZERO);
+        assert!(!handlers.is_double_click(MouseButton::Left, 0.6, Vec2::ZERO));
+        assert!(!handlers.is_double_click(MouseButton::Left, 0.7, Vec2::new(5.0, 0.0)));
+        assert!(!handlers.is_double_click(MouseButton::Right, 0.8, Vec2::new(5.0, 0.0)));
+        assert!(handlers.is_double_click(MouseButton::Right, 0.9, Vec2::new(5.0, 3.0)));
+    }
+}
@@ -0,0 +1,70 @@
+// src/ui/registry.rs
+use bevy::hierarchy::despawn_with_children_recursive;
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+/// Registre des entités de l'UI, indexées par ID de composant.
+///
+/// Tenu à jour par le `UIBuilder` à chaque construction ; les systèmes de mise à
+/// jour y retrouvent l'entité visée par un `UIUpdateEvent`.
+#[derive(Resource, Default)]
+pub struct UIRegistry {
+    /// Entité de chaque composant
+    pub(crate) entities: HashMap<String, Entity>,
+    /// Entités qui accueillent les enfants DSL quand ce n'est pas l'entité du composant
+    /// elle-même (ex: le contenu d'une fenêtre)
+    pub(crate) containers: HashMap<String, Entity>,
+    /// Rangées intermédiaires créées pour la mise en page des grilles
+    pub(crate) grid_rows: HashMap<String, Vec<Entity>>,
+    /// Entité ayant reçu le focus
+    pub(crate) focused: Option<Entity>,
+    /// Ordre de premier plan de la dernière fenêtre activée
+    pub(crate) focus_order: i32,
+}
+
+impl UIRegistry {
+    /// Entité d'un composant
+    pub fn get(&self, id: &str) -> Option<Entity> {
+        self.entities.get(id).copied()
+    }
+
+    /// Entité qui accueille les enfants d'un composant
+    pub fn container(&self, id: &str) -> Option<Entity> {
+        self.containers.get(id).copied().or_else(|| self.get(id))
+    }
+
+    /// Vide le registre (reconstruction complète de l'UI)
+    pub fn clear(&mut self) {
+        self.entities.clear();
+        self.containers.clear();
+        self.grid_rows.clear();
+        self.focused = None;
+    }
+
+    /// Retire des composants du registre (leurs entités sont détruites par ailleurs)
+    pub fn forget(&mut self, ids: &[String]) {
+        for id in ids {
+            if let Some(entity) = self.entities.remove(id) {
+                if self.focused == Some(entity) {
+                    self.focused = None;
+                }
+            }
+            self.containers.remove(id);
+            self.grid_rows.remove(id);
+        }
+    }
+}
+
+/// Détruit une entité de l'UI et ses descendants ; le focus est oublié s'il
+/// était porté par l'une d'elles
+pub(crate) fn despawn_subtree(world: &mut World, root: Entity) {
+    let mut current = world.get_resource::<UIRegistry>().and_then(|registry| registry.focused);
+    while let Some(entity) = current {
+        if entity == root {
+            world.resource_mut::<UIRegistry>().focused = None;
+            break;
+        }
+        current = world.get::<Parent>(entity).map(|parent| parent.get());
+    }
+    despawn_with_children_recursive(world, root);
+}
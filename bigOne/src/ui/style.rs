@@ -0,0 +1,756 @@
+// src/ui/style.rs
+//! Résolution de la propriété `style:` des composants.
+//!
+//! ```ruby
+//! button(id: "save", text: "Enregistrer", style: {
+//!   background: "rgb(40, 120, 60)", border_color: "rgb(20, 60, 30)", border_width: 2,
+//!   padding: "8 16", margin: 4, min_width: 120, opacity: 0.9
+//! })
+//! ```
+//!
+//! Les clés s'écrivent en snake_case ou en camelCase (`border_width`, `borderWidth`).
+//! Les longueurs acceptent un nombre de pixels (`12`, `"12px"`), un pourcentage
+//! (`"50%"`) ou `"auto"` ; les marges et espacements internes acceptent aussi la
+//! forme CSS à 2, 3 ou 4 valeurs (`"8 16"` : vertical puis horizontal).
+//! Les couleurs suivent la syntaxe CSS (voir `color`), jetons de thème compris.
+//!
+//! Le style d'un composant est recalculé à partir de son apparence d'origine : les
+//! règles des feuilles de style (voir `stylesheet`) puis son propre `style:`.
+use bevy::ecs::system::Command;
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+use crate::ui::color::{ColorTokens, ColorValue};
+use crate::ui::components::UIElement;
+use crate::ui::theme::{Theme, Themed};
+
+/// Propriété de style reconnue et validée
+#[derive(Debug, Clone, PartialEq)]
+pub enum StyleProperty {
+    Background(ColorValue),
+    BorderColor(ColorValue),
+    TextColor(ColorValue),
+    FontSize(f32),
+    /// Chemin de police, relatif aux assets
+    Font(String),
+    BorderWidth(UiRect),
+    Padding(UiRect),
+    Margin(UiRect),
+    RowGap(Val),
+    ColumnGap(Val),
+    Width(Val),
+    Height(Val),
+    MinWidth(Val),
+    MinHeight(Val),
+    MaxWidth(Val),
+    MaxHeight(Val),
+    FlexGrow(f32),
+    FlexShrink(f32),
+    AlignItems(AlignItems),
+    AlignSelf(AlignSelf),
+    JustifyContent(JustifyContent),
+    Opacity(f32),
+    /// Un seul côté d'un rectangle (`padding_top`...), appliqué après le raccourci
+    Side(Edge, Side, Val),
+    /// Valeur qui référence des jetons du thème (`$spacing.md`), résolue à chaque
+    /// recalcul du style (voir `expand`)
+    Deferred(String, String),
+}
+
+impl StyleProperty {
+    /// La propriété rend-elle `other` sans effet ?
+    fn overrides(&self, other: &StyleProperty) -> bool {
+        match (self, other) {
+            (StyleProperty::Side(edge, side, _), StyleProperty::Side(other_edge, other_side, _)) => edge == other_edge && side == other_side,
+            (StyleProperty::Deferred(key, _), StyleProperty::Deferred(other_key, _)) => key == other_key,
+            (StyleProperty::Padding(_), StyleProperty::Side(Edge::Padding, ..))
+            | (StyleProperty::Margin(_), StyleProperty::Side(Edge::Margin, ..))
+            | (StyleProperty::BorderWidth(_), StyleProperty::Side(Edge::Border, ..)) => true,
+            _ => std::mem::discriminant(self) == std::mem::discriminant(other),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edge {
+    Padding,
+    Margin,
+    Border,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Top,
+    Right,
+    Bottom,
+    Left,
+}
+
+impl Edge {
+    fn of(self, style: &Style) -> UiRect {
+        match self {
+            Edge::Padding => style.padding,
+            Edge::Margin => style.margin,
+            Edge::Border => style.border,
+        }
+    }
+
+    fn of_mut(self, style: &mut Style) -> &mut UiRect {
+        match self {
+            Edge::Padding => &mut style.padding,
+            Edge::Margin => &mut style.margin,
+            Edge::Border => &mut style.border,
+        }
+    }
+}
+
+impl Side {
+    fn of(self, rect: UiRect) -> Val {
+        match self {
+            Side::Top => rect.top,
+            Side::Right => rect.right,
+            Side::Bottom => rect.bottom,
+            Side::Left => rect.left,
+        }
+    }
+
+    fn of_mut(self, rect: &mut UiRect) -> &mut Val {
+        match self {
+            Side::Top => &mut rect.top,
+            Side::Right => &mut rect.right,
+            Side::Bottom => &mut rect.bottom,
+            Side::Left => &mut rect.left,
+        }
+    }
+}
+
+/// Style résolu d'un composant, applicable à son entité
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ResolvedStyle {
+    pub properties: Vec<StyleProperty>,
+}
+
+impl ResolvedStyle {
+    /// Résout un hash de style ; les entrées invalides sont ignorées et décrites
+    /// dans la liste d'erreurs retournée
+    pub fn resolve(map: &HashMap<String, String>) -> (Self, Vec<String>) {
+        let mut resolved = Self::default();
+        let mut errors = Vec::new();
+
+        // Ordre stable : les raccourcis (`padding`) avant leurs variantes (`padding_top`)
+        let mut entries: Vec<(&String, &String)> = map.iter().collect();
+        entries.sort_by_key(|(key, _)| (normalize_key(key).len(), key.to_string()));
+
+        for (key, value) in entries {
+            match resolve_property(&normalize_key(key), value.trim(), &mut resolved.properties) {
+                Ok(true) => {},
+                Ok(false) => errors.push(format!("propriété de style inconnue `{}`", key)),
+                Err(reason) => errors.push(format!("valeur invalide pour `{}`: {} ({})", key, value, reason)),
+            }
+        }
+        (resolved, errors)
+    }
+
+    /// Ajoute les propriétés d'un autre style ; celles qu'elles remplacent sont retirées
+    pub fn merge(&mut self, other: ResolvedStyle) {
+        for property in other.properties {
+            self.properties.retain(|existing| !property.overrides(existing));
+            self.properties.push(property);
+        }
+    }
+
+    /// Remplace les valeurs à jetons par leur valeur dans `lookup` ; une valeur dont
+    /// un jeton est inconnu est ignorée et décrite dans la liste d'erreurs retournée
+    pub fn expand(self, lookup: impl Fn(&str) -> Option<String>) -> (Self, Vec<String>) {
+        let mut expanded = Self::default();
+        let mut errors = Vec::new();
+        for property in self.properties {
+            let StyleProperty::Deferred(key, value) = property else {
+                expanded.properties.push(property);
+                continue;
+            };
+            let resolved = substitute_tokens(&value, &lookup)
+                .and_then(|value| resolve_property(&key, &value, &mut expanded.properties));
+            if let Err(reason) = resolved {
+                errors.push(format!("valeur invalide pour `{}`: {} ({})", key, value, reason));
+            }
+        }
+        (expanded, errors)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.properties.is_empty()
+    }
+
+    /// Remet dans `style` les valeurs de `base` pour chaque propriété de mise en page
+    /// de ce style
+    pub fn restore_layout(&self, style: &mut Style, base: &Style) {
+        for property in &self.properties {
+            match property {
+                StyleProperty::BorderWidth(_) => style.border = base.border,
+                StyleProperty::Padding(_) => style.padding = base.padding,
+                StyleProperty::Margin(_) => style.margin = base.margin,
+                StyleProperty::RowGap(_) => style.row_gap = base.row_gap,
+                StyleProperty::ColumnGap(_) => style.column_gap = base.column_gap,
+                StyleProperty::Width(_) => style.width = base.width,
+                StyleProperty::Height(_) => style.height = base.height,
+                StyleProperty::MinWidth(_) => style.min_width = base.min_width,
+                StyleProperty::MinHeight(_) => style.min_height = base.min_height,
+                StyleProperty::MaxWidth(_) => style.max_width = base.max_width,
+                StyleProperty::MaxHeight(_) => style.max_height = base.max_height,
+                StyleProperty::FlexGrow(_) => style.flex_grow = base.flex_grow,
+                StyleProperty::FlexShrink(_) => style.flex_shrink = base.flex_shrink,
+                StyleProperty::AlignItems(_) => style.align_items = base.align_items,
+                StyleProperty::AlignSelf(_) => style.align_self = base.align_self,
+                StyleProperty::JustifyContent(_) => style.justify_content = base.justify_content,
+                StyleProperty::Side(edge, side, _) => {
+                    let value = side.of(edge.of(base));
+                    *side.of_mut(edge.of_mut(style)) = value;
+                },
+                _ => {},
+            }
+        }
+    }
+
+    /// Applique les propriétés de mise en page à un `Style`
+    pub fn apply_layout(&self, style: &mut Style) {
+        for property in &self.properties {
+            match property {
+                StyleProperty::BorderWidth(border) => style.border = *border,
+                StyleProperty::Padding(padding) => style.padding = *padding,
+                StyleProperty::Margin(margin) => style.margin = *margin,
+                StyleProperty::RowGap(gap) => style.row_gap = *gap,
+                StyleProperty::ColumnGap(gap) => style.column_gap = *gap,
+                StyleProperty::Width(width) => style.width = *width,
+                StyleProperty::Height(height) => style.height = *height,
+                StyleProperty::MinWidth(width) => style.min_width = *width,
+                StyleProperty::MinHeight(height) => style.min_height = *height,
+                StyleProperty::MaxWidth(width) => style.max_width = *width,
+                StyleProperty::MaxHeight(height) => style.max_height = *height,
+                StyleProperty::FlexGrow(grow) => style.flex_grow = *grow,
+                StyleProperty::FlexShrink(shrink) => style.flex_shrink = *shrink,
+                StyleProperty::AlignItems(align) => style.align_items = *align,
+                StyleProperty::AlignSelf(align) => style.align_self = *align,
+                StyleProperty::JustifyContent(justify) => style.justify_content = *justify,
+                StyleProperty::Side(edge, side, val) => *side.of_mut(edge.of_mut(style)) = *val,
+                _ => {},
+            }
+        }
+    }
+
+    pub fn background(&self) -> Option<&ColorValue> {
+        self.properties.iter().rev().find_map(|property| match property {
+            StyleProperty::Background(color) => Some(color),
+            _ => None,
+        })
+    }
+
+    pub fn border_color(&self) -> Option<&ColorValue> {
+        self.properties.iter().rev().find_map(|property| match property {
+            StyleProperty::BorderColor(color) => Some(color),
+            _ => None,
+        })
+    }
+
+    pub fn text_color(&self) -> Option<&ColorValue> {
+        self.properties.iter().rev().find_map(|property| match property {
+            StyleProperty::TextColor(color) => Some(color),
+            _ => None,
+        })
+    }
+
+    pub fn font_size(&self) -> Option<f32> {
+        self.properties.iter().rev().find_map(|property| match property {
+            StyleProperty::FontSize(size) => Some(*size),
+            _ => None,
+        })
+    }
+
+    pub fn font(&self) -> Option<&str> {
+        self.properties.iter().rev().find_map(|property| match property {
+            StyleProperty::Font(font) => Some(font.as_str()),
+            _ => None,
+        })
+    }
+
+    pub fn opacity(&self) -> Option<f32> {
+        self.properties.iter().rev().find_map(|property| match property {
+            StyleProperty::Opacity(opacity) => Some(*opacity),
+            _ => None,
+        })
+    }
+}
+
+/// Style propre d'un composant (`style:` et `set_style`), appliqué après les règles
+/// des feuilles de style
+#[derive(Component, Debug, Clone, Default)]
+pub struct InlineStyle(pub ResolvedStyle);
+
+/// Apparence d'une entité avant sa première mise en style, et dernier style appliqué
+#[derive(Component, Debug, Clone)]
+pub struct StyleBase {
+    layout: Style,
+    background: Option<Color>,
+    border: Option<Color>,
+    /// Couleur, taille et police de chaque section des textes du composant
+    texts: Vec<(Entity, Vec<(Color, f32, Handle<Font>)>)>,
+    applied: ResolvedStyle,
+}
+
+impl StyleBase {
+    fn capture(world: &World, entity: Entity) -> Self {
+        let texts = text_targets(world, entity)
+            .into_iter()
+            .filter_map(|target| {
+                let text = world.get::<Text>(target)?;
+                let sections = text.sections.iter().map(|section| (section.style.color, section.style.font_size, section.style.font.clone())).collect();
+                Some((target, sections))
+            })
+            .collect();
+        Self {
+            layout: world.get::<Style>(entity).cloned().unwrap_or_default(),
+            background: world.get::<BackgroundColor>(entity).map(|background| background.0),
+            border: world.get::<BorderColor>(entity).map(|border| border.0),
+            texts,
+            applied: ResolvedStyle::default(),
+        }
+    }
+}
+
+/// Applique le style calculé d'une entité de l'UI.
+///
+/// Le style remplace le précédent : les propriétés qu'il ne définit plus reprennent
+/// leur valeur d'origine, relevée dans `StyleBase` à la première application. Bevy
+/// n'a pas d'opacité de nœud : elle est appliquée à l'alpha du fond, de la bordure et
+/// des textes. Les couleurs, la taille et la police de texte visent le texte du
+/// composant, ou ceux de ses enfants internes (libellé d'un bouton, titre d'une
+/// fenêtre) ; un enfant `Themed` reprend les valeurs du thème plutôt que celles
+/// d'origine. Les jetons de couleur sont résolus contre les `ColorTokens` du moment ;
+/// un jeton inconnu laisse la couleur d'origine.
+pub struct ApplyStyle {
+    pub entity: Entity,
+    pub style: ResolvedStyle,
+}
+
+impl Command for ApplyStyle {
+    fn apply(self, world: &mut World) {
+        if world.get_entity(self.entity).is_none() {
+            return;
+        }
+        if world.get::<StyleBase>(self.entity).is_none() {
+            // Une entité jamais stylée garde simplement son apparence d'origine
+            if self.style.is_empty() {
+                return;
+            }
+            let base = StyleBase::capture(world, self.entity);
+            world.entity_mut(self.entity).insert(base);
+        }
+        let Some(mut base) = world.get::<StyleBase>(self.entity).cloned() else {
+            return;
+        };
+
+        let tokens = world.get_resource::<ColorTokens>().cloned();
+        let resolve = |color: Option<&ColorValue>| {
+            let color = color?;
+            let resolved = color.resolve(tokens.as_ref());
+            if resolved.is_none() {
+                warn!("Jeton de couleur inconnu: {:?}", color);
+            }
+            resolved
+        };
+        let background_color = resolve(self.style.background());
+        let border_color = resolve(self.style.border_color());
+        let text_color = resolve(self.style.text_color());
+        let font_size = self.style.font_size();
+        let opacity = self.style.opacity().unwrap_or(1.0);
+        let assets = world.get_resource::<AssetServer>().cloned();
+        let font = self.style.font().zip(assets.as_ref()).map(|(path, assets)| assets.load(path.to_string()));
+
+        // Valeurs de repli des sections de texte : d'origine, ou du thème
+        let theme = world.get_resource::<Theme>();
+        let texts: Vec<(Entity, Vec<(Color, f32, Handle<Font>)>)> = base
+            .texts
+            .iter()
+            .map(|(target, sections)| {
+                let (themed_color, themed_size, themed_font) = match (world.get::<Themed>(*target), theme, assets.as_ref()) {
+                    (Some(themed), Some(theme), Some(assets)) => themed.text_style(theme, assets),
+                    _ => (None, None, None),
+                };
+                let sections = sections
+                    .iter()
+                    .map(|(color, size, font)| (
+                        themed_color.unwrap_or(*color),
+                        themed_size.unwrap_or(*size),
+                        themed_font.clone().unwrap_or_else(|| font.clone()),
+                    ))
+                    .collect();
+                (*target, sections)
+            })
+            .collect();
+
+        let mut entity = world.entity_mut(self.entity);
+        if let Some(mut style) = entity.get_mut::<Style>() {
+            base.applied.restore_layout(&mut style, &base.layout);
+            self.style.apply_layout(&mut style);
+        }
+        if let Some(color) = background_color.or(base.background) {
+            entity.insert(BackgroundColor(with_opacity(color, opacity)));
+        }
+        if let Some(color) = border_color.or(base.border) {
+            entity.insert(BorderColor(with_opacity(color, opacity)));
+        }
+
+        for (target, sections) in texts {
+            let Some(mut text) = world.get_mut::<Text>(target) else {
+                continue;
+            };
+            for (section, (color, size, fallback_font)) in text.sections.iter_mut().zip(sections) {
+                section.style.color = with_opacity(text_color.unwrap_or(color), opacity);
+                section.style.font_size = font_size.unwrap_or(size);
+                section.style.font = font.clone().unwrap_or(fallback_font);
+            }
+        }
+
+        base.applied = self.style;
+        world.entity_mut(self.entity).insert(base);
+    }
+}
+
+/// Textes portés par le composant : le sien, sinon ceux de ses enfants internes
+/// (hors composants DSL imbriqués)
+fn text_targets(world: &World, entity: Entity) -> Vec<Entity> {
+    if world.get::<Text>(entity).is_some() {
+        return vec![entity];
+    }
+    let mut targets = Vec::new();
+    let mut pending: Vec<Entity> = world.get::<Children>(entity).map(|children| children.to_vec()).unwrap_or_default();
+    while let Some(child) = pending.pop() {
+        if world.get::<UIElement>(child).is_some() {
+            continue;
+        }
+        if world.get::<Text>(child).is_some() {
+            targets.push(child);
+        }
+        if let Some(children) = world.get::<Children>(child) {
+            pending.extend(children.iter().copied());
+        }
+    }
+    targets
+}
+
+fn with_opacity(color: Color, opacity: f32) -> Color {
+    color.with_a(color.a() * opacity)
+}
+
+/// `border_width`, `borderWidth` et `border-width` désignent la même propriété
+fn normalize_key(key: &str) -> String {
+    key.chars()
+        .filter(|c| *c != '_' && *c != '-')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Ajoute la propriété décrite par `key` ; retourne `Ok(false)` si la clé est inconnue
+fn resolve_property(key: &str, value: &str, properties: &mut Vec<StyleProperty>) -> Result<bool, String> {
+    // Les jetons des couleurs sont gardés par `ColorValue`, les autres attendent le thème
+    if value.contains('$') && !is_color_key(key) {
+        // La clé est validée avec une valeur factice à la place des jetons
+        let probe = substitute_tokens(value, |_| Some("1".to_string()))?;
+        if !resolve_property(key, &probe, &mut Vec::new()).unwrap_or(true) {
+            return Ok(false);
+        }
+        properties.push(StyleProperty::Deferred(key.to_string(), value.to_string()));
+        return Ok(true);
+    }
+
+    let property = match key {
+        "background" | "backgroundcolor" => StyleProperty::Background(ColorValue::parse(value)?),
+        "bordercolor" => StyleProperty::BorderColor(ColorValue::parse(value)?),
+        "color" | "textcolor" => StyleProperty::TextColor(ColorValue::parse(value)?),
+        "fontsize" | "size" => {
+            let size = parse_number(value)?;
+            if size <= 0.0 {
+                return Err("taille positive attendue".to_string());
+            }
+            StyleProperty::FontSize(size)
+        },
+        "font" | "fontfamily" => {
+            if value.is_empty() {
+                return Err("chemin de police attendu".to_string());
+            }
+            StyleProperty::Font(value.to_string())
+        },
+        "border" | "borderwidth" => StyleProperty::BorderWidth(parse_rect(value)?),
+        "padding" => StyleProperty::Padding(parse_rect(value)?),
+        "margin" => StyleProperty::Margin(parse_rect(value)?),
+        "gap" => {
+            let gap = parse_val(value)?;
+            properties.push(StyleProperty::RowGap(gap));
+            StyleProperty::ColumnGap(gap)
+        },
+        "rowgap" => StyleProperty::RowGap(parse_val(value)?),
+        "columngap" => StyleProperty::ColumnGap(parse_val(value)?),
+        "width" => StyleProperty::Width(parse_val(value)?),
+        "height" => StyleProperty::Height(parse_val(value)?),
+        "minwidth" => StyleProperty::MinWidth(parse_val(value)?),
+        "minheight" => StyleProperty::MinHeight(parse_val(value)?),
+        "maxwidth" => StyleProperty::MaxWidth(parse_val(value)?),
+        "maxheight" => StyleProperty::MaxHeight(parse_val(value)?),
+        "flexgrow" | "grow" => StyleProperty::FlexGrow(parse_number(value)?),
+        "flexshrink" | "shrink" => StyleProperty::FlexShrink(parse_number(value)?),
+        "align" | "alignitems" => StyleProperty::AlignItems(parse_align_items(value)?),
+        "alignself" => StyleProperty::AlignSelf(parse_align_self(value)?),
+        "justify" | "justifycontent" => StyleProperty::JustifyContent(parse_justify(value)?),
+        "opacity" => {
+            let opacity = parse_number(value)?;
+            if !(0.0..=1.0).contains(&opacity) {
+                return Err("attendu entre 0 et 1".to_string());
+            }
+            StyleProperty::Opacity(opacity)
+        },
+        _ => return resolve_side(key, value, properties),
+    };
+    properties.push(property);
+    Ok(true)
+}
+
+/// Côtés individuels : `padding_top`, `margin_left`, `border_bottom_width`...
+fn resolve_side(key: &str, value: &str, properties: &mut Vec<StyleProperty>) -> Result<bool, String> {
+    let edges = [("padding", Edge::Padding), ("margin", Edge::Margin), ("border", Edge::Border)];
+    let Some((edge, side)) = edges.iter().find_map(|(prefix, edge)| {
+        let side = key.strip_prefix(prefix)?;
+        let side = if *edge == Edge::Border { side.strip_suffix("width").unwrap_or(side) } else { side };
+        Some((*edge, side))
+    }) else {
+        return Ok(false);
+    };
+    let side = match side {
+        "top" => Side::Top,
+        "right" => Side::Right,
+        "bottom" => Side::Bottom,
+        "left" => Side::Left,
+        _ => return Ok(false),
+    };
+    properties.push(StyleProperty::Side(edge, side, parse_val(value)?));
+    Ok(true)
+}
+
+fn is_color_key(key: &str) -> bool {
+    matches!(key, "background" | "backgroundcolor" | "bordercolor" | "color" | "textcolor")
+}
+
+/// Remplace chaque mot `$catégorie.nom` d'une valeur par la valeur du jeton
+fn substitute_tokens(value: &str, lookup: impl Fn(&str) -> Option<String>) -> Result<String, String> {
+    value
+        .split_whitespace()
+        .map(|word| match word.strip_prefix('$') {
+            Some(token) => lookup(token).ok_or_else(|| format!("jeton inconnu `{}`", word)),
+            None => Ok(word.to_string()),
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map(|words| words.join(" "))
+}
+
+fn parse_number(value: &str) -> Result<f32, String> {
+    value.parse::<f32>().map_err(|_| "nombre attendu".to_string())
+}
+
+/// Longueur : `12`, `"12px"`, `"50%"` ou `"auto"`
+pub fn parse_val(value: &str) -> Result<Val, String> {
+    let value = value.trim();
+    if value == "auto" {
+        return Ok(Val::Auto);
+    }
+    if let Some(percent) = value.strip_suffix('%') {
+        return percent.trim().parse::<f32>().map(Val::Percent).map_err(|_| "pourcentage invalide".to_string());
+    }
+    let pixels = value.strip_suffix("px").unwrap_or(value).trim();
+    pixels.parse::<f32>().map(Val::Px).map_err(|_| "longueur attendue (12, \"12px\", \"50%\" ou \"auto\")".to_string())
+}
+
+/// Rectangle à la CSS : 1 à 4 longueurs (haut, droite, bas, gauche)
+fn parse_rect(value: &str) -> Result<UiRect, String> {
+    let values = value.split_whitespace().map(parse_val).collect::<Result<Vec<_>, _>>()?;
+    match values.as_slice() {
+        [all] => Ok(UiRect::all(*all)),
+        [vertical, horizontal] => Ok(UiRect::new(*horizontal, *horizontal, *vertical, *vertical)),
+        [top, horizontal, bottom] => Ok(UiRect::new(*horizontal, *horizontal, *top, *bottom)),
+        [top, right, bottom, left] => Ok(UiRect::new(*left, *right, *top, *bottom)),
+        _ => Err("1 à 4 longueurs attendues".to_string()),
+    }
+}
+
+fn parse_align_items(value: &str) -> Result<AlignItems, String> {
+    match normalize_key(value).as_str() {
+        "start" | "flexstart" => Ok(AlignItems::FlexStart),
+        "center" => Ok(AlignItems::Center),
+        "end" | "flexend" => Ok(AlignItems::FlexEnd),
+        "stretch" => Ok(AlignItems::Stretch),
+        "baseline" => Ok(AlignItems::Baseline),
+        _ => Err("start, center, end, stretch ou baseline attendu".to_string()),
+    }
+}
+
+fn parse_align_self(value: &str) -> Result<AlignSelf, String> {
+    match normalize_key(value).as_str() {
+        "auto" => Ok(AlignSelf::Auto),
+        "start" | "flexstart" => Ok(AlignSelf::FlexStart),
+        "center" => Ok(AlignSelf::Center),
+        "end" | "flexend" => Ok(AlignSelf::FlexEnd),
+        "stretch" => Ok(AlignSelf::Stretch),
+        "baseline" => Ok(AlignSelf::Baseline),
+        _ => Err("auto, start, center, end, stretch ou baseline attendu".to_string()),
+    }
+}
+
+fn parse_justify(value: &str) -> Result<JustifyContent, String> {
+    match normalize_key(value).as_str() {
+        "start" | "flexstart" => Ok(JustifyContent::FlexStart),
+        "center" => Ok(JustifyContent::Center),
+        "end" | "flexend" => Ok(JustifyContent::FlexEnd),
+        "spacebetween" => Ok(JustifyContent::SpaceBetween),
+        "spacearound" => Ok(JustifyContent::SpaceAround),
+        "spaceevenly" => Ok(JustifyContent::SpaceEvenly),
+        _ => Err("start, center, end, space_between, space_around ou space_evenly attendu".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn style(entries: &[(&str, &str)]) -> HashMap<String, String> {
+        entries.iter().map(|(key, value)| (key.to_string(), value.to_string())).collect()
+    }
+
+    #[test]
+    fn accepts_snake_camel_and_kebab_keys() {
+        for key in ["border_width", "borderWidth", "border-width"] {
+            let (resolved, errors) = ResolvedStyle::resolve(&style(&[(key, "2")]));
+            assert!(errors.is_empty(), "{:?}", errors);
+            assert_eq!(resolved.properties, [StyleProperty::BorderWidth(UiRect::all(Val::Px(2.0)))]);
+        }
+    }
+
+    #[test]
+    fn parses_css_rectangles() {
+        let (resolved, errors) = ResolvedStyle::resolve(&style(&[("padding", "8 16"), ("margin", "1 2 3 4")]));
+
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert!(resolved.properties.contains(&StyleProperty::Padding(UiRect::new(
+            Val::Px(16.0),
+            Val::Px(16.0),
+            Val::Px(8.0),
+            Val::Px(8.0)
+        ))));
+        assert!(resolved.properties.contains(&StyleProperty::Margin(UiRect::new(
+            Val::Px(4.0),
+            Val::Px(2.0),
+            Val::Px(1.0),
+            Val::Px(3.0)
+        ))));
+    }
+
+    #[test]
+    fn orders_shorthands_before_their_sides() {
+        let (resolved, errors) = ResolvedStyle::resolve(&style(&[("padding_top", "2"), ("padding", "8")]));
+
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert_eq!(
+            resolved.properties,
+            [
+                StyleProperty::Padding(UiRect::all(Val::Px(8.0))),
+                StyleProperty::Side(Edge::Padding, Side::Top, Val::Px(2.0)),
+            ]
+        );
+
+        let mut layout = Style::default();
+        resolved.apply_layout(&mut layout);
+        assert_eq!(layout.padding.top, Val::Px(2.0));
+        assert_eq!(layout.padding.bottom, Val::Px(8.0));
+    }
+
+    #[test]
+    fn gap_sets_rows_and_columns() {
+        let (resolved, _) = ResolvedStyle::resolve(&style(&[("gap", "10%")]));
+
+        assert_eq!(
+            resolved.properties,
+            [StyleProperty::RowGap(Val::Percent(10.0)), StyleProperty::ColumnGap(Val::Percent(10.0))]
+        );
+    }
+
+    #[test]
+    fn reports_invalid_entries_and_keeps_the_others() {
+        let (resolved, errors) = ResolvedStyle::resolve(&style(&[
+            ("opacity", "1.5"),
+            ("wobble", "3"),
+            ("width", "large"),
+            ("height", "auto"),
+        ]));
+
+        assert_eq!(resolved.properties, [StyleProperty::Height(Val::Auto)]);
+        assert_eq!(errors.len(), 3, "{:?}", errors);
+    }
+
+    #[test]
+    fn keeps_color_tokens_and_defers_the_others() {
+        let (resolved, errors) = ResolvedStyle::resolve(&style(&[("background", "$primary"), ("padding", "$spacing.md 4")]));
+
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert!(resolved.properties.contains(&StyleProperty::Background(ColorValue::Token("primary".to_string()))));
+        assert!(resolved
+            .properties
+            .contains(&StyleProperty::Deferred("padding".to_string(), "$spacing.md 4".to_string())));
+
+        let (expanded, errors) = resolved.expand(|token| (token == "spacing.md").then(|| "12".to_string()));
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert!(expanded.properties.contains(&StyleProperty::Padding(UiRect::new(
+            Val::Px(4.0),
+            Val::Px(4.0),
+            Val::Px(12.0),
+            Val::Px(12.0)
+        ))));
+        assert_eq!(expanded.background(), Some(&ColorValue::Token("primary".to_string())));
+    }
+
+    #[test]
+    fn reports_unknown_tokens_on_expand() {
+        let (resolved, errors) = ResolvedStyle::resolve(&style(&[("width", "$sizes.huge")]));
+        assert!(errors.is_empty(), "{:?}", errors);
+
+        let (expanded, errors) = resolved.expand(|_| None);
+        assert!(expanded.is_empty());
+        assert_eq!(errors.len(), 1);
+    }
+
+    #[test]
+    fn rejects_tokens_for_unknown_keys() {
+        let (resolved, errors) = ResolvedStyle::resolve(&style(&[("wobble", "$spacing.md")]));
+
+        assert!(resolved.is_empty());
+        assert_eq!(errors, ["propriété de style inconnue `wobble`"]);
+    }
+
+    #[test]
+    fn merge_replaces_overridden_properties() {
+        let (mut base, _) = ResolvedStyle::resolve(&style(&[("padding_left", "2"), ("width", "10"), ("opacity", "0.5")]));
+        let (inline, _) = ResolvedStyle::resolve(&style(&[("padding", "4"), ("width", "20")]));
+
+        base.merge(inline);
+
+        assert_eq!(base.properties.len(), 3);
+        assert_eq!(base.opacity(), Some(0.5));
+        assert!(base.properties.contains(&StyleProperty::Padding(UiRect::all(Val::Px(4.0)))));
+        assert!(base.properties.contains(&StyleProperty::Width(Val::Px(20.0))));
+    }
+
+    #[test]
+    fn parses_lengths() {
+        assert_eq!(parse_val("auto"), Ok(Val::Auto));
+        assert_eq!(parse_val("50%"), Ok(Val::Percent(50.0)));
+        assert_eq!(parse_val("12px"), Ok(Val::Px(12.0)));
+        assert_eq!(parse_val(" 12 "), Ok(Val::Px(12.0)));
+        assert!(parse_val("12em").is_err());
+    }
+}
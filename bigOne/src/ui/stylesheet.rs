@@ -0,0 +1,394 @@
+// src/ui/stylesheet.rs
+//! Feuilles de style déclarées en Ruby.
+//!
+//! ```ruby
+//! stylesheet do
+//!   rule "button.primary", background: "#3366cc"
+//!   rule "button.primary:hover", background: "#4477dd"
+//!   rule "#sidebar text", size: 14
+//! end
+//! ```
+//!
+//! Un sélecteur enchaîne des sélecteurs composés séparés par des espaces (descendance).
+//! Un sélecteur composé combine un type (`button`) ou `*`, un ID (`#sidebar`), des
+//! classes (`.primary`, propriété `class:` des composants) et des états (`:hover`,
+//! `:pressed`, `:focus`, `:disabled`). Les règles s'appliquent par spécificité
+//! croissante puis dans l'ordre de déclaration, après l'apparence par défaut du thème
+//! (voir `theme`) ; le `style:` du composant passe en dernier.
+use bevy::prelude::*;
+use std::collections::HashSet;
+
+use crate::ui::color::ColorTokens;
+use crate::ui::components::*;
+use crate::ui::style::{ApplyStyle, InlineStyle, ResolvedStyle};
+use crate::ui::theme::{BaseStylesheet, Theme};
+
+/// Types de composants du DSL, pour signaler les sélecteurs qui ne peuvent rien viser
+const COMPONENT_TYPES: [&str; 14] = [
+    "window", "button", "text", "image", "canvas", "svg", "scrollview", "list", "grid",
+    "input", "viewport3d", "stack", "row", "column",
+];
+
+/// État d'interaction visé par une pseudo-classe
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PseudoState {
+    Hover,
+    Pressed,
+    Focus,
+    Disabled,
+}
+
+/// Sélecteur composé : `button#save.primary:hover`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CompoundSelector {
+    pub component_type: Option<String>,
+    pub id: Option<String>,
+    pub classes: Vec<String>,
+    pub states: Vec<PseudoState>,
+}
+
+/// Sélecteur complet : sélecteurs composés liés par la descendance
+#[derive(Debug, Clone, PartialEq)]
+pub struct Selector {
+    pub compounds: Vec<CompoundSelector>,
+}
+
+/// Spécificité : (IDs, classes et états, types)
+pub type Specificity = (usize, usize, usize);
+
+/// Ce qu'un sélecteur peut observer d'un composant affiché
+pub struct ElementState {
+    pub id: String,
+    pub component_type: String,
+    pub classes: Vec<String>,
+    pub hovered: bool,
+    pub pressed: bool,
+    pub focused: bool,
+    pub disabled: bool,
+}
+
+impl Selector {
+    pub fn parse(selector: &str) -> Result<Self, String> {
+        let compounds = selector
+            .split_whitespace()
+            .map(parse_compound)
+            .collect::<Result<Vec<_>, _>>()?;
+        if compounds.is_empty() {
+            return Err("sélecteur vide".to_string());
+        }
+        Ok(Self { compounds })
+    }
+
+    pub fn specificity(&self) -> Specificity {
+        self.compounds.iter().fold((0, 0, 0), |(ids, classes, types), compound| (
+            ids + compound.id.is_some() as usize,
+            classes + compound.classes.len() + compound.states.len(),
+            types + compound.component_type.is_some() as usize,
+        ))
+    }
+
+    /// `ancestors` va du parent le plus proche à la racine
+    pub fn matches(&self, element: &ElementState, ancestors: &[ElementState]) -> bool {
+        let Some((last, rest)) = self.compounds.split_last() else {
+            return false;
+        };
+        if !last.matches(element) {
+            return false;
+        }
+        // Descendance seule : l'ancêtre le plus proche qui convient est toujours le bon choix
+        let mut ancestors = ancestors.iter();
+        rest.iter().rev().all(|compound| ancestors.any(|ancestor| compound.matches(ancestor)))
+    }
+
+    /// Le sélecteur dépend-il d'un ancêtre ?
+    fn has_descendant(&self) -> bool {
+        self.compounds.len() > 1
+    }
+}
+
+impl CompoundSelector {
+    fn matches(&self, element: &ElementState) -> bool {
+        self.component_type.as_deref().map_or(true, |component_type| component_type == element.component_type)
+            && self.id.as_deref().map_or(true, |id| id == element.id)
+            && self.classes.iter().all(|class| element.classes.contains(class))
+            && self.states.iter().all(|state| match state {
+                PseudoState::Hover => element.hovered,
+                PseudoState::Pressed => element.pressed,
+                PseudoState::Focus => element.focused,
+                PseudoState::Disabled => element.disabled,
+            })
+    }
+}
+
+fn parse_compound(compound: &str) -> Result<CompoundSelector, String> {
+    if compound == ">" || compound == "+" || compound == "~" {
+        return Err(format!("combinateur `{}` non supporté (seule la descendance l'est)", compound));
+    }
+
+    let mut selector = CompoundSelector::default();
+    let mut rest = compound;
+
+    // Type en tête, ou `*`
+    let type_len = rest.find(|c: char| matches!(c, '#' | '.' | ':')).unwrap_or(rest.len());
+    let (component_type, tail) = rest.split_at(type_len);
+    match component_type {
+        "" | "*" => {},
+        name if COMPONENT_TYPES.contains(&name) => selector.component_type = Some(name.to_string()),
+        name => return Err(format!("type de composant inconnu `{}`", name)),
+    }
+    rest = tail;
+
+    while let Some(marker) = rest.chars().next() {
+        let tail = &rest[1..];
+        let len = tail.find(|c: char| matches!(c, '#' | '.' | ':')).unwrap_or(tail.len());
+        let (name, tail) = tail.split_at(len);
+        if name.is_empty() {
+            return Err(format!("nom manquant après `{}` dans `{}`", marker, compound));
+        }
+        match marker {
+            '#' if selector.id.is_some() => return Err(format!("plusieurs IDs dans `{}`", compound)),
+            '#' => selector.id = Some(name.to_string()),
+            '.' => selector.classes.push(name.to_string()),
+            _ => selector.states.push(match name {
+                "hover" => PseudoState::Hover,
+                "pressed" | "active" => PseudoState::Pressed,
+                "focus" => PseudoState::Focus,
+                "disabled" => PseudoState::Disabled,
+                other => return Err(format!("état inconnu `:{}` (hover, pressed, focus ou disabled)", other)),
+            }),
+        }
+        rest = tail;
+    }
+    Ok(selector)
+}
+
+/// Règle de feuille de style
+#[derive(Debug, Clone, PartialEq)]
+pub struct StyleRule {
+    pub selector: Selector,
+    pub style: ResolvedStyle,
+}
+
+/// Règles de toutes les feuilles de style du script, dans l'ordre de déclaration
+#[derive(Resource, Debug, Clone, Default, PartialEq)]
+pub struct Stylesheet {
+    pub rules: Vec<StyleRule>,
+}
+
+impl Stylesheet {
+    /// Style des règles qui s'appliquent à un composant, par spécificité puis par ordre
+    pub fn computed(&self, element: &ElementState, ancestors: &[ElementState]) -> ResolvedStyle {
+        let mut matched: Vec<(Specificity, usize, &StyleRule)> = self
+            .rules
+            .iter()
+            .enumerate()
+            .filter(|(_, rule)| rule.selector.matches(element, ancestors))
+            .map(|(order, rule)| (rule.selector.specificity(), order, rule))
+            .collect();
+        matched.sort_by_key(|(specificity, order, _)| (*specificity, *order));
+
+        ResolvedStyle {
+            properties: matched
+                .into_iter()
+                .flat_map(|(_, _, rule)| rule.style.properties.iter().cloned())
+                .collect(),
+        }
+    }
+
+    fn has_descendant_rules(&self) -> bool {
+        self.rules.iter().any(|rule| rule.selector.has_descendant())
+    }
+}
+
+/// Recalcule le style des composants dont l'état, les classes ou le style propre ont
+/// changé ; toute l'UI quand la feuille de style, le thème ou les jetons de couleur
+/// changent
+#[allow(clippy::too_many_arguments)]
+pub fn restyle_system(
+    mut commands: Commands,
+    base: Res<BaseStylesheet>,
+    stylesheet: Res<Stylesheet>,
+    theme: Res<Theme>,
+    tokens: Res<ColorTokens>,
+    changed: Query<Entity, (With<UIElement>, Or<(Changed<Interaction>, Changed<StyleClasses>, Changed<InlineStyle>, Added<Focused>, Added<Disabled>)>)>,
+    mut unfocused: RemovedComponents<Focused>,
+    mut enabled: RemovedComponents<Disabled>,
+    elements: Query<(Entity, &UIElement, Option<&StyleClasses>, Option<&Interaction>, Has<Focused>, Has<Disabled>, Option<&InlineStyle>)>,
+    parents: Query<&Parent>,
+    children: Query<&Children>,
+) {
+    let mut dirty: HashSet<Entity> = if stylesheet.is_changed() || theme.is_changed() || tokens.is_changed() {
+        elements.iter().map(|(entity, ..)| entity).collect()
+    } else {
+        changed.iter().chain(unfocused.iter()).chain(enabled.iter()).collect()
+    };
+    if dirty.is_empty() {
+        return;
+    }
+
+    // Un changement d'état d'un ancêtre peut concerner les sélecteurs de descendance
+    if stylesheet.has_descendant_rules() || base.0.has_descendant_rules() {
+        let roots: Vec<Entity> = dirty.iter().copied().collect();
+        for root in roots {
+            dirty.extend(children.iter_descendants(root).filter(|entity| elements.contains(*entity)));
+        }
+    }
+
+    for entity in dirty {
+        let Ok(item) = elements.get(entity) else {
+            continue;
+        };
+        let ancestors: Vec<ElementState> = parents
+            .iter_ancestors(entity)
+            .filter_map(|ancestor| elements.get(ancestor).ok().map(element_state))
+            .collect();
+        let inline = item.6.map(|inline| inline.0.clone());
+
+        let state = element_state(item);
+        let mut style = base.0.computed(&state, &ancestors);
+        style.properties.extend(stylesheet.computed(&state, &ancestors).properties);
+        if let Some(inline) = inline {
+            style.properties.extend(inline.properties);
+        }
+
+        let (style, errors) = style.expand(|token| theme.lookup(token));
+        for error in errors {
+            warn!("Style de `{}`: {}", state.id, error);
+        }
+        commands.add(ApplyStyle { entity, style });
+    }
+}
+
+type ElementItem<'a> = (Entity, &'a UIElement, Option<&'a StyleClasses>, Option<&'a Interaction>, bool, bool, Option<&'a InlineStyle>);
+
+fn element_state((_, element, classes, interaction, focused, disabled, _): ElementItem) -> ElementState {
+    let (hovered, pressed) = match interaction {
+        Some(Interaction::Pressed) => (true, true),
+        Some(Interaction::Hovered) => (true, false),
+        _ => (false, false),
+    };
+    ElementState {
+        id: element.id.clone(),
+        component_type: element.component_type.clone(),
+        classes: classes.map(|classes| classes.0.clone()).unwrap_or_default(),
+        hovered,
+        pressed,
+        focused,
+        disabled,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::color::ColorValue;
+    use crate::ui::style::StyleProperty;
+
+    fn element(component_type: &str, id: &str, classes: &[&str]) -> ElementState {
+        ElementState {
+            id: id.to_string(),
+            component_type: component_type.to_string(),
+            classes: classes.iter().map(|class| class.to_string()).collect(),
+            hovered: false,
+            pressed: false,
+            focused: false,
+            disabled: false,
+        }
+    }
+
+    fn rule(selector: &str, background: &str) -> StyleRule {
+        StyleRule {
+            selector: Selector::parse(selector).unwrap(),
+            style: ResolvedStyle {
+                properties: vec![StyleProperty::Background(ColorValue::parse(background).unwrap())],
+            },
+        }
+    }
+
+    #[test]
+    fn parses_compound_selectors() {
+        let selector = Selector::parse("button#save.primary.large:hover:active").unwrap();
+
+        assert_eq!(
+            selector.compounds,
+            [CompoundSelector {
+                component_type: Some("button".to_string()),
+                id: Some("save".to_string()),
+                classes: vec!["primary".to_string(), "large".to_string()],
+                states: vec![PseudoState::Hover, PseudoState::Pressed],
+            }]
+        );
+        assert_eq!(Selector::parse("*").unwrap().compounds, [CompoundSelector::default()]);
+        assert_eq!(Selector::parse("  #sidebar   text ").unwrap().compounds.len(), 2);
+    }
+
+    #[test]
+    fn rejects_unsupported_selectors() {
+        for selector in [
+            "",
+            "   ",
+            "row > text",
+            "row + text",
+            "row ~ text",
+            "paragraph",
+            "button:visited",
+            "#a#b",
+            "button.",
+            "text#",
+        ] {
+            assert!(Selector::parse(selector).is_err(), "`{}` accepté", selector);
+        }
+        assert_eq!(Selector::parse("paragraph").unwrap_err(), "type de composant inconnu `paragraph`");
+    }
+
+    #[test]
+    fn computes_specificity() {
+        let specificity = |selector: &str| Selector::parse(selector).unwrap().specificity();
+
+        assert_eq!(specificity("*"), (0, 0, 0));
+        assert_eq!(specificity("button"), (0, 0, 1));
+        assert_eq!(specificity("button.primary:hover"), (0, 2, 1));
+        assert_eq!(specificity("#sidebar text.note"), (1, 1, 1));
+    }
+
+    #[test]
+    fn matches_states_and_ancestors() {
+        let mut button = element("button", "save", &["primary"]);
+        let panel = element("column", "panel", &[]);
+        let sidebar = element("window", "sidebar", &["dark"]);
+        let ancestors = [panel, sidebar];
+
+        let hover = Selector::parse("button.primary:hover").unwrap();
+        assert!(!hover.matches(&button, &ancestors));
+        button.hovered = true;
+        assert!(hover.matches(&button, &ancestors));
+
+        assert!(Selector::parse("#sidebar button").unwrap().matches(&button, &ancestors));
+        assert!(Selector::parse(".dark column #save").unwrap().matches(&button, &ancestors));
+        assert!(!Selector::parse("column .dark #save").unwrap().matches(&button, &ancestors));
+        assert!(!Selector::parse("#sidebar button").unwrap().matches(&button, &[]));
+        assert!(!Selector::parse("button.secondary").unwrap().matches(&button, &ancestors));
+    }
+
+    #[test]
+    fn applies_rules_by_specificity_then_order() {
+        let stylesheet = Stylesheet {
+            rules: vec![
+                rule("#save", "red"),
+                rule("button.primary", "green"),
+                rule("button", "blue"),
+                rule(".primary", "yellow"),
+                rule("text", "black"),
+            ],
+        };
+
+        let computed = stylesheet.computed(&element("button", "save", &["primary"]), &[]);
+
+        let backgrounds: Vec<StyleProperty> = ["blue", "yellow", "green", "red"]
+            .into_iter()
+            .map(|color| StyleProperty::Background(ColorValue::parse(color).unwrap()))
+            .collect();
+        assert_eq!(computed.properties, backgrounds);
+        assert_eq!(computed.background(), Some(&ColorValue::parse("red").unwrap()));
+    }
+}
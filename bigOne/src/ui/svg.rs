@@ -0,0 +1,257 @@
+// src/ui/svg.rs
+//! Rendu des SVG : analyse par usvg, rastérisation par resvg.
+//!
+//! ```ruby
+//! svg(id: "logo", source: "icons/logo.svg", width: 48)
+//! svg(id: "close", markup: '<svg viewBox="0 0 10 10"><path d="M1 1L9 9M9 1L1 9" stroke="#000"/></svg>',
+//!     width: 16, height: 16, stroke: "$text")
+//! ```
+//!
+//! Un SVG est rastérisé à la taille calculée de son nœud multipliée par le facteur
+//! d'échelle de la fenêtre, puis de nouveau dès que cette taille change. Une dimension
+//! non déclarée suit la taille intrinsèque du document, proportions conservées.
+//! `fill:` et `stroke:` remplacent la couleur des remplissages et des contours
+//! existants (teinte d'icône), jetons de thème compris.
+use bevy::asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext};
+use bevy::prelude::*;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use bevy::utils::{BoxedFuture, HashSet};
+use bevy::window::PrimaryWindow;
+use resvg::{tiny_skia, usvg};
+use std::sync::OnceLock;
+use usvg::{TreeParsing, TreeTextToPath};
+
+use crate::ui::color::{ColorTokens, ColorValue};
+
+/// Document SVG, validé au chargement
+#[derive(Asset, TypePath, Debug, Clone)]
+pub struct Svg {
+    data: Vec<u8>,
+    /// Taille intrinsèque, en pixels logiques
+    pub size: Vec2,
+}
+
+/// Erreur de lecture ou de rendu d'un SVG
+#[derive(Debug, thiserror::Error)]
+pub enum SvgError {
+    #[error("lecture impossible: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("SVG invalide: {0}")]
+    Parse(#[from] usvg::Error),
+    #[error("taille de rendu invalide: {0}x{1}")]
+    Size(u32, u32),
+}
+
+/// Couleurs imposées aux tracés d'un SVG
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Tint {
+    pub fill: Option<Color>,
+    pub stroke: Option<Color>,
+}
+
+impl Svg {
+    pub fn from_data(data: Vec<u8>) -> Result<Self, SvgError> {
+        let tree = parse(&data)?;
+        Ok(Self {
+            size: Vec2::new(tree.size.width(), tree.size.height()),
+            data,
+        })
+    }
+
+    /// Rastérise le document sur `size` pixels, centré et sans déformation
+    pub fn rasterize(&self, size: UVec2, tint: &Tint) -> Result<Image, SvgError> {
+        let mut pixmap = tiny_skia::Pixmap::new(size.x, size.y).ok_or(SvgError::Size(size.x, size.y))?;
+        let tree = parse(&self.data)?;
+        apply_tint(&tree, tint);
+
+        let (width, height) = (tree.size.width(), tree.size.height());
+        let scale = (size.x as f32 / width).min(size.y as f32 / height);
+        let transform = tiny_skia::Transform::from_translate(
+            (size.x as f32 - width * scale) / 2.0,
+            (size.y as f32 - height * scale) / 2.0,
+        )
+        .pre_scale(scale, scale);
+        resvg::Tree::from_usvg(&tree).render(transform, &mut pixmap.as_mut());
+        Ok(pixmap_to_image(&pixmap))
+    }
+}
+
+/// Convertit un rendu tiny-skia en image Bevy
+pub(crate) fn pixmap_to_image(pixmap: &tiny_skia::Pixmap) -> Image {
+    // tiny-skia produit de l'alpha prémultiplié, Bevy attend de l'alpha droit
+    let data = pixmap
+        .pixels()
+        .iter()
+        .flat_map(|pixel| {
+            let color = pixel.demultiply();
+            [color.red(), color.green(), color.blue(), color.alpha()]
+        })
+        .collect();
+    Image::new(
+        Extent3d { width: pixmap.width(), height: pixmap.height(), depth_or_array_layers: 1 },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+    )
+}
+
+/// Polices système et polices des assets, pour convertir les textes en tracés ;
+/// `sans-serif` désigne la police de l'UI
+fn fonts() -> &'static usvg::fontdb::Database {
+    static FONTS: OnceLock<usvg::fontdb::Database> = OnceLock::new();
+    FONTS.get_or_init(|| {
+        let mut fonts = usvg::fontdb::Database::new();
+        fonts.load_system_fonts();
+        fonts.load_fonts_dir("assets/fonts");
+        fonts.set_sans_serif_family("Fira Sans");
+        fonts
+    })
+}
+
+pub(crate) fn parse(data: &[u8]) -> Result<usvg::Tree, SvgError> {
+    let mut tree = usvg::Tree::from_data(data, &usvg::Options::default())?;
+    tree.convert_text(fonts());
+    Ok(tree)
+}
+
+fn apply_tint(tree: &usvg::Tree, tint: &Tint) {
+    if tint.fill.is_none() && tint.stroke.is_none() {
+        return;
+    }
+    for node in tree.root.descendants() {
+        if let usvg::NodeKind::Path(ref mut path) = *node.borrow_mut() {
+            if let (Some(fill), Some(color)) = (path.fill.as_mut(), tint.fill) {
+                set_paint(&mut fill.paint, &mut fill.opacity, color);
+            }
+            if let (Some(stroke), Some(color)) = (path.stroke.as_mut(), tint.stroke) {
+                set_paint(&mut stroke.paint, &mut stroke.opacity, color);
+            }
+        }
+    }
+}
+
+fn set_paint(paint: &mut usvg::Paint, opacity: &mut usvg::Opacity, color: Color) {
+    let [red, green, blue, _] = color.as_rgba_u8();
+    *paint = usvg::Paint::Color(usvg::Color::new_rgb(red, green, blue));
+    *opacity = usvg::Opacity::new_clamped(opacity.get() * color.a());
+}
+
+/// Chargeur des fichiers `.svg`
+#[derive(Default)]
+pub struct SvgLoader;
+
+impl AssetLoader for SvgLoader {
+    type Asset = Svg;
+    type Settings = ();
+    type Error = SvgError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Svg, SvgError>> {
+        Box::pin(async move {
+            let mut data = Vec::new();
+            reader.read_to_end(&mut data).await?;
+            Svg::from_data(data)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["svg"]
+    }
+}
+
+/// SVG affiché par un nœud d'image ; le rendu remplace la texture de son `UiImage`
+#[derive(Component, Debug, Clone)]
+pub struct SvgImage {
+    pub handle: Handle<Svg>,
+    pub fill: Option<ColorValue>,
+    pub stroke: Option<ColorValue>,
+}
+
+/// Paramètres du dernier rendu d'un SVG
+#[derive(Component, Debug, Clone, PartialEq)]
+pub struct SvgRaster {
+    source: AssetId<Svg>,
+    size: UVec2,
+    tint: Tint,
+}
+
+/// Image transparente d'un pixel, remplacée par le premier rendu (SVG, canevas)
+pub fn placeholder_image() -> Image {
+    Image::new_fill(
+        Extent3d { width: 1, height: 1, depth_or_array_layers: 1 },
+        TextureDimension::D2,
+        &[0, 0, 0, 0],
+        TextureFormat::Rgba8UnormSrgb,
+    )
+}
+
+/// Rastérise les SVG dont le document, la taille, l'échelle ou la teinte a changé
+pub fn rasterize_svg_system(
+    mut commands: Commands,
+    documents: Res<Assets<Svg>>,
+    mut images: ResMut<Assets<Image>>,
+    mut document_events: EventReader<AssetEvent<Svg>>,
+    tokens: Res<ColorTokens>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut nodes: Query<(Entity, &SvgImage, &Node, &mut Style, &UiImage, Option<&SvgRaster>)>,
+) {
+    // Un document rechargé à chaud garde son identifiant
+    let modified: HashSet<AssetId<Svg>> = document_events
+        .iter()
+        .filter_map(|event| match event {
+            AssetEvent::Modified { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+    let scale = windows.get_single().map_or(1.0, |window| window.scale_factor() as f32);
+
+    for (entity, svg, node, mut style, image, raster) in &mut nodes {
+        let Some(document) = documents.get(&svg.handle) else {
+            continue;
+        };
+        // Style n'est modifié (et la mise en page relancée) que si une dimension change
+        if let Some((width, height)) = intrinsic_size(&style, document.size) {
+            style.width = width;
+            style.height = height;
+        }
+
+        // Le nœud n'a pas encore de taille calculée
+        let size = (node.size() * scale).round().as_uvec2();
+        if size.x == 0 || size.y == 0 {
+            continue;
+        }
+        let tint = Tint {
+            fill: svg.fill.as_ref().and_then(|color| color.resolve(Some(&tokens))),
+            stroke: svg.stroke.as_ref().and_then(|color| color.resolve(Some(&tokens))),
+        };
+        let rendered = SvgRaster { source: svg.handle.id(), size, tint };
+        if raster == Some(&rendered) && !modified.contains(&rendered.source) {
+            continue;
+        }
+
+        match document.rasterize(size, &tint) {
+            Ok(raster) => {
+                images.insert(image.texture.id(), raster);
+            },
+            Err(error) => warn!("Rendu du SVG impossible: {}", error),
+        }
+        // Un échec n'est pas retenté tant que rien ne change
+        commands.entity(entity).insert(rendered);
+    }
+}
+
+/// Dimensions à donner au nœud : les dimensions `auto` prennent la taille intrinsèque
+/// du document, proportions conservées ; `None` si le style n'a pas à changer
+fn intrinsic_size(style: &Style, intrinsic: Vec2) -> Option<(Val, Val)> {
+    let ratio = intrinsic.x / intrinsic.y.max(f32::EPSILON);
+    match (style.width, style.height) {
+        (Val::Auto, Val::Auto) => Some((Val::Px(intrinsic.x), Val::Px(intrinsic.y))),
+        (Val::Auto, Val::Px(height)) => Some((Val::Px(height * ratio), Val::Px(height))),
+        (Val::Px(width), Val::Auto) => Some((Val::Px(width), Val::Px(width / ratio))),
+        _ => None,
+    }
+}
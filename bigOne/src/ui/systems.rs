@@ -2,13 +2,31 @@
 use bevy::{
     prelude::*,
     input::mouse::{MouseButton, MouseButtonInput},
-    window::PrimaryWindow,
+    window::{PrimaryWindow, RequestRedraw},
 };
-use std::collections::HashMap;
+use winit::event_loop::EventLoop;
 
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use crate::ui::components::*;
-use crate::ui::builder::base::UIBuilder;
-use crate::dsl::parser::DSLParser;
+use crate::ui::builder::UIBuilder;
+use crate::ui::canvas::{rasterize_canvas_system, request_canvas_draw_system, CanvasDrawn, CanvasSurface};
+use crate::ui::color::ColorTokens;
+use crate::ui::pointer::canvas_pointer_system;
+use crate::ui::registry::UIRegistry;
+use crate::ui::stylesheet::Stylesheet;
+use crate::ui::svg::{rasterize_svg_system, Svg, SvgLoader};
+use crate::ui::theme::{BaseStylesheet, Theme, Themes};
+use crate::ui::viewport3d::{despawn_viewport_system, resize_viewport_system, spawn_viewport_system, viewport_default_light_system, viewport_layers_system, viewport_scene_system, ViewportLayers};
+use crate::ui::updates::add_update_systems;
+use crate::dsl::parser::{DSLEvaluationResult, ScriptError};
+use crate::dsl::sandbox::SandboxConfig;
+use crate::dsl::loader::DependencyGraph;
+use crate::dsl::worker::{RubyRequest, RubyResponse, RubyWorker};
+use crate::dsl::reactive::property_updates;
+use crate::ui::error_overlay::{ErrorOverlay, show_error_overlay, clear_error_overlay, dismiss_error_overlay_system};
+use crate::ui::file_watcher::{AssetChangedEvent, FileWatcher, DEFAULT_DEBOUNCE, asset_path};
+use crate::dsl::loader::canonical;
 
 /// Plugin pour l'interface utilisateur DSL
 pub struct UIDSLPlugin;
@@ -17,42 +35,131 @@ impl Plugin for UIDSLPlugin {
     fn build(&self, app: &mut App) {
         // Enregistrer les ressources nécessaires
         app.init_resource::<UIDSLState>()
+            .init_resource::<UIRegistry>()
+            .init_resource::<ColorTokens>()
+            .init_resource::<Stylesheet>()
+            .init_resource::<BaseStylesheet>()
+            .init_resource::<Themes>()
+            .init_resource::<Theme>()
+            .init_resource::<ViewportLayers>()
             .init_resource::<UIHotReload>();
 
+        // Documents SVG, rastérisés à la taille de leur nœud
+        app.init_asset::<Svg>()
+            .init_asset_loader::<SvgLoader>();
+
         // Enregistrer les événements personnalisés
         app.add_event::<RubyCallbackEvent>()
-            .add_event::<UIUpdateEvent>();
+            .add_event::<UIUpdateEvent>()
+            .add_event::<CanvasDrawn>()
+            .add_event::<AssetChangedEvent>();
 
         // Ajouter les systèmes
         app.add_systems(Update, (
             window_drag_system,
             window_resize_system,
             button_click_system,
+            canvas_pointer_system,
             input_interaction_system,
-            handle_ui_updates,
             handle_ruby_callbacks,
+            ruby_response_system,
             hot_reload_system,
+            dismiss_error_overlay_system,
+            rasterize_svg_system,
+            request_canvas_draw_system,
+            rasterize_canvas_system,
+            spawn_viewport_system,
+            resize_viewport_system,
+            despawn_viewport_system,
+            viewport_layers_system,
+            viewport_scene_system,
+            viewport_default_light_system,
         ));
+        add_update_systems(app);
+    }
+
+    fn finish(&self, app: &mut App) {
+        // La configuration du hot-reload est connue une fois tous les plugins ajoutés
+        let hot_reload = app.world.resource::<UIHotReload>();
+        if !hot_reload.enabled {
+            return;
+        }
+        let script_dir = canonical(Path::new(&hot_reload.script_path).parent().unwrap_or(Path::new(".")));
+        let dirs: Vec<PathBuf> = [script_dir, canonical(&hot_reload.asset_dir)]
+            .into_iter()
+            .filter(|dir| dir.is_dir())
+            .collect();
+
+        // Les notifications réveillent la boucle d'événements, même en mode économe
+        let wake = app
+            .world
+            .get_non_send_resource::<EventLoop<()>>()
+            .map(|event_loop| event_loop.create_proxy());
+
+        match FileWatcher::new(&dirs, DEFAULT_DEBOUNCE, wake) {
+            Ok(watcher) => { app.insert_resource(watcher); },
+            Err(error) => warn!("Surveillance des fichiers indisponible, hot-reload désactivé: {}", error),
+        }
     }
 }
 
 /// État global de l'interface DSL
 #[derive(Resource)]
 pub struct UIDSLState {
-    /// Constructeur d'UI
-    pub builder: UIBuilder,
-    /// Parser DSL
-    pub parser: Option<DSLParser>,
+    /// Interpréteur Ruby, sur son propre thread
+    pub worker: Option<RubyWorker>,
     /// État de la dernière évaluation
     pub last_eval: Option<DSLEvaluationResult>,
+    /// Graphe des imports de la dernière évaluation
+    pub dependencies: DependencyGraph,
+    /// Restrictions et capacités appliquées au parser à sa création
+    pub sandbox: SandboxConfig,
+    /// Valeurs des composants déjà transmises à l'interpréteur
+    pub live: LiveSnapshot,
+    /// Blocage d'un handler, affiché de nouveau une fois le script réévalué
+    pub stall: Option<ScriptError>,
 }
 
 impl Default for UIDSLState {
     fn default() -> Self {
         Self {
-            builder: UIBuilder::new(),
-            parser: None,
+            worker: None,
             last_eval: None,
+            dependencies: DependencyGraph::default(),
+            sandbox: SandboxConfig::default(),
+            live: LiveSnapshot::default(),
+            stall: None,
+        }
+    }
+}
+
+/// Suivi des valeurs lues par `OS.find(id)` : seuls les composants modifiés
+/// depuis le dernier relevé transmis sont relevés à nouveau
+pub struct LiveSnapshot {
+    /// Le prochain relevé couvre tous les composants (nouvel interpréteur)
+    full: bool,
+    /// Composants modifiés depuis le dernier relevé transmis
+    dirty: HashSet<Entity>,
+    /// Composants transmis, avec leur ID, pour signaler leur retrait
+    sent: HashMap<Entity, String>,
+    /// IDs des composants retirés depuis le dernier relevé transmis
+    removed: Vec<String>,
+}
+
+impl LiveSnapshot {
+    /// L'interpréteur ne connaît plus aucune valeur : tout relever au prochain relevé
+    pub fn resync(&mut self) {
+        *self = Self::default();
+    }
+}
+
+impl Default for LiveSnapshot {
+    fn default() -> Self {
+        Self {
+            full: true,
+            dirty: HashSet::new(),
+            sent: HashMap::new(),
+            removed: Vec::new(),
         }
     }
 }
@@ -64,8 +171,22 @@ pub struct UIHotReload {
     pub enabled: bool,
     /// Chemin du script principal
     pub script_path: String,
-    /// Dernier timestamp de modification
-    pub last_modified: std::time::SystemTime,
+    /// Répertoire des assets (images, polices, SVG) rechargés individuellement
+    pub asset_dir: String,
+    /// Scripts importés lors de la dernière évaluation, surveillés avec le script principal
+    pub dependencies: HashSet<PathBuf>,
+}
+
+impl UIHotReload {
+    /// Met à jour la liste des fichiers surveillés à partir du graphe des imports
+    pub fn watch_dependencies(&mut self, dsl_state: &UIDSLState) {
+        self.dependencies = dsl_state.dependencies.files();
+    }
+
+    /// Le fichier est le script principal ou l'un de ses imports
+    fn is_script(&self, path: &Path) -> bool {
+        *path == canonical(&self.script_path) || self.dependencies.contains(path)
+    }
 }
 
 impl Default for UIHotReload {
@@ -73,79 +194,232 @@ impl Default for UIHotReload {
         Self {
             enabled: true,
             script_path: "scripts/ui.rb".to_string(),
-            last_modified: std::time::SystemTime::now(),
+            asset_dir: "assets".to_string(),
+            dependencies: HashSet::new(),
         }
     }
 }
 
-/// Initialise le système DSL avec un script
+/// Initialise le système DSL avec un script.
+///
+/// L'évaluation est attendue : cette fonction sert à la construction initiale de
+/// l'UI. Si une UI a déjà été construite, le nouvel arbre est réconcilié avec l'ancien.
 pub fn initialize_dsl(
-    mut commands: Commands,
-    mut dsl_state: ResMut<UIDSLState>,
-    asset_server: Res<AssetServer>,
+    commands: &mut Commands,
+    dsl_state: &mut UIDSLState,
+    registry: &mut UIRegistry,
+    asset_server: &Res<AssetServer>,
+    script_path: &str,
     script: &str,
-) -> Result<(), String> {
-    // Créer le parser DSL s'il n'existe pas déjà
-    if dsl_state.parser.is_none() {
-        let mut parser = DSLParser::new()?;
-        parser.initialize_dsl()?;
-        dsl_state.parser = Some(parser);
+) -> Result<Vec<UIUpdateEvent>, ScriptError> {
+    // Démarrer l'interpréteur s'il n'existe pas déjà
+    if dsl_state.worker.is_none() {
+        let worker = RubyWorker::spawn(dsl_state.sandbox.clone()).map_err(ScriptError::internal)?;
+        dsl_state.worker = Some(worker);
     }
 
-    // Évaluer le script DSL
-    if let Some(parser) = &mut dsl_state.parser {
-        let eval_result = parser.evaluate_dsl(script)?;
+    let limit = dsl_state.sandbox.budget_for(script_path).stall_limit();
+    let response = match &mut dsl_state.worker {
+        Some(worker) => worker.evaluate_now(script_path, script, limit),
+        None => return Ok(Vec::new()),
+    };
+    if let Some(stall) = abandon_stalled_worker(dsl_state, script_path) {
+        return Err(stall.error);
+    }
+    let RubyResponse::Evaluated { result, dependencies, .. } = response else {
+        return Ok(Vec::new());
+    };
+    dsl_state.dependencies = dependencies;
+
+    apply_evaluation(commands, dsl_state, registry, asset_server, result)
+}
+
+/// Interpréteur abandonné parce qu'il ne répondait plus
+struct Stall {
+    /// Erreur affichée, qui nomme le script ou le handler bloqué
+    error: ScriptError,
+    /// Le blocage est survenu pendant l'évaluation du script lui-même
+    evaluation: bool,
+}
+
+/// Abandonne l'interpréteur s'il ne répond plus dans le budget du script
+fn abandon_stalled_worker(dsl_state: &mut UIDSLState, script_path: &str) -> Option<Stall> {
+    let limit = dsl_state.sandbox.budget_for(script_path).stall_limit();
+    let worker = dsl_state.worker.as_ref()?;
+    if !worker.stalled(limit) {
+        return None;
+    }
+    let current = worker.current()?.clone();
+
+    let error = ScriptError::timeout(format!(
+        "`{}` ne répond plus depuis {} ms : l'interpréteur Ruby a été remplacé et l'état du script perdu",
+        current.label,
+        limit.as_millis()
+    ));
+    error!("{}", error);
+    if let Some(worker) = dsl_state.worker.take() {
+        worker.abandon();
+    }
+    dsl_state.live.resync();
+    Some(Stall { error, evaluation: current.evaluation })
+}
+
+/// Démarre un nouvel interpréteur et lui envoie le script sans attendre : le
+/// résultat est appliqué par `ruby_response_system`, comme un rechargement
+fn respawn_worker(dsl_state: &mut UIDSLState, script_path: &str, script: String) -> Result<(), ScriptError> {
+    let mut worker = RubyWorker::spawn(dsl_state.sandbox.clone()).map_err(ScriptError::internal)?;
+    worker.send(RubyRequest::Evaluate {
+        script_path: script_path.to_string(),
+        script,
+    });
+    dsl_state.worker = Some(worker);
+    Ok(())
+}
+
+/// Applique le résultat d'une évaluation à l'UI vivante.
+///
+/// Seules les entités modifiées sont touchées, et les mises à jour de propriétés
+/// à appliquer sont retournées. En cas d'erreur, l'UI existante n'est pas modifiée.
+fn apply_evaluation(
+    commands: &mut Commands,
+    dsl_state: &mut UIDSLState,
+    registry: &mut UIRegistry,
+    asset_server: &Res<AssetServer>,
+    result: Result<DSLEvaluationResult, ScriptError>,
+) -> Result<Vec<UIUpdateEvent>, ScriptError> {
+    // Évaluation et conversion complètes avant de toucher à l'UI vivante
+    let eval_result = result?;
+    
+    // Une erreur de conversion bloque l'application du script
+    if let Some(error) = eval_result.first_error() {
+        return Err(ScriptError::from(error));
+    }
+    
+    // Les avertissements n'empêchent pas l'application du script
+    for diagnostic in &eval_result.diagnostics {
+        warn!("{}", diagnostic);
+    }
+    
+    // Réconcilier l'UI existante avec les composants générés
+    let previous = dsl_state.last_eval.as_ref().map(|eval| eval.components.as_slice()).unwrap_or(&[]);
+    let updates = UIBuilder::new(registry).reconcile_ui(commands, previous, &eval_result.components, asset_server);
+    
+    // Les règles inchangées ne déclenchent pas de recalcul du style de toute l'UI
+    let stylesheet = eval_result.stylesheet.clone();
+    let (themes, theme) = (eval_result.themes.clone(), eval_result.theme.clone());
+    commands.add(move |world: &mut World| {
+        world.resource_mut::<Stylesheet>().set_if_neq(stylesheet);
         
-        // S'il y a des erreurs, les renvoyer
-        if !eval_result.errors.is_empty() {
-            return Err(eval_result.errors.join("\n"));
+        // De même, des thèmes inchangés ne recalculent rien (voir `sync_theme_system`)
+        let mut reloaded = world.resource::<Themes>().clone();
+        for error in reloaded.reload(themes, theme.as_deref()) {
+            warn!("Thème: {}", error);
         }
+        world.resource_mut::<Themes>().set_if_neq(reloaded);
         
-        // Construire l'UI à partir des composants générés
-        dsl_state.builder.build_ui(&mut commands, &eval_result.components, &asset_server);
-        
-        // Sauvegarder le résultat d'évaluation
-        dsl_state.last_eval = Some(eval_result);
-    }
+        // Les handlers `on_draw` ont pu changer : les canevas conservés sont redessinés
+        for mut canvas in world.query::<&mut CanvasSurface>().iter_mut(world) {
+            canvas.invalidate();
+        }
+    });
+    
+    // Sauvegarder le résultat d'évaluation
+    dsl_state.last_eval = Some(eval_result);
 
-    Ok(())
+    Ok(updates)
 }
 
-/// Système pour la fonctionnalité de hot-reload
+/// Affiche le résultat d'un rechargement : mises à jour appliquées, ou bannière d'erreur
+fn report_reload(
+    commands: &mut Commands,
+    asset_server: &Res<AssetServer>,
+    overlays: &Query<Entity, With<ErrorOverlay>>,
+    ui_update_events: &mut EventWriter<UIUpdateEvent>,
+    script_path: &str,
+    result: Result<Vec<UIUpdateEvent>, ScriptError>,
+) {
+    match result {
+        Ok(updates) => {
+            ui_update_events.send_batch(updates);
+            clear_error_overlay(commands, overlays);
+            info!("UI rechargée depuis {}", script_path);
+        },
+        Err(error) => {
+            // L'UI précédente reste en place, l'erreur est affichée par-dessus
+            error!("Erreur lors du rechargement DSL: {}", error);
+            show_error_overlay(commands, asset_server, overlays, script_path, &error);
+        }
+    }
+}
+
+/// Système pour la fonctionnalité de hot-reload.
+///
+/// Les modifications de scripts réévaluent l'UI ; celles des assets ne
+/// rechargent que les fichiers concernés.
+#[allow(clippy::too_many_arguments)]
 fn hot_reload_system(
     mut commands: Commands,
-    mut hot_reload: ResMut<UIHotReload>,
+    hot_reload: Res<UIHotReload>,
     mut dsl_state: ResMut<UIDSLState>,
+    watcher: Option<ResMut<FileWatcher>>,
     asset_server: Res<AssetServer>,
-    windows: Query<Entity, With<UIElement>>,
+    mut asset_events: EventWriter<AssetChangedEvent>,
+    mut redraw: EventWriter<RequestRedraw>,
+    overlays: Query<Entity, With<ErrorOverlay>>,
 ) {
+    let Some(mut watcher) = watcher else {
+        return;
+    };
     if !hot_reload.enabled {
         return;
     }
 
-    // Vérifier si le fichier a été modifié
-    if let Ok(metadata) = std::fs::metadata(&hot_reload.script_path) {
-        if let Ok(modified) = metadata.modified() {
-            // Si le fichier a été modifié depuis la dernière vérification
-            if modified > hot_reload.last_modified {
-                hot_reload.last_modified = modified;
-                
-                // Lire le contenu du fichier
-                if let Ok(script) = std::fs::read_to_string(&hot_reload.script_path) {
-                    // Despawn toutes les entités UI existantes
-                    for entity in windows.iter() {
-                        commands.entity(entity).despawn_recursive();
-                    }
-                    
-                    // Tenter de réinitialiser l'UI avec le nouveau script
-                    if let Err(error) = initialize_dsl(commands.reborrow(), dsl_state.reborrow(), asset_server.clone(), &script) {
-                        error!("Erreur lors du rechargement DSL: {}", error);
-                    } else {
-                        info!("UI rechargée depuis {}", hot_reload.script_path);
-                    }
+    let changed = watcher.poll();
+    // Continuer à tourner jusqu'à la fin du délai de stabilisation
+    if watcher.has_pending() {
+        redraw.send(RequestRedraw);
+    }
+    if changed.is_empty() {
+        return;
+    }
+
+    // Assets : recharger uniquement les fichiers modifiés
+    let asset_dir = canonical(&hot_reload.asset_dir);
+    for path in &changed {
+        if let Some(asset) = asset_path(path, &asset_dir) {
+            info!("Asset modifié: {}", asset);
+            asset_server.reload(asset.clone());
+            asset_events.send(AssetChangedEvent { path: asset });
+        }
+    }
+
+    // Scripts : le script principal est réévalué s'il est concerné
+    let scripts: Vec<&PathBuf> = changed.iter().filter(|path| hot_reload.is_script(path)).collect();
+    if scripts.is_empty() {
+        return;
+    }
+    for path in &scripts {
+        let dependents = dsl_state.dependencies.dependents_of(path);
+        info!("{} modifié ({} script(s) dépendant(s))", path.display(), dependents.len());
+    }
+
+    // Il réimporte les fichiers modifiés. Le résultat est appliqué par
+    // ruby_response_system quand il arrive
+    if let Ok(script) = std::fs::read_to_string(&hot_reload.script_path) {
+        match dsl_state.worker.as_mut() {
+            Some(worker) => worker.send(RubyRequest::Evaluate {
+                script_path: hot_reload.script_path.clone(),
+                script,
+            }),
+            // Interpréteur abandonné après un blocage : le script modifié repart d'un neuf
+            None => {
+                if let Err(error) = respawn_worker(&mut dsl_state, &hot_reload.script_path, script) {
+                    error!("Erreur lors du rechargement DSL: {}", error);
+                    show_error_overlay(&mut commands, &asset_server, &overlays, &hot_reload.script_path, &error);
                 }
             }
         }
+        redraw.send(RequestRedraw);
     }
 }
 
@@ -347,7 +621,7 @@ fn window_resize_system(
 /// Système pour gérer les clics sur les boutons
 fn button_click_system(
     buttons: Query<(Entity, &UIElement, &EventCallback), With<Button>>,
-    mut interaction_query: Query<(Entity, &Interaction, Changed<Interaction>), With<Button>>,
+    mut interaction_query: Query<(Entity, &Interaction, Changed<Interaction>), (With<Button>, Without<Disabled>)>,
     mut ruby_callback_events: EventWriter<RubyCallbackEvent>,
 ) {
     for (entity, interaction, changed) in interaction_query.iter_mut() {
@@ -358,10 +632,10 @@ fn button_click_system(
                     // Déclencher l'événement de callback Ruby
                     ruby_callback_events.send(RubyCallbackEvent {
                         callback: callback.callback.clone(),
-                        arguments: HashMap::from([
-                            ("id".to_string(), ui_element.id.clone()),
-                            ("event".to_string(), "click".to_string()),
-                        ]),
+                        arguments: serde_json::json!({
+                            "id": ui_element.id,
+                            "event": "click",
+                        }),
                     });
                     break;
                 }
@@ -372,7 +646,7 @@ fn button_click_system(
 
 /// Système pour gérer les interactions avec les champs de saisie
 fn input_interaction_system(
-    mut inputs: Query<(Entity, &UIElement, &EventCallback, &Children), With<UIElement>>,
+    mut inputs: Query<(Entity, &UIElement, &EventCallback, &Children), (With<UIElement>, Without<Disabled>)>,
     mut text_query: Query<&mut Text>,
     keys: Res<Input<KeyCode>>,
     mut ui_update_events: EventWriter<UIUpdateEvent>,
@@ -399,11 +673,11 @@ fn input_interaction_system(
                     // Déclencher l'événement de callback Ruby
                     ruby_callback_events.send(RubyCallbackEvent {
                         callback: callback.callback.clone(),
-                        arguments: HashMap::from([
-                            ("id".to_string(), ui_element.id.clone()),
-                            ("event".to_string(), "submit".to_string()),
-                            ("value".to_string(), current_text),
-                        ]),
+                        arguments: serde_json::json!({
+                            "id": ui_element.id,
+                            "event": "submit",
+                            "value": current_text,
+                        }),
                     });
                 }
             }
@@ -411,67 +685,202 @@ fn input_interaction_system(
     }
 }
 
-/// Système pour gérer les mises à jour de l'UI
-fn handle_ui_updates(
-    mut commands: Commands,
-    mut ui_update_events: EventReader<UIUpdateEvent>,
+/// Composant affiché relevé pour `OS.find(id)`
+type LiveElement<'a> = (Entity, &'a UIElement, Option<&'a Node>, Option<&'a Style>, Option<&'a Visibility>, Option<&'a Text>, Option<&'a Children>, Has<Focused>, Option<&'a StyleClasses>, Has<Disabled>);
+
+/// Composants dont une valeur relevée a changé
+type LiveChanged = (
+    With<UIElement>,
+    Or<(
+        Added<UIElement>,
+        Changed<Text>,
+        Changed<Style>,
+        Changed<Node>,
+        Changed<Visibility>,
+        Changed<Children>,
+        Changed<StyleClasses>,
+        Added<Focused>,
+        Added<Disabled>,
+    )>,
+);
+
+/// Système pour gérer les callbacks Ruby : ils sont transmis à l'interpréteur,
+/// dans l'ordre, sans attendre leur exécution
+#[allow(clippy::too_many_arguments)]
+fn handle_ruby_callbacks(
+    mut ruby_callback_events: EventReader<RubyCallbackEvent>,
     mut dsl_state: ResMut<UIDSLState>,
+    elements: Query<LiveElement>,
+    texts: Query<&Text>,
+    changed: Query<Entity, LiveChanged>,
+    changed_texts: Query<&Parent, (Changed<Text>, Without<UIElement>)>,
+    mut unfocused: RemovedComponents<Focused>,
+    mut enabled: RemovedComponents<Disabled>,
+    mut despawned: RemovedComponents<UIElement>,
 ) {
-    for event in ui_update_events.iter() {
-        // Demander au builder de mettre à jour le composant
-        if let Err(error) = dsl_state.builder.update_component(&mut commands, &event.id, &event.update) {
-            error!("Erreur de mise à jour UI: {}", error);
+    // Les changements sont relevés à chaque frame, et transmis avec le prochain callback
+    let live = &mut dsl_state.live;
+    live.dirty.extend(changed.iter());
+    live.dirty.extend(changed_texts.iter().map(|parent| parent.get()));
+    live.dirty.extend(unfocused.iter().chain(enabled.iter()));
+    for entity in despawned.iter() {
+        live.dirty.remove(&entity);
+        if let Some(id) = live.sent.remove(&entity) {
+            live.removed.push(id);
         }
     }
+
+    if ruby_callback_events.is_empty() {
+        return;
+    }
+    let UIDSLState { worker, live, .. } = &mut *dsl_state;
+    let Some(worker) = worker.as_mut() else {
+        return;
+    };
+
+    // Tant que des réponses sont attendues, l'ECS ne reflète pas encore les
+    // modifications des handlers précédents : Ruby garde alors ses propres valeurs
+    if !worker.is_busy() {
+        worker.send(RubyRequest::Snapshot {
+            components: live_snapshot(live, &elements, &texts),
+            removed: std::mem::take(&mut live.removed),
+        });
+    }
+
+    for event in ruby_callback_events.iter() {
+        worker.send(RubyRequest::Callback {
+            callback: event.callback.clone(),
+            arguments: event.arguments.clone(),
+        });
+    }
 }
 
-/// Système pour gérer les callbacks Ruby
-fn handle_ruby_callbacks(
-    mut ruby_callback_events: EventReader<RubyCallbackEvent>,
+/// Relève les valeurs affichées des composants modifiés depuis le dernier relevé
+/// (de tous au premier relevé), lues par `OS.find(id)`
+fn live_snapshot(live: &mut LiveSnapshot, elements: &Query<LiveElement>, texts: &Query<&Text>) -> serde_json::Value {
+    let mut snapshot = serde_json::Map::new();
+    let dirty = std::mem::take(&mut live.dirty);
+    let entities: Vec<Entity> = if std::mem::take(&mut live.full) {
+        elements.iter().map(|(entity, ..)| entity).collect()
+    } else {
+        dirty.into_iter().collect()
+    };
+
+    for entity in entities {
+        let Ok((_, element, node, style, visibility, text, children, focused, classes, disabled)) = elements.get(entity) else {
+            continue;
+        };
+        let mut values = serde_json::Map::new();
+        values.insert("type".to_string(), element.component_type.clone().into());
+
+        // Texte porté par le nœud lui-même ou par un enfant direct (bouton, saisie)
+        let text = text.or_else(|| children.and_then(|children| children.iter().find_map(|&child| texts.get(child).ok())));
+        if let Some(section) = text.and_then(|text| text.sections.first()) {
+            values.insert("text".to_string(), section.value.clone().into());
+        }
+        if let Some(style) = style {
+            if let (Val::Px(x), Val::Px(y)) = (style.left, style.top) {
+                values.insert("x".to_string(), x.into());
+                values.insert("y".to_string(), y.into());
+            }
+        }
+        if let Some(node) = node {
+            let size = node.size();
+            values.insert("width".to_string(), size.x.into());
+            values.insert("height".to_string(), size.y.into());
+        }
+        values.insert("visible".to_string(), (visibility != Some(&Visibility::Hidden)).into());
+        values.insert("focused".to_string(), focused.into());
+        values.insert("class".to_string(), classes.map(|classes| classes.0.clone()).unwrap_or_default().into());
+        values.insert("disabled".to_string(), disabled.into());
+
+        live.sent.insert(entity, element.id.clone());
+        snapshot.insert(element.id.clone(), values.into());
+    }
+    snapshot.into()
+}
+
+/// Système appliquant les réponses de l'interpréteur Ruby, dans l'ordre des requêtes
+#[allow(clippy::too_many_arguments)]
+fn ruby_response_system(
+    mut commands: Commands,
     mut dsl_state: ResMut<UIDSLState>,
+    mut registry: ResMut<UIRegistry>,
+    mut hot_reload: ResMut<UIHotReload>,
+    asset_server: Res<AssetServer>,
     mut ui_update_events: EventWriter<UIUpdateEvent>,
+    mut canvas_events: EventWriter<CanvasDrawn>,
+    mut redraw: EventWriter<RequestRedraw>,
+    overlays: Query<Entity, With<ErrorOverlay>>,
 ) {
-    for event in ruby_callback_events.iter() {
-        // Exécuter le callback Ruby
-        if let Some(parser) = &mut dsl_state.parser {
-            match parser.execute_callback(&event.callback, event.arguments.clone()) {
-                Ok(result) => {
-                    // Traiter le résultat du callback (par exemple, mettre à jour l'UI)
-                    info!("Callback Ruby exécuté: {} -> {}", event.callback, result);
-                    
-                    // Si le résultat est un JSON qui contient une mise à jour d'UI, l'appliquer
-                    if let Ok(update) = serde_json::from_str::<serde_json::Value>(&result) {
-                        if let Some(updates) = update.get("updates").and_then(|u| u.as_array()) {
-                            for update_item in updates {
-                                if let (Some(id), Some(action), Some(value)) = (
-                                    update_item.get("id").and_then(|i| i.as_str()),
-                                    update_item.get("action").and_then(|a| a.as_str()),
-                                    update_item.get("value"),
-                                ) {
-                                    // Créer un événement de mise à jour UI en fonction de l'action
-                                    match action {
-                                        "setText" => {
-                                            if let Some(text) = value.as_str() {
-                                                ui_update_events.send(UIUpdateEvent {
-                                                    id: id.to_string(),
-                                                    update: UIComponentUpdate::SetText(text.to_string()),
-                                                });
-                                            }
-                                        },
-                                        // Autres types de mises à jour...
-                                        _ => {
-                                            warn!("Action de mise à jour inconnue: {}", action);
-                                        }
-                                    }
-                                }
-                            }
+    let Some(worker) = dsl_state.worker.as_mut() else {
+        return;
+    };
+    let responses = worker.try_recv();
+    // Continuer à tourner tant que l'interpréteur travaille
+    if worker.is_busy() {
+        redraw.send(RequestRedraw);
+    }
+
+    for response in responses {
+        match response {
+            RubyResponse::Callback { callback, result, property_changes } => {
+                match result {
+                    Ok(response) => {
+                        info!("Callback Ruby exécuté: {} ({} mise(s) à jour)", callback, response.updates.len());
+                        for error in &response.errors {
+                            warn!("Réponse invalide du callback {}: {}", callback, error);
                         }
-                    }
-                },
-                Err(error) => {
-                    error!("Erreur d'exécution du callback Ruby: {}", error);
+                        ui_update_events.send_batch(response.updates);
+                    },
+                    Err(error) => error!("Erreur d'exécution du callback Ruby: {}", error),
                 }
-            }
+                
+                // Propager les propriétés recalculées par les états modifiés dans le handler
+                match property_changes {
+                    Ok(changes) => ui_update_events.send_batch(property_updates(changes)),
+                    Err(error) => error!("Erreur de mise à jour réactive: {}", error),
+                }
+            },
+            RubyResponse::Drawn { id, result } => canvas_events.send(CanvasDrawn { id, result }),
+            RubyResponse::Evaluated { script_path, result, dependencies } => {
+                dsl_state.dependencies = dependencies;
+                hot_reload.watch_dependencies(&dsl_state);
+                
+                // Réconcilier l'UI avec le nouveau script, sans tout reconstruire
+                let result = apply_evaluation(&mut commands, &mut dsl_state, &mut registry, &asset_server, result);
+                let recovered = result.is_ok();
+                report_reload(&mut commands, &asset_server, &overlays, &mut ui_update_events, &script_path, result);
+                
+                // Réévaluation après le blocage d'un handler : le blocage reste signalé
+                if let Some(error) = dsl_state.stall.take().filter(|_| recovered) {
+                    show_error_overlay(&mut commands, &asset_server, &overlays, &script_path, &error);
+                }
+            },
         }
     }
-}
\ No newline at end of file
+
+    // Interpréteur bloqué hors d'atteinte du chien de garde : l'UI reste affichée
+    // et le script est réévalué par un nouvel interpréteur, sans attendre le résultat.
+    // Un script bloqué à l'évaluation n'est réessayé qu'à sa prochaine modification
+    let script_path = hot_reload.script_path.clone();
+    let Some(stall) = abandon_stalled_worker(&mut dsl_state, &script_path) else {
+        return;
+    };
+    show_error_overlay(&mut commands, &asset_server, &overlays, &script_path, &stall.error);
+    if stall.evaluation {
+        dsl_state.stall = None;
+        return;
+    }
+    let respawned = std::fs::read_to_string(&script_path)
+        .map_err(|e| ScriptError::internal(format!("Lecture de {} impossible: {}", script_path, e)))
+        .and_then(|script| respawn_worker(&mut dsl_state, &script_path, script));
+    match respawned {
+        Ok(()) => {
+            dsl_state.stall = Some(stall.error);
+            redraw.send(RequestRedraw);
+        },
+        Err(error) => error!("Redémarrage de l'interpréteur Ruby impossible: {}", error),
+    }
+}
+
@@ -0,0 +1,525 @@
+// src/ui/theme.rs
+//! Thèmes : jetons nommés de couleurs, tailles de police, espacements, rayons et polices.
+//!
+//! ```ruby
+//! theme :ocean, extends: :dark do |t|
+//!   t.color :primary, "#1478c8"
+//!   t.font_size :title, 20
+//!   t.spacing :md, 10
+//! end
+//! load_theme "themes/sable.json"
+//! OS.theme = :ocean
+//! ```
+//!
+//! Les styles référencent les couleurs par `$nom` et les autres jetons par
+//! `$catégorie.nom` : `$font_size.title`, `$spacing.md`, `$radius.sm`, `$font.bold`.
+//! L'apparence par défaut des composants est une feuille de style de base écrite avec
+//! ces jetons (`BaseStylesheet`), appliquée avant celles du script ; les pièces
+//! internes des fenêtres portent un `Themed`. Changer de thème recalcule l'apparence
+//! de toute l'UI sans reconstruire l'arbre.
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+use crate::ui::color::{parse_color, ColorTokens};
+use crate::ui::style::ResolvedStyle;
+use crate::ui::stylesheet::{Selector, StyleRule, Stylesheet};
+
+/// Thème actif au démarrage
+pub const DEFAULT_THEME: &str = "dark";
+
+/// Jetons d'un thème
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct Theme {
+    pub name: String,
+    pub colors: HashMap<String, Color>,
+    pub font_sizes: HashMap<String, f32>,
+    pub spacing: HashMap<String, f32>,
+    /// Bevy 0.12 n'arrondit pas les nœuds : les rayons ne servent qu'aux scripts
+    pub radii: HashMap<String, f32>,
+    /// Chemins des polices, relatifs aux assets
+    pub fonts: HashMap<String, String>,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+impl Theme {
+    /// Thème sans jeton
+    pub fn empty(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            colors: HashMap::new(),
+            font_sizes: HashMap::new(),
+            spacing: HashMap::new(),
+            radii: HashMap::new(),
+            fonts: HashMap::new(),
+        }
+    }
+
+    pub fn dark() -> Self {
+        Self::with_colors("dark", &[
+            ("background", Color::rgb(0.15, 0.15, 0.15)),
+            ("surface", Color::rgb(0.1, 0.1, 0.1)),
+            ("header", Color::rgb(0.2, 0.2, 0.2)),
+            ("border", Color::rgb(0.3, 0.3, 0.3)),
+            ("text", Color::WHITE),
+            ("text-muted", Color::rgb(0.5, 0.5, 0.5)),
+            ("primary", Color::rgb(0.25, 0.5, 0.85)),
+            ("primary-hover", Color::rgb(0.32, 0.58, 0.92)),
+            ("primary-pressed", Color::rgb(0.2, 0.42, 0.75)),
+            ("on-primary", Color::WHITE),
+            ("danger", Color::rgb(0.8, 0.2, 0.2)),
+            ("on-danger", Color::WHITE),
+            ("input", Color::rgb(0.2, 0.2, 0.2)),
+            ("canvas", Color::rgb(0.9, 0.9, 0.9)),
+            ("disabled", Color::rgb(0.3, 0.3, 0.3)),
+        ])
+    }
+
+    pub fn light() -> Self {
+        Self::with_colors("light", &[
+            ("background", Color::rgb(0.94, 0.94, 0.94)),
+            ("surface", Color::rgb(0.98, 0.98, 0.98)),
+            ("header", Color::rgb(0.85, 0.85, 0.87)),
+            ("border", Color::rgb(0.75, 0.75, 0.75)),
+            ("text", Color::rgb(0.1, 0.1, 0.1)),
+            ("text-muted", Color::rgb(0.45, 0.45, 0.45)),
+            ("primary", Color::rgb(0.16, 0.42, 0.8)),
+            ("primary-hover", Color::rgb(0.22, 0.5, 0.88)),
+            ("primary-pressed", Color::rgb(0.12, 0.34, 0.68)),
+            ("on-primary", Color::WHITE),
+            ("danger", Color::rgb(0.85, 0.25, 0.25)),
+            ("on-danger", Color::WHITE),
+            ("input", Color::WHITE),
+            ("canvas", Color::WHITE),
+            ("disabled", Color::rgb(0.8, 0.8, 0.8)),
+        ])
+    }
+
+    /// Tailles, espacements, rayons et polices communs aux thèmes intégrés
+    fn with_colors(name: &str, colors: &[(&str, Color)]) -> Self {
+        let numbers = |entries: &[(&str, f32)]| entries.iter().map(|(name, value)| (name.to_string(), *value)).collect();
+        Self {
+            name: name.to_string(),
+            colors: colors.iter().map(|(name, color)| (name.to_string(), *color)).collect(),
+            font_sizes: numbers(&[("small", 14.0), ("body", 16.0), ("title", 16.0), ("large", 20.0)]),
+            spacing: numbers(&[("xs", 2.0), ("sm", 4.0), ("md", 8.0), ("lg", 10.0), ("xl", 16.0)]),
+            radii: numbers(&[("sm", 2.0), ("md", 4.0), ("lg", 8.0)]),
+            fonts: [("regular", "fonts/FiraSans-Regular.ttf"), ("bold", "fonts/FiraSans-Bold.ttf")]
+                .iter()
+                .map(|(name, path)| (name.to_string(), path.to_string()))
+                .collect(),
+        }
+    }
+
+    pub fn color(&self, name: &str) -> Option<Color> {
+        self.colors.get(name).copied()
+    }
+
+    pub fn font_size(&self, name: &str) -> Option<f32> {
+        self.font_sizes.get(name).copied()
+    }
+
+    pub fn font(&self, name: &str) -> Option<&str> {
+        self.fonts.get(name).map(String::as_str)
+    }
+
+    /// Valeur textuelle d'un jeton `catégorie.nom`, pour les styles ; les couleurs
+    /// passent par les `ColorTokens`
+    pub fn lookup(&self, token: &str) -> Option<String> {
+        let (category, name) = token.split_once('.')?;
+        match category {
+            "font_size" | "size" => self.font_size(name).map(|size| size.to_string()),
+            "spacing" => self.spacing.get(name).map(|spacing| spacing.to_string()),
+            "radius" => self.radii.get(name).map(|radius| radius.to_string()),
+            "font" => self.font(name).map(str::to_string),
+            _ => None,
+        }
+    }
+
+    /// Ajoute ou remplace les jetons d'un autre thème
+    fn extend(&mut self, other: Theme) {
+        self.colors.extend(other.colors);
+        self.font_sizes.extend(other.font_sizes);
+        self.spacing.extend(other.spacing);
+        self.radii.extend(other.radii);
+        self.fonts.extend(other.fonts);
+    }
+}
+
+/// Thème déclaré par un script ou un fichier JSON, résolu par `Themes::define`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThemeDefinition {
+    pub name: String,
+    /// Thème complété ; le thème sombre par défaut
+    pub extends: Option<String>,
+    /// Jetons propres à la définition
+    pub tokens: Theme,
+}
+
+impl ThemeDefinition {
+    /// Lit une définition JSON :
+    /// `{ "name", "extends", "colors", "font_sizes", "spacing", "radii", "fonts" }`.
+    ///
+    /// Les jetons invalides sont ignorés et décrits dans la liste d'erreurs retournée.
+    pub fn from_json(value: &serde_json::Value) -> Result<(Self, Vec<String>), String> {
+        let name = match &value["name"] {
+            serde_json::Value::String(name) if is_token_name(name) => name.clone(),
+            serde_json::Value::Null => return Err("thème sans nom".to_string()),
+            other => return Err(format!("nom de thème invalide: {}", other)),
+        };
+        let extends = value["extends"].as_str().map(str::to_string);
+        let mut tokens = Theme::empty(name.clone());
+        let mut errors = Vec::new();
+
+        for (category, entries) in value.as_object().into_iter().flatten() {
+            // `location` est un hash, ajouté par le DSL pour situer les erreurs
+            if matches!(category.as_str(), "name" | "extends" | "location") {
+                continue;
+            }
+            let Some(entries) = entries.as_object() else {
+                errors.push(format!("`{}` doit être un hash de jetons", category));
+                continue;
+            };
+            for (token, entry) in entries {
+                if !is_token_name(token) {
+                    errors.push(format!("nom de jeton invalide `{}`", token));
+                    continue;
+                }
+                let added = match category.as_str() {
+                    "colors" => entry
+                        .as_str()
+                        .ok_or_else(|| "texte attendu".to_string())
+                        .and_then(parse_color)
+                        .map(|color| { tokens.colors.insert(token.clone(), color); }),
+                    "font_sizes" => number(entry).map(|size| { tokens.font_sizes.insert(token.clone(), size); }),
+                    "spacing" => number(entry).map(|spacing| { tokens.spacing.insert(token.clone(), spacing); }),
+                    "radii" => number(entry).map(|radius| { tokens.radii.insert(token.clone(), radius); }),
+                    "fonts" => entry
+                        .as_str()
+                        .map(|path| { tokens.fonts.insert(token.clone(), path.to_string()); })
+                        .ok_or_else(|| "chemin attendu".to_string()),
+                    other => {
+                        errors.push(format!("catégorie de jetons inconnue `{}`", other));
+                        break;
+                    },
+                };
+                if let Err(reason) = added {
+                    errors.push(format!("jeton `{}.{}` ignoré: {} ({})", category, token, reason, entry));
+                }
+            }
+        }
+        Ok((Self { name, extends, tokens }, errors))
+    }
+}
+
+fn number(value: &serde_json::Value) -> Result<f32, String> {
+    match value {
+        serde_json::Value::Number(number) => number.as_f64().map(|number| number as f32).ok_or_else(|| "nombre attendu".to_string()),
+        serde_json::Value::String(text) => text.trim().trim_end_matches("px").parse().map_err(|_| "nombre attendu".to_string()),
+        _ => Err("nombre attendu".to_string()),
+    }
+}
+
+fn is_token_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// Thèmes connus et nom du thème actif
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct Themes {
+    themes: HashMap<String, Theme>,
+    active: String,
+}
+
+impl Default for Themes {
+    fn default() -> Self {
+        Self {
+            themes: [Theme::dark(), Theme::light()].into_iter().map(|theme| (theme.name.clone(), theme)).collect(),
+            active: DEFAULT_THEME.to_string(),
+        }
+    }
+}
+
+impl Themes {
+    pub fn active(&self) -> &Theme {
+        self.themes.get(&self.active).unwrap_or_else(|| &self.themes[DEFAULT_THEME])
+    }
+
+    pub fn set_active(&mut self, name: &str) -> Result<(), String> {
+        if !self.themes.contains_key(name) {
+            return Err(format!("thème inconnu `{}` (thèmes connus: {})", name, self.names().join(", ")));
+        }
+        self.active = name.to_string();
+        Ok(())
+    }
+
+    /// Ajoute ou remplace un thème, complété par celui qu'il étend
+    pub fn define(&mut self, definition: ThemeDefinition) -> Result<(), String> {
+        let base = definition.extends.as_deref().unwrap_or(DEFAULT_THEME);
+        let mut theme = self
+            .themes
+            .get(base)
+            .cloned()
+            .ok_or_else(|| format!("le thème `{}` étend un thème inconnu `{}`", definition.name, base))?;
+        theme.extend(definition.tokens);
+        theme.name = definition.name.clone();
+        self.themes.insert(definition.name, theme);
+        Ok(())
+    }
+
+    /// Remplace les thèmes déclarés par le script, dans l'ordre de déclaration ; le
+    /// thème actif est conservé s'il existe encore
+    pub fn reload(&mut self, definitions: Vec<ThemeDefinition>, active: Option<&str>) -> Vec<String> {
+        let mut reloaded = Themes {
+            active: self.active.clone(),
+            ..Themes::default()
+        };
+        let mut errors: Vec<String> = definitions
+            .into_iter()
+            .filter_map(|definition| reloaded.define(definition).err())
+            .collect();
+        if let Some(active) = active {
+            if let Err(error) = reloaded.set_active(active) {
+                errors.push(error);
+            }
+        }
+        if !reloaded.themes.contains_key(&reloaded.active) {
+            reloaded.active = DEFAULT_THEME.to_string();
+        }
+        *self = reloaded;
+        errors
+    }
+
+    fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.themes.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        names
+    }
+}
+
+/// Reporte le thème actif dans `Theme` et ses couleurs dans `ColorTokens` ; l'UI
+/// n'est recalculée que si le thème a réellement changé
+pub fn sync_theme_system(themes: Res<Themes>, mut theme: ResMut<Theme>, mut tokens: ResMut<ColorTokens>) {
+    if !themes.is_changed() {
+        return;
+    }
+    let active = themes.active();
+    tokens.set_if_neq(ColorTokens(active.colors.clone()));
+    theme.set_if_neq(active.clone());
+}
+
+/// Apparence par défaut des composants, avant les feuilles de style du script
+const BASE_RULES: &[(&str, &[(&str, &str)])] = &[
+    ("window", &[("background", "$background"), ("border_color", "$border")]),
+    ("button", &[
+        ("background", "$primary"), ("color", "$on-primary"), ("font_size", "$font_size.body"),
+        ("font", "$font.regular"), ("padding", "$spacing.md"), ("margin", "$spacing.sm"),
+    ]),
+    ("button:hover", &[("background", "$primary-hover")]),
+    ("button:pressed", &[("background", "$primary-pressed")]),
+    ("button:disabled", &[("background", "$disabled"), ("color", "$text-muted")]),
+    ("text", &[("color", "$text"), ("font_size", "$font_size.body"), ("font", "$font.regular"), ("margin", "$spacing.sm")]),
+    ("input", &[("background", "$input"), ("border_color", "$border"), ("padding", "$spacing.sm"), ("margin", "$spacing.sm")]),
+    ("scrollview", &[("background", "$background"), ("margin", "$spacing.sm")]),
+    ("canvas", &[("background", "$canvas"), ("margin", "$spacing.sm")]),
+    ("viewport3d", &[("background", "$surface"), ("margin", "$spacing.sm")]),
+    ("image", &[("margin", "$spacing.sm")]),
+    ("svg", &[("margin", "$spacing.sm")]),
+];
+
+/// Feuille de style de base, écrite avec les jetons du thème
+#[derive(Resource, Debug, Clone)]
+pub struct BaseStylesheet(pub Stylesheet);
+
+impl Default for BaseStylesheet {
+    fn default() -> Self {
+        let rules = BASE_RULES
+            .iter()
+            .map(|(selector, style)| {
+                let style: HashMap<String, String> = style.iter().map(|(key, value)| (key.to_string(), value.to_string())).collect();
+                StyleRule {
+                    selector: Selector::parse(selector).expect("sélecteur de base invalide"),
+                    style: ResolvedStyle::resolve(&style).0,
+                }
+            })
+            .collect();
+        Self(Stylesheet { rules })
+    }
+}
+
+/// Pièce interne d'un composant (en-tête de fenêtre, bouton de fermeture...) dont
+/// l'apparence suit les jetons du thème
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct Themed {
+    pub background: Option<&'static str>,
+    pub text: Option<&'static str>,
+    pub font_size: Option<&'static str>,
+    pub font: Option<&'static str>,
+}
+
+impl Themed {
+    pub fn background(color: &'static str) -> Self {
+        Self {
+            background: Some(color),
+            ..default()
+        }
+    }
+
+    pub fn text(color: &'static str, font_size: &'static str, font: &'static str) -> Self {
+        Self {
+            text: Some(color),
+            font_size: Some(font_size),
+            font: Some(font),
+            ..default()
+        }
+    }
+
+    /// Couleur, taille et police de texte du thème ; `None` pour ce que la pièce ne
+    /// suit pas ou que le thème ne définit pas
+    pub fn text_style(&self, theme: &Theme, asset_server: &AssetServer) -> (Option<Color>, Option<f32>, Option<Handle<Font>>) {
+        (
+            self.text.and_then(|color| theme.color(color)),
+            self.font_size.and_then(|size| theme.font_size(size)),
+            self.font.and_then(|font| theme.font(font)).map(|path| asset_server.load(path.to_string())),
+        )
+    }
+}
+
+/// Applique le thème aux pièces internes nouvellement créées, ou à toutes quand le
+/// thème change
+pub fn apply_themed_system(
+    theme: Res<Theme>,
+    asset_server: Res<AssetServer>,
+    mut parts: Query<(Ref<Themed>, Option<&mut BackgroundColor>, Option<&mut Text>)>,
+) {
+    for (themed, background, text) in &mut parts {
+        if !theme.is_changed() && !themed.is_added() {
+            continue;
+        }
+        if let (Some(color), Some(mut background)) = (themed.background.and_then(|color| theme.color(color)), background) {
+            background.0 = color;
+        }
+        let Some(mut text) = text else {
+            continue;
+        };
+        let (color, font_size, font) = themed.text_style(&theme, &asset_server);
+        for section in &mut text.sections {
+            if let Some(color) = color {
+                section.style.color = color;
+            }
+            if let Some(font_size) = font_size {
+                section.style.font_size = font_size;
+            }
+            if let Some(font) = &font {
+                section.style.font = font.clone();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn definition(value: serde_json::Value) -> ThemeDefinition {
+        let (definition, errors) = ThemeDefinition::from_json(&value).unwrap();
+        assert!(errors.is_empty(), "{:?}", errors);
+        definition
+    }
+
+    #[test]
+    fn reads_json_definitions() {
+        let definition = definition(json!({
+            "name": "ocean",
+            "extends": "light",
+            "colors": { "primary": "#1478c8" },
+            "font_sizes": { "title": 20 },
+            "spacing": { "md": "10px" },
+            "radii": { "sm": 3.5 },
+            "fonts": { "bold": "fonts/Ocean-Bold.ttf" },
+            "location": { "file": "scripts/ui.rb", "line": 3 },
+        }));
+
+        assert_eq!(definition.name, "ocean");
+        assert_eq!(definition.extends.as_deref(), Some("light"));
+        assert_eq!(definition.tokens.color("primary"), Some(Color::rgb_u8(0x14, 0x78, 0xc8)));
+        assert_eq!(definition.tokens.font_size("title"), Some(20.0));
+        assert_eq!(definition.tokens.lookup("spacing.md").as_deref(), Some("10"));
+        assert_eq!(definition.tokens.lookup("radius.sm").as_deref(), Some("3.5"));
+        assert_eq!(definition.tokens.font("bold"), Some("fonts/Ocean-Bold.ttf"));
+    }
+
+    #[test]
+    fn reports_invalid_tokens_and_keeps_the_others() {
+        let (definition, errors) = ThemeDefinition::from_json(&json!({
+            "name": "broken",
+            "colors": { "primary": "blurple", "accent": "red", "bad name": "blue" },
+            "spacing": { "md": true },
+            "shadows": { "sm": 2 },
+            "fonts": "fonts/Broken.ttf",
+        }))
+        .unwrap();
+
+        assert_eq!(definition.tokens.colors.len(), 1);
+        assert_eq!(definition.tokens.color("accent"), Some(Color::rgb_u8(255, 0, 0)));
+        assert!(definition.tokens.spacing.is_empty());
+        assert_eq!(errors.len(), 5, "{:?}", errors);
+    }
+
+    #[test]
+    fn requires_a_valid_name() {
+        assert_eq!(ThemeDefinition::from_json(&json!({ "colors": {} })).unwrap_err(), "thème sans nom");
+        assert!(ThemeDefinition::from_json(&json!({ "name": "mon thème" })).is_err());
+        assert!(ThemeDefinition::from_json(&json!({ "name": 3 })).is_err());
+    }
+
+    #[test]
+    fn definitions_extend_their_base_theme() {
+        let mut themes = Themes::default();
+        themes
+            .define(definition(json!({ "name": "ocean", "extends": "light", "colors": { "primary": "#1478c8" } })))
+            .unwrap();
+        themes.define(definition(json!({ "name": "night", "spacing": { "md": 12 } }))).unwrap();
+        themes.set_active("ocean").unwrap();
+
+        let ocean = themes.active();
+        assert_eq!(ocean.name, "ocean");
+        assert_eq!(ocean.color("primary"), Some(Color::rgb_u8(0x14, 0x78, 0xc8)));
+        assert_eq!(ocean.color("background"), Theme::light().color("background"));
+
+        themes.set_active("night").unwrap();
+        assert_eq!(themes.active().lookup("spacing.md").as_deref(), Some("12"));
+        assert_eq!(themes.active().color("background"), Theme::dark().color("background"));
+
+        let error = themes.define(definition(json!({ "name": "coral", "extends": "reef" }))).unwrap_err();
+        assert_eq!(error, "le thème `coral` étend un thème inconnu `reef`");
+        assert!(themes.set_active("coral").is_err());
+    }
+
+    #[test]
+    fn reload_replaces_script_themes() {
+        let mut themes = Themes::default();
+        themes.define(definition(json!({ "name": "ocean" }))).unwrap();
+        themes.define(definition(json!({ "name": "sand" }))).unwrap();
+        themes.set_active("sand").unwrap();
+
+        // Le thème actif disparaît : retour au thème par défaut
+        let errors = themes.reload(vec![definition(json!({ "name": "ocean" }))], None);
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert_eq!(themes.active().name, DEFAULT_THEME);
+        assert!(themes.set_active("sand").is_err());
+
+        themes.set_active("ocean").unwrap();
+        let errors = themes.reload(
+            vec![definition(json!({ "name": "ocean" })), definition(json!({ "name": "reef", "extends": "coral" }))],
+            Some("coral"),
+        );
+        assert_eq!(errors.len(), 2, "{:?}", errors);
+        assert_eq!(themes.active().name, "ocean");
+    }
+}
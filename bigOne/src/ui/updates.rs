@@ -0,0 +1,487 @@
+// src/ui/updates.rs
+//! Application des `UIUpdateEvent` à l'ECS.
+//!
+//! Les changements de structure passent d'abord par le builder ; les propriétés
+//! sont ensuite appliquées par un système par famille de composants Bevy, une fois
+//! les entités construites dans la même frame réellement créées. Les styles sont
+//! recalculés en dernier, avec les classes, les états et le thème modifiés dans la frame.
+//!
+//! L'ordre des mises à jour est respecté : une propriété modifiée avant le remplacement
+//! de son composant dans la même frame est écartée, le remplaçant portant déjà sa valeur.
+use bevy::prelude::*;
+use std::collections::HashSet;
+
+use crate::dsl::worker::RubyRequest;
+use crate::ui::builder::UIBuilder;
+use crate::ui::canvas::CanvasSurface;
+use crate::ui::color::parse_color;
+use crate::ui::components::*;
+use crate::ui::registry::{despawn_subtree, UIRegistry};
+use crate::ui::style::{InlineStyle, ResolvedStyle};
+use crate::ui::stylesheet::restyle_system;
+use crate::ui::svg::SvgImage;
+use crate::ui::theme::{apply_themed_system, sync_theme_system, Themes};
+use crate::ui::systems::UIDSLState;
+use crate::ui::viewport3d::{euler_rotation, patch_material};
+
+/// Mises à jour de propriétés de la frame rendues caduques par un remplacement ultérieur
+#[derive(Resource, Default)]
+struct SupersededUpdates(HashSet<usize>);
+
+/// Ajoute les systèmes de mise à jour de l'UI
+pub fn add_update_systems(app: &mut App) {
+    app.init_resource::<SupersededUpdates>();
+    app.add_systems(Update, (
+        apply_structure_updates,
+        (
+            apply_text_updates,
+            apply_image_updates,
+            apply_canvas_updates,
+            apply_layout_updates,
+            apply_visibility_updates,
+            apply_style_updates,
+            apply_state_updates,
+            apply_theme_updates,
+            apply_scene_updates,
+        ),
+        sync_theme_system,
+        apply_themed_system,
+        restyle_system,
+    ).chain());
+}
+
+/// Remplacements, insertions, suppressions, fenêtres et focus
+fn apply_structure_updates(
+    mut commands: Commands,
+    mut events: EventReader<UIUpdateEvent>,
+    mut registry: ResMut<UIRegistry>,
+    mut dsl_state: ResMut<UIDSLState>,
+    mut superseded: ResMut<SupersededUpdates>,
+    asset_server: Res<AssetServer>,
+) {
+    superseded.0.clear();
+    // Mises à jour de propriétés déjà lues : (événement, composant visé)
+    let mut properties: Vec<(usize, &str)> = Vec::new();
+
+    for (event, event_id) in events.iter_with_id() {
+        if let UIComponentUpdate::Replace(component) = &event.update {
+            let rebuilt: HashSet<String> = component.subtree_ids().into_iter().collect();
+            superseded.0.extend(
+                properties.iter().filter(|(_, target)| rebuilt.contains(*target)).map(|(index, _)| *index),
+            );
+        }
+
+        let result = match &event.update {
+            UIComponentUpdate::Replace(component) => replace_component(&mut commands, &mut registry, &asset_server, &event.id, component),
+            UIComponentUpdate::InsertChild { component, index } => insert_child(&mut commands, &mut registry, &asset_server, &event.id, component, *index),
+            UIComponentUpdate::Remove | UIComponentUpdate::CloseWindow => remove_entity(&mut commands, &mut registry, &event.id),
+            UIComponentUpdate::OpenWindow(window) => open_window(&mut commands, &mut registry, &asset_server, &event.id, window),
+            UIComponentUpdate::Focus => focus(&mut commands, &mut registry, &event.id),
+            _ => {
+                properties.push((event_id.id, &event.id));
+                continue;
+            },
+        };
+        if let Err(error) = result {
+            error!("Erreur de mise à jour UI: {}", error);
+            continue;
+        }
+
+        // Reporter les changements de structure dans l'arbre de référence, pour que
+        // le prochain rechargement réconcilie l'UI réellement affichée
+        let released = mirror_structure(&mut dsl_state, &event.id, &event.update);
+        if released.is_empty() {
+            continue;
+        }
+        registry.forget(&released);
+        if let Some(worker) = dsl_state.worker.as_mut() {
+            for id in released {
+                worker.send(RubyRequest::Release { id });
+            }
+        }
+    }
+}
+
+/// Reconstruit un sous-arbre à la place de l'ancien, chez le même parent et au même rang
+fn replace_component(commands: &mut Commands, registry: &mut UIRegistry, asset_server: &Res<AssetServer>, id: &str, component: &UIComponent) -> Result<(), String> {
+    let old_entity = registry.get(id).ok_or_else(|| not_found(id))?;
+    registry.forget(&[id.to_string()]);
+    let new_entity = UIBuilder::new(registry)
+        .build_component(commands, component, None, asset_server)
+        .ok_or_else(|| format!("Construction du remplaçant de '{}' impossible", id))?;
+
+    // Le rang est lu au moment de l'échange : les commandes précédentes de la frame
+    // (insertions, remplacements de frères) sont déjà appliquées
+    commands.add(move |world: &mut World| {
+        let parent = world.get::<Parent>(old_entity).map(|parent| parent.get());
+        if let Some(parent) = parent {
+            let index = world
+                .get::<Children>(parent)
+                .and_then(|children| children.iter().position(|child| *child == old_entity))
+                .unwrap_or(0);
+            world.entity_mut(parent).insert_children(index, &[new_entity]);
+        }
+        despawn_subtree(world, old_entity);
+    });
+    Ok(())
+}
+
+/// Ajoute un enfant construit à la position donnée, ou à la fin
+fn insert_child(commands: &mut Commands, registry: &mut UIRegistry, asset_server: &Res<AssetServer>, parent_id: &str, component: &UIComponent, index: Option<usize>) -> Result<(), String> {
+    if registry.grid_rows.contains_key(parent_id) {
+        return Err(format!("Insertion dans la grille '{}' non supportée : remplacez la grille", parent_id));
+    }
+    let container = registry.container(parent_id).ok_or_else(|| not_found(parent_id))?;
+    let child = UIBuilder::new(registry)
+        .build_component(commands, component, None, asset_server)
+        .ok_or_else(|| format!("Construction de l'enfant de '{}' impossible", parent_id))?;
+
+    match index {
+        Some(index) => {
+            // L'index est borné au nombre d'enfants existants au moment de l'insertion
+            commands.add(move |world: &mut World| {
+                let count = world.get::<Children>(container).map_or(0, |children| children.len());
+                world.entity_mut(container).insert_children(index.min(count), &[child]);
+            });
+        },
+        None => {
+            commands.entity(container).push_children(&[child]);
+        },
+    }
+    Ok(())
+}
+
+fn remove_entity(commands: &mut Commands, registry: &mut UIRegistry, id: &str) -> Result<(), String> {
+    let entity = registry.get(id).ok_or_else(|| not_found(id))?;
+    commands.add(move |world: &mut World| despawn_subtree(world, entity));
+    Ok(())
+}
+
+fn open_window(commands: &mut Commands, registry: &mut UIRegistry, asset_server: &Res<AssetServer>, id: &str, window: &UIComponent) -> Result<(), String> {
+    if registry.get(id).is_some() {
+        return Err(format!("La fenêtre '{}' est déjà ouverte", id));
+    }
+    UIBuilder::new(registry)
+        .build_component(commands, window, None, asset_server)
+        .map(|_| ())
+        .ok_or_else(|| format!("Ouverture de la fenêtre '{}' impossible", id))
+}
+
+/// Déplace le marqueur `Focused` et passe la fenêtre du composant au premier plan
+fn focus(commands: &mut Commands, registry: &mut UIRegistry, id: &str) -> Result<(), String> {
+    let entity = registry.get(id).ok_or_else(|| not_found(id))?;
+    if let Some(previous) = registry.focused.replace(entity) {
+        // L'entité précédente a pu être détruite entre-temps
+        if previous != entity {
+            if let Some(mut previous) = commands.get_entity(previous) {
+                previous.remove::<Focused>();
+            }
+        }
+    }
+    commands.entity(entity).insert(Focused);
+
+    registry.focus_order += 1;
+    let order = registry.focus_order;
+    commands.add(move |world: &mut World| {
+        let mut root = entity;
+        while let Some(parent) = world.get::<Parent>(root) {
+            root = parent.get();
+        }
+        world.entity_mut(root).insert(ZIndex::Global(order));
+    });
+    Ok(())
+}
+
+/// Applique une mise à jour structurelle à l'arbre de la dernière évaluation ;
+/// retourne les IDs des composants qui n'existent plus
+fn mirror_structure(dsl_state: &mut UIDSLState, id: &str, update: &UIComponentUpdate) -> Vec<String> {
+    let Some(eval) = dsl_state.last_eval.as_mut() else {
+        return Vec::new();
+    };
+    let tree = &mut eval.components;
+
+    match update {
+        UIComponentUpdate::Replace(component) => {
+            let Some(target) = find_component_mut(tree, id) else {
+                return Vec::new();
+            };
+            let kept: HashSet<String> = component.subtree_ids().into_iter().collect();
+            let released = target.subtree_ids().into_iter().filter(|id| !kept.contains(id)).collect();
+            *target = component.clone();
+            released
+        },
+        UIComponentUpdate::InsertChild { component, index } => {
+            if let Some(children) = find_component_mut(tree, id).and_then(|parent| parent.children_mut()) {
+                let index = index.unwrap_or(children.len()).min(children.len());
+                children.insert(index, component.clone());
+            }
+            Vec::new()
+        },
+        UIComponentUpdate::OpenWindow(component) => {
+            tree.push(component.clone());
+            Vec::new()
+        },
+        UIComponentUpdate::Remove | UIComponentUpdate::CloseWindow => {
+            remove_component(tree, id).map(|removed| removed.subtree_ids()).unwrap_or_default()
+        },
+        _ => Vec::new(),
+    }
+}
+
+/// Texte du composant : le sien, ou celui de son premier descendant qui en porte un
+/// (libellé d'un bouton, valeur d'un champ, titre d'une fenêtre)
+fn apply_text_updates(
+    mut events: EventReader<UIUpdateEvent>,
+    superseded: Res<SupersededUpdates>,
+    registry: Res<UIRegistry>,
+    mut texts: Query<&mut Text>,
+    children: Query<&Children>,
+) {
+    for event in pending(&mut events, &superseded) {
+        let UIComponentUpdate::SetText(value) = &event.update else {
+            continue;
+        };
+        let Some(entity) = registry.get(&event.id) else {
+            warn!("{}", not_found(&event.id));
+            continue;
+        };
+
+        let target = std::iter::once(entity)
+            .chain(children.iter_descendants(entity))
+            .find(|candidate| texts.contains(*candidate));
+        match target.and_then(|target| texts.get_mut(target).ok()) {
+            Some(mut text) => {
+                if let Some(section) = text.sections.first_mut() {
+                    section.value = value.clone();
+                }
+            },
+            None => warn!("Le composant '{}' n'affiche pas de texte", event.id),
+        }
+    }
+}
+
+fn apply_image_updates(
+    mut events: EventReader<UIUpdateEvent>,
+    superseded: Res<SupersededUpdates>,
+    registry: Res<UIRegistry>,
+    asset_server: Res<AssetServer>,
+    mut images: Query<(&mut UiImage, Option<&mut SvgImage>)>,
+) {
+    for event in pending(&mut events, &superseded) {
+        let UIComponentUpdate::SetImage(source) = &event.update else {
+            continue;
+        };
+        let Some(entity) = registry.get(&event.id) else {
+            warn!("{}", not_found(&event.id));
+            continue;
+        };
+        match images.get_mut(entity) {
+            // Un SVG garde sa texture, que `rasterize_svg_system` remplit
+            Ok((_, Some(mut svg))) => svg.handle = asset_server.load(source.clone()),
+            Ok((mut image, None)) => image.texture = asset_server.load(source.clone()),
+            Err(_) => warn!("Le composant '{}' n'affiche pas d'image", event.id),
+        }
+    }
+}
+
+/// Invalidation des canevas
+fn apply_canvas_updates(
+    mut events: EventReader<UIUpdateEvent>,
+    superseded: Res<SupersededUpdates>,
+    registry: Res<UIRegistry>,
+    mut canvases: Query<&mut CanvasSurface>,
+) {
+    for event in pending(&mut events, &superseded) {
+        let UIComponentUpdate::Invalidate = &event.update else {
+            continue;
+        };
+        let Some(entity) = registry.get(&event.id) else {
+            warn!("{}", not_found(&event.id));
+            continue;
+        };
+        match canvases.get_mut(entity) {
+            Ok(mut canvas) => canvas.invalidate(),
+            Err(_) => warn!("Le composant '{}' n'est pas un canevas", event.id),
+        }
+    }
+}
+
+/// Position et taille
+fn apply_layout_updates(
+    mut events: EventReader<UIUpdateEvent>,
+    superseded: Res<SupersededUpdates>,
+    registry: Res<UIRegistry>,
+    mut styles: Query<&mut Style>,
+) {
+    for event in pending(&mut events, &superseded) {
+        if !matches!(event.update, UIComponentUpdate::SetPosition(..) | UIComponentUpdate::SetSize(..)) {
+            continue;
+        }
+        let Some(mut style) = registry.get(&event.id).and_then(|entity| styles.get_mut(entity).ok()) else {
+            warn!("{}", not_found(&event.id));
+            continue;
+        };
+        match event.update {
+            UIComponentUpdate::SetPosition(x, y) => {
+                style.left = Val::Px(x);
+                style.top = Val::Px(y);
+            },
+            UIComponentUpdate::SetSize(width, height) => {
+                style.width = Val::Px(width);
+                style.height = Val::Px(height);
+            },
+            _ => {},
+        }
+    }
+}
+
+fn apply_visibility_updates(
+    mut events: EventReader<UIUpdateEvent>,
+    superseded: Res<SupersededUpdates>,
+    registry: Res<UIRegistry>,
+    mut visibilities: Query<&mut Visibility>,
+) {
+    for event in pending(&mut events, &superseded) {
+        let UIComponentUpdate::SetVisible(visible) = event.update else {
+            continue;
+        };
+        let Some(mut visibility) = registry.get(&event.id).and_then(|entity| visibilities.get_mut(entity).ok()) else {
+            warn!("{}", not_found(&event.id));
+            continue;
+        };
+        *visibility = if visible { Visibility::Inherited } else { Visibility::Hidden };
+    }
+}
+
+/// Les propriétés reçues complètent le style propre du composant
+fn apply_style_updates(
+    mut events: EventReader<UIUpdateEvent>,
+    superseded: Res<SupersededUpdates>,
+    registry: Res<UIRegistry>,
+    mut inline_styles: Query<&mut InlineStyle>,
+) {
+    for event in pending(&mut events, &superseded) {
+        let UIComponentUpdate::SetStyle(style_map) = &event.update else {
+            continue;
+        };
+        let Some(mut inline) = registry.get(&event.id).and_then(|entity| inline_styles.get_mut(entity).ok()) else {
+            warn!("{}", not_found(&event.id));
+            continue;
+        };
+
+        let (style, errors) = ResolvedStyle::resolve(style_map);
+        for error in errors {
+            warn!("Style de '{}': {}", event.id, error);
+        }
+        inline.0.merge(style);
+    }
+}
+
+/// Classes et désactivation, suivies par les feuilles de style
+fn apply_state_updates(
+    mut commands: Commands,
+    mut events: EventReader<UIUpdateEvent>,
+    superseded: Res<SupersededUpdates>,
+    registry: Res<UIRegistry>,
+    mut classes: Query<&mut StyleClasses>,
+) {
+    for event in pending(&mut events, &superseded) {
+        if !matches!(event.update, UIComponentUpdate::SetClasses(_) | UIComponentUpdate::SetDisabled(_)) {
+            continue;
+        }
+        let Some(entity) = registry.get(&event.id) else {
+            warn!("{}", not_found(&event.id));
+            continue;
+        };
+        match &event.update {
+            UIComponentUpdate::SetClasses(names) => match classes.get_mut(entity) {
+                Ok(mut current) => current.0 = names.clone(),
+                Err(_) => warn!("{}", not_found(&event.id)),
+            },
+            UIComponentUpdate::SetDisabled(true) => {
+                commands.entity(entity).insert(Disabled);
+            },
+            UIComponentUpdate::SetDisabled(false) => {
+                commands.entity(entity).remove::<Disabled>();
+            },
+            _ => {},
+        }
+    }
+}
+
+/// Définition et changement de thème
+fn apply_theme_updates(mut events: EventReader<UIUpdateEvent>, mut themes: ResMut<Themes>) {
+    for event in events.iter() {
+        let result = match &event.update {
+            UIComponentUpdate::SetTheme(name) => themes.set_active(name),
+            UIComponentUpdate::DefineTheme(definition) => themes.define(definition.clone()),
+            _ => continue,
+        };
+        if let Err(error) = result {
+            warn!("Thème: {}", error);
+        }
+    }
+}
+
+/// Transformation et matériau des nœuds 3D ; la couleur d'un matériau est aussi
+/// celle d'une lumière
+fn apply_scene_updates(
+    mut events: EventReader<UIUpdateEvent>,
+    superseded: Res<SupersededUpdates>,
+    registry: Res<UIRegistry>,
+    asset_server: Res<AssetServer>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut nodes: Query<(&mut Transform, Option<&Handle<StandardMaterial>>, Option<&mut PointLight>, Option<&mut DirectionalLight>), With<SceneNode>>,
+) {
+    for event in pending(&mut events, &superseded) {
+        if !matches!(event.update, UIComponentUpdate::SetTransform { .. } | UIComponentUpdate::SetMaterial(_)) {
+            continue;
+        }
+        let Some(entity) = registry.get(&event.id) else {
+            warn!("{}", not_found(&event.id));
+            continue;
+        };
+        let Ok((mut transform, material, point_light, directional_light)) = nodes.get_mut(entity) else {
+            warn!("Le composant '{}' n'est pas un nœud 3D", event.id);
+            continue;
+        };
+        match &event.update {
+            UIComponentUpdate::SetTransform { position, rotation, scale } => {
+                if let Some(position) = position {
+                    transform.translation = Vec3::from(*position);
+                }
+                if let Some(rotation) = rotation {
+                    transform.rotation = euler_rotation(*rotation);
+                }
+                if let Some(scale) = scale {
+                    transform.scale = Vec3::from(*scale);
+                }
+            },
+            UIComponentUpdate::SetMaterial(props) => {
+                if let Some(material) = material.and_then(|handle| materials.get_mut(handle)) {
+                    patch_material(material, props, &asset_server);
+                }
+                let color = props.color.as_deref().and_then(|color| parse_color(color).ok());
+                if let (Some(color), Some(mut light)) = (color, point_light) {
+                    light.color = color;
+                }
+                if let (Some(color), Some(mut light)) = (color, directional_light) {
+                    light.color = color;
+                }
+            },
+            _ => {},
+        }
+    }
+}
+
+/// Mises à jour de la frame, sans celles qu'un remplacement ultérieur a rendues caduques
+fn pending<'a>(events: &'a mut EventReader<'_, '_, UIUpdateEvent>, superseded: &'a SupersededUpdates) -> impl Iterator<Item = &'a UIUpdateEvent> {
+    events
+        .iter_with_id()
+        .filter(move |(_, event_id)| !superseded.0.contains(&event_id.id))
+        .map(|(event, _)| event)
+}
+
+fn not_found(id: &str) -> String {
+    format!("Composant avec ID '{}' non trouvé", id)
+}
@@ -0,0 +1,483 @@
+// src/ui/viewport3d.rs
+//! Viewports 3D : chaque `viewport3d` possède une caméra qui rend sa scène dans
+//! une image, affichée par son nœud d'UI.
+//!
+//! L'image suit la taille calculée du nœud multipliée par le facteur d'échelle de
+//! la fenêtre ; elle est découpée par les fenêtres et les vues défilantes comme
+//! toute image d'UI. Le contenu de chaque viewport est placé sur sa propre couche
+//! de rendu (`RenderLayers`) : plusieurs viewports affichent des scènes isolées.
+//!
+//! ```ruby
+//! viewport3d(id: "robot", scene: "models/robot.glb#Scene0", auto_frame: true,
+//!            on_load: ->(e) { puts e["bounds"] }, on_error: ->(e) { puts e["message"] })
+//! ```
+//!
+//! La scène glTF est chargée sur la couche du viewport. Son chargement est signalé
+//! à `on_load`, avec ses limites, ou son échec à `on_error` ; `auto_frame: true`
+//! recule la caméra, dans sa direction de visée, jusqu'à voir toute la scène.
+//!
+//! Le bloc d'un viewport déclare aussi des nœuds 3D, construits par le builder
+//! sous la racine du viewport et modifiables comme les autres composants :
+//!
+//! ```ruby
+//! viewport3d(id: "preview") do
+//!   group(id: "turntable", rotation: [0, 45, 0]) do
+//!     cube(id: "box", size: 1.5, color: "#4a90d9", metallic: 0.2, roughness: 0.6)
+//!     sphere(id: "ball", radius: 0.5, position: [0, 1.25, 0], color: "tomato")
+//!   end
+//!   plane(id: "floor", size: 10, position: [0, -0.75, 0], texture: "textures/grid.png")
+//!   point_light(id: "key", position: [3, 5, 3], intensity: 2000, color: "#ffe8c0")
+//! end
+//! ```
+//!
+//! La lumière par défaut du viewport s'éteint dès qu'il déclare ses propres lumières.
+use bevy::{
+    prelude::*,
+    asset::LoadState,
+    core_pipeline::clear_color::ClearColorConfig,
+    render::{
+        camera::RenderTarget,
+        primitives::Aabb,
+        render_resource::{Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages},
+        view::RenderLayers,
+    },
+    scene::SceneInstance,
+    window::PrimaryWindow,
+};
+use std::collections::BTreeSet;
+
+use crate::ui::color::parse_color;
+use crate::ui::components::{CameraProps, MaterialProps, RubyCallbackEvent, SceneNode, UIElement, Viewport3DProps};
+
+/// Couches de rendu libres pour les viewports ; la couche 0 reste celle de la scène principale
+#[derive(Resource)]
+pub struct ViewportLayers {
+    free: BTreeSet<u8>,
+}
+
+impl Default for ViewportLayers {
+    fn default() -> Self {
+        Self { free: (1..RenderLayers::TOTAL_LAYERS as u8).collect() }
+    }
+}
+
+/// Viewport 3D porté par le nœud d'UI ; sa caméra, sa lumière par défaut et sa
+/// scène glTF sont créées par `spawn_viewport_system`
+#[derive(Component, Debug)]
+pub struct Viewport3D {
+    texture: Handle<Image>,
+    camera: CameraProps,
+    /// Racine du contenu 3D, créée par le builder
+    root: Entity,
+    /// Caméra et lumière par défaut, une fois créées
+    content: Option<(Entity, Entity)>,
+    /// Scène glTF et son instance, une fois créée
+    scene: Option<ViewportScene>,
+}
+
+/// Scène glTF d'un viewport
+#[derive(Debug)]
+struct ViewportScene {
+    source: String,
+    handle: Handle<Scene>,
+    entity: Option<Entity>,
+    auto_frame: bool,
+    on_load: Option<String>,
+    on_error: Option<String>,
+    status: SceneStatus,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SceneStatus {
+    Loading,
+    Loaded,
+    Failed,
+}
+
+impl Viewport3D {
+    pub fn new(props: &Viewport3DProps, texture: Handle<Image>, root: Entity, scene: Option<Handle<Scene>>) -> Self {
+        let camera = props.camera.clone().unwrap_or(CameraProps { position: [0.0, 5.0, 10.0], target: [0.0, 0.0, 0.0] });
+        let scene = scene.zip(props.scene.clone()).map(|(handle, source)| ViewportScene {
+            source,
+            handle,
+            entity: None,
+            auto_frame: props.auto_frame.unwrap_or(false),
+            on_load: props.on_load.clone(),
+            on_error: props.on_error.clone(),
+            status: SceneStatus::Loading,
+        });
+        Self { texture, camera, root, content: None, scene }
+    }
+}
+
+/// Racine du contenu d'un viewport, détruite avec son nœud
+#[derive(Component, Debug)]
+pub struct ViewportContent {
+    pub viewport: Entity,
+    /// Parent des nœuds 3D déclarés dans le DSL
+    pub nodes: Entity,
+    /// Couche de rendu, attribuée par `assign_layer`
+    layer_index: Option<u8>,
+}
+
+impl ViewportContent {
+    pub fn new(viewport: Entity, nodes: Entity) -> Self {
+        Self { viewport, nodes, layer_index: None }
+    }
+
+    pub fn layer(&self) -> Option<RenderLayers> {
+        self.layer_index.map(RenderLayers::layer)
+    }
+}
+
+/// Attribue une couche libre à la racine d'un viewport ; ses descendants la
+/// reçoivent par `viewport_layers_system`
+pub fn assign_layer(world: &mut World, root: Entity) {
+    let Some(index) = world.resource_mut::<ViewportLayers>().free.pop_first() else {
+        warn!("Plus de couche de rendu libre : viewport 3D ignoré");
+        return;
+    };
+    let Some(mut entity) = world.get_entity_mut(root) else {
+        world.resource_mut::<ViewportLayers>().free.insert(index);
+        return;
+    };
+    if let Some(mut content) = entity.get_mut::<ViewportContent>() {
+        content.layer_index = Some(index);
+    }
+    entity.insert(RenderLayers::layer(index));
+}
+
+/// Rotation d'un nœud 3D, donnée en angles d'Euler XYZ en degrés
+pub fn euler_rotation([x, y, z]: [f32; 3]) -> Quat {
+    Quat::from_euler(EulerRot::XYZ, x.to_radians(), y.to_radians(), z.to_radians())
+}
+
+/// Applique les champs renseignés d'un matériau du DSL
+pub fn patch_material(material: &mut StandardMaterial, props: &MaterialProps, asset_server: &AssetServer) {
+    if let Some(color) = &props.color {
+        match parse_color(color) {
+            Ok(color) => material.base_color = color,
+            Err(reason) => warn!("Couleur de matériau `{}` invalide: {}", color, reason),
+        }
+    }
+    if let Some(metallic) = props.metallic {
+        material.metallic = metallic.clamp(0.0, 1.0);
+    }
+    if let Some(roughness) = props.roughness {
+        material.perceptual_roughness = roughness.clamp(0.0, 1.0);
+    }
+    if let Some(texture) = &props.texture {
+        material.base_color_texture = Some(asset_server.load(texture.clone()));
+    }
+}
+
+/// Image cible d'une caméra, à la taille donnée en pixels physiques
+pub fn render_target_image(size: UVec2) -> Image {
+    let size = Extent3d { width: size.x.max(1), height: size.y.max(1), depth_or_array_layers: 1 };
+    let mut image = Image {
+        texture_descriptor: TextureDescriptor {
+            label: Some("viewport3d"),
+            size,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::Bgra8UnormSrgb,
+            mip_level_count: 1,
+            sample_count: 1,
+            usage: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST | TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        },
+        ..default()
+    };
+    // Remplit les données à la taille de la texture
+    image.resize(size);
+    image
+}
+
+/// Crée la caméra, la lumière par défaut et la scène glTF des viewports ajoutés,
+/// sous leur racine
+pub fn spawn_viewport_system(
+    mut commands: Commands,
+    mut viewports: Query<&mut Viewport3D, Added<Viewport3D>>,
+    contents: Query<&ViewportContent>,
+) {
+    for mut viewport in &mut viewports {
+        // Sans couche libre, le viewport reste vide (voir `assign_layer`)
+        let Some(layer) = contents.get(viewport.root).ok().and_then(ViewportContent::layer) else {
+            continue;
+        };
+        let position = Vec3::from(viewport.camera.position);
+        let target = Vec3::from(viewport.camera.target);
+
+        // Rendue avant l'UI, sans l'UI, sur un fond transparent qui laisse voir celui du nœud
+        let camera = commands
+            .spawn((
+                Camera3dBundle {
+                    camera: Camera {
+                        target: RenderTarget::Image(viewport.texture.clone()),
+                        order: -1,
+                        ..default()
+                    },
+                    camera_3d: Camera3d {
+                        clear_color: ClearColorConfig::Custom(Color::NONE),
+                        ..default()
+                    },
+                    transform: Transform::from_translation(position).looking_at(target, Vec3::Y),
+                    ..default()
+                },
+                UiCameraConfig { show_ui: false },
+                layer,
+            ))
+            .id();
+
+        let light = commands
+            .spawn((
+                PointLightBundle {
+                    point_light: PointLight {
+                        intensity: 1500.0,
+                        shadows_enabled: true,
+                        ..default()
+                    },
+                    transform: Transform::from_xyz(4.0, 8.0, 4.0),
+                    ..default()
+                },
+                layer,
+            ))
+            .id();
+
+        let mut children = vec![camera, light];
+        // Les entités de la scène reçoivent la couche à leur apparition (voir `viewport_layers_system`)
+        if let Some(scene) = viewport.scene.as_mut() {
+            let entity = commands.spawn((SceneBundle { scene: scene.handle.clone(), ..default() }, layer)).id();
+            scene.entity = Some(entity);
+            children.push(entity);
+        }
+
+        commands.entity(viewport.root).push_children(&children);
+        viewport.content = Some((camera, light));
+    }
+}
+
+/// Ajuste l'image des viewports à la taille de leur nœud et suspend le rendu
+/// des viewports masqués ou sans taille
+pub fn resize_viewport_system(
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut images: ResMut<Assets<Image>>,
+    viewports: Query<(&Viewport3D, &Node, &InheritedVisibility)>,
+    mut cameras: Query<&mut Camera>,
+) {
+    let scale = windows.get_single().map_or(1.0, |window| window.scale_factor() as f32);
+
+    for (viewport, node, visibility) in &viewports {
+        let Some((camera, _)) = viewport.content else {
+            continue;
+        };
+        let Ok(mut camera) = cameras.get_mut(camera) else {
+            continue;
+        };
+        let size = (node.size() * scale).round().as_uvec2();
+        let active = visibility.get() && size.x > 0 && size.y > 0;
+        if camera.is_active != active {
+            camera.is_active = active;
+        }
+        if !active {
+            continue;
+        }
+
+        // La caméra suit la nouvelle taille de son image cible
+        let extent = Extent3d { width: size.x, height: size.y, depth_or_array_layers: 1 };
+        let resize = images.get(&viewport.texture).map_or(false, |image| image.texture_descriptor.size != extent);
+        if resize {
+            if let Some(image) = images.get_mut(&viewport.texture) {
+                image.resize(extent);
+            }
+        }
+    }
+}
+
+/// Détruit le contenu des viewports retirés de l'UI et libère leur couche
+pub fn despawn_viewport_system(
+    mut commands: Commands,
+    mut removed: RemovedComponents<Viewport3D>,
+    mut layers: ResMut<ViewportLayers>,
+    contents: Query<(Entity, &ViewportContent)>,
+) {
+    for viewport in removed.iter() {
+        for (root, content) in &contents {
+            if content.viewport == viewport {
+                commands.entity(root).despawn_recursive();
+                if let Some(index) = content.layer_index {
+                    layers.free.insert(index);
+                }
+            }
+        }
+    }
+}
+
+/// Place sur la couche de leur viewport les entités rattachées à son contenu et
+/// leurs descendants : nœuds 3D construits ou déplacés d'un viewport à l'autre,
+/// entités des scènes glTF, créées par Bevy sans couche
+pub fn viewport_layers_system(
+    mut commands: Commands,
+    attached: Query<(Entity, Option<&RenderLayers>), Changed<Parent>>,
+    parents: Query<&Parent>,
+    children: Query<&Children>,
+    contents: Query<&ViewportContent>,
+) {
+    for (entity, current) in &attached {
+        let Some(layer) = parents
+            .iter_ancestors(entity)
+            .find_map(|ancestor| contents.get(ancestor).ok())
+            .and_then(ViewportContent::layer)
+        else {
+            continue;
+        };
+        if current == Some(&layer) {
+            continue;
+        }
+        commands.entity(entity).insert(layer);
+        for descendant in children.iter_descendants(entity) {
+            commands.entity(descendant).insert(layer);
+        }
+    }
+}
+
+/// Éteint la lumière par défaut des viewports qui déclarent leurs propres lumières
+pub fn viewport_default_light_system(
+    viewports: Query<&Viewport3D>,
+    contents: Query<&ViewportContent>,
+    children: Query<&Children>,
+    lights: Query<(), (With<SceneNode>, Or<(With<PointLight>, With<DirectionalLight>)>)>,
+    mut visibilities: Query<&mut Visibility>,
+) {
+    for viewport in &viewports {
+        let (Some((_, light)), Ok(content)) = (viewport.content, contents.get(viewport.root)) else {
+            continue;
+        };
+        let lit = children.iter_descendants(content.nodes).any(|node| lights.contains(node));
+        let visibility = if lit { Visibility::Hidden } else { Visibility::Inherited };
+        if let Ok(mut current) = visibilities.get_mut(light) {
+            if *current != visibility {
+                *current = visibility;
+            }
+        }
+    }
+}
+
+/// Signale à Ruby le chargement ou l'échec des scènes glTF, et cadre la caméra
+/// des viewports en `auto_frame`
+#[allow(clippy::too_many_arguments)]
+pub fn viewport_scene_system(
+    asset_server: Res<AssetServer>,
+    scene_spawner: Res<SceneSpawner>,
+    mut scene_events: EventReader<AssetEvent<Scene>>,
+    mut viewports: Query<(&UIElement, &mut Viewport3D)>,
+    instances: Query<&SceneInstance>,
+    bounds: Query<(&Aabb, &GlobalTransform)>,
+    mut cameras: Query<(&mut Transform, &Projection)>,
+    mut ruby_callback_events: EventWriter<RubyCallbackEvent>,
+) {
+    // Une scène rechargée à chaud est de nouveau signalée une fois prête
+    let modified: Vec<AssetId<Scene>> = scene_events
+        .iter()
+        .filter_map(|event| match event {
+            AssetEvent::Modified { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+
+    for (element, mut viewport) in &mut viewports {
+        let camera = viewport.content.map(|(camera, _)| camera);
+        let Some(scene) = viewport.scene.as_mut() else {
+            continue;
+        };
+        if modified.contains(&scene.handle.id()) {
+            scene.status = SceneStatus::Loading;
+        }
+        if scene.status != SceneStatus::Loading {
+            continue;
+        }
+
+        if asset_server.get_load_state(scene.handle.id()) == Some(LoadState::Failed) {
+            scene.status = SceneStatus::Failed;
+            warn!("Viewport 3D `{}`: chargement de la scène `{}` impossible", element.id, scene.source);
+            if let Some(callback) = &scene.on_error {
+                ruby_callback_events.send(RubyCallbackEvent {
+                    callback: callback.clone(),
+                    arguments: serde_json::json!({
+                        "id": element.id,
+                        "event": "error",
+                        "scene": scene.source,
+                        "message": format!("chargement de la scène `{}` impossible", scene.source),
+                    }),
+                });
+            }
+            continue;
+        }
+
+        // La scène est prête quand son instance a été créée dans le monde
+        let Some(instance) = scene.entity.and_then(|entity| instances.get(entity).ok()) else {
+            continue;
+        };
+        if !scene_spawner.instance_is_ready(**instance) {
+            continue;
+        }
+        scene.status = SceneStatus::Loaded;
+
+        // Limites de la scène, dans le repère du viewport
+        let scene_bounds = scene_spawner
+            .iter_instance_entities(**instance)
+            .filter_map(|entity| bounds.get(entity).ok())
+            .map(|(aabb, transform)| world_bounds(aabb, transform))
+            .reduce(|(min_a, max_a), (min_b, max_b)| (min_a.min(min_b), max_a.max(max_b)));
+
+        if let (true, Some(bounds), Some(camera)) = (scene.auto_frame, scene_bounds, camera) {
+            if let Ok((mut transform, projection)) = cameras.get_mut(camera) {
+                frame_bounds(&mut transform, projection, bounds);
+            }
+        }
+
+        if let Some(callback) = &scene.on_load {
+            let mut arguments = serde_json::json!({
+                "id": element.id,
+                "event": "load",
+                "scene": scene.source,
+            });
+            if let Some((min, max)) = scene_bounds {
+                arguments["bounds"] = serde_json::json!({ "min": min.to_array(), "max": max.to_array() });
+            }
+            ruby_callback_events.send(RubyCallbackEvent { callback: callback.clone(), arguments });
+        }
+    }
+}
+
+/// Boîte englobante alignée sur les axes d'une boîte locale transformée
+fn world_bounds(aabb: &Aabb, transform: &GlobalTransform) -> (Vec3, Vec3) {
+    let (center, half) = (Vec3::from(aabb.center), Vec3::from(aabb.half_extents));
+    let matrix = transform.compute_matrix();
+    let corners = (0..8).map(|index| {
+        let sign = Vec3::new(
+            if index & 1 == 0 { -1.0 } else { 1.0 },
+            if index & 2 == 0 { -1.0 } else { 1.0 },
+            if index & 4 == 0 { -1.0 } else { 1.0 },
+        );
+        matrix.transform_point3(center + half * sign)
+    });
+    corners.fold((Vec3::MAX, Vec3::MIN), |(min, max), corner| (min.min(corner), max.max(corner)))
+}
+
+/// Recule la caméra dans sa direction de visée jusqu'à voir toute la sphère englobante
+fn frame_bounds(transform: &mut Transform, projection: &Projection, (min, max): (Vec3, Vec3)) {
+    let center = (min + max) / 2.0;
+    let radius = ((max - min) / 2.0).length().max(0.01);
+    let distance = match projection {
+        Projection::Perspective(perspective) => {
+            // Le champ le plus étroit, vertical ou horizontal, limite le cadrage
+            let vertical = perspective.fov / 2.0;
+            let horizontal = (vertical.tan() * perspective.aspect_ratio).atan();
+            radius / vertical.min(horizontal).sin()
+        },
+        Projection::Orthographic(_) => radius * 2.0,
+    };
+    let direction = transform.forward();
+    transform.translation = center - direction * distance;
+    transform.look_at(center, Vec3::Y);
+}